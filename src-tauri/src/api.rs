@@ -0,0 +1,813 @@
+//! Opt-in localhost HTTP + WebSocket API for scripts and editor integrations.
+//!
+//! Every request must carry the token stored in the app data dir as
+//! `Authorization: Bearer <token>`. Only the WebSocket upgrade may pass it as a `token` query
+//! parameter instead, since browsers cannot set headers on it. No CORS headers are sent, so
+//! web pages cannot read the responses.
+//!
+//! - `GET /api/dashboard` - DashboardData
+//! - `GET /api/sessions`, `GET /api/sessions/{session_id}` - SessionInfo
+//! - `GET /api/events?project_dir=&session_id=&event_types=a,b&since=&until=&offset=&limit=` - EventPage
+//! - `GET /api/git?project_dir=` - GitInfo
+//! - `GET /api/ws` - WebSocket pushing `{"event": "state-updated", "payload": DashboardData}`
+
+use base64::Engine;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{IpAddr, Ipv4Addr, SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::Manager;
+
+use crate::git_cache::local_git_info;
+use crate::history::{EventFilter, EventStore};
+use crate::settings::get_config_dir;
+use crate::state::AppState;
+
+/// GUID appended to Sec-WebSocket-Key (RFC 6455)
+const WEBSOCKET_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+/// Upper bound for request line + headers
+const MAX_HEADER_BYTES: usize = 16 * 1024;
+/// Upper bound for a frame sent by a WebSocket client
+const MAX_FRAME_BYTES: u64 = 64 * 1024;
+const ACCEPT_POLL_INTERVAL: Duration = Duration::from_millis(200);
+const READ_TIMEOUT: Duration = Duration::from_secs(10);
+const LOCK_ERROR: &str = "Failed to acquire state lock";
+
+/// Connection details shown to the user so they can configure their scripts
+#[derive(Debug, Clone, Serialize)]
+pub struct ApiInfo {
+    pub enabled: bool,
+    pub running: bool,
+    pub port: u16,
+    pub token: String,
+    pub base_url: String,
+}
+
+#[derive(Default)]
+struct ApiInner {
+    /// Set to stop the accept loop of the running server
+    stop: Option<Arc<AtomicBool>>,
+    port: u16,
+    /// Outgoing message queues of connected WebSocket clients
+    clients: Vec<Sender<String>>,
+}
+
+/// Local API server; at most one listener runs at a time
+#[derive(Default)]
+pub struct ApiServer {
+    inner: Mutex<ApiInner>,
+}
+
+struct ApiContext {
+    app: tauri::AppHandle,
+    state: Arc<Mutex<AppState>>,
+    server: Arc<ApiServer>,
+    token: String,
+}
+
+impl ApiServer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.inner
+            .lock()
+            .map(|inner| inner.stop.is_some())
+            .unwrap_or(false)
+    }
+
+    /// Start listening on 127.0.0.1:`port`, restarting if already running
+    pub fn start(
+        self: &Arc<Self>,
+        app: &tauri::AppHandle,
+        state: Arc<Mutex<AppState>>,
+        port: u16,
+    ) -> Result<(), String> {
+        self.stop();
+
+        let token = load_or_create_token(app, TOKEN_FILE)?;
+        let listener = bind_with_retry(IpAddr::V4(Ipv4Addr::LOCALHOST), port)?;
+        listener
+            .set_nonblocking(true)
+            .map_err(|e| format!("Failed to configure listener: {}", e))?;
+
+        let stop = Arc::new(AtomicBool::new(false));
+        {
+            let mut inner = self.inner.lock().map_err(|_| LOCK_ERROR)?;
+            inner.stop = Some(Arc::clone(&stop));
+            inner.port = port;
+        }
+
+        let ctx = Arc::new(ApiContext {
+            app: app.clone(),
+            state,
+            server: Arc::clone(self),
+            token,
+        });
+
+        log::info!(target: "eocc.api", "Local API listening on 127.0.0.1:{}", port);
+        std::thread::spawn(move || {
+            while !stop.load(Ordering::Relaxed) {
+                match listener.accept() {
+                    Ok((stream, _)) => {
+                        let ctx = Arc::clone(&ctx);
+                        std::thread::spawn(move || handle_connection(&ctx, stream));
+                    }
+                    Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => {
+                        std::thread::sleep(ACCEPT_POLL_INTERVAL);
+                    }
+                    Err(e) => {
+                        log::warn!(target: "eocc.api", "Failed to accept connection: {:?}", e);
+                        std::thread::sleep(ACCEPT_POLL_INTERVAL);
+                    }
+                }
+            }
+            log::info!(target: "eocc.api", "Local API stopped");
+        });
+
+        Ok(())
+    }
+
+    /// Stop the listener and disconnect WebSocket clients
+    pub fn stop(&self) {
+        if let Ok(mut inner) = self.inner.lock() {
+            if let Some(stop) = inner.stop.take() {
+                stop.store(true, Ordering::Relaxed);
+            }
+            // Dropping the senders ends each client's writer loop
+            inner.clients.clear();
+        }
+    }
+
+    /// Push an event to all connected WebSocket clients
+    pub fn broadcast<T: Serialize>(&self, event: &str, payload: &T) {
+        let Ok(mut inner) = self.inner.lock() else {
+            return;
+        };
+        if inner.clients.is_empty() {
+            return;
+        }
+        let Ok(message) = serde_json::to_string(&serde_json::json!({
+            "event": event,
+            "payload": payload,
+        })) else {
+            return;
+        };
+        inner
+            .clients
+            .retain(|client| client.send(message.clone()).is_ok());
+    }
+
+    fn register_client(&self, client: Sender<String>) {
+        if let Ok(mut inner) = self.inner.lock() {
+            inner.clients.push(client);
+        }
+    }
+
+    pub fn info(&self, app: &tauri::AppHandle, enabled: bool, port: u16) -> ApiInfo {
+        let running_port = self
+            .inner
+            .lock()
+            .ok()
+            .filter(|inner| inner.stop.is_some())
+            .map(|inner| inner.port);
+        let port = running_port.unwrap_or(port);
+        ApiInfo {
+            enabled,
+            running: running_port.is_some(),
+            port,
+            token: load_or_create_token(app, TOKEN_FILE).unwrap_or_default(),
+            base_url: format!("http://127.0.0.1:{}/api", port),
+        }
+    }
+}
+
+/// Bind the port, retrying briefly while a just-stopped listener releases it
+pub fn bind_with_retry(ip: IpAddr, port: u16) -> Result<TcpListener, String> {
+    let addr = SocketAddr::from((ip, port));
+    let mut attempts = 0;
+    loop {
+        match TcpListener::bind(addr) {
+            Ok(listener) => return Ok(listener),
+            Err(e) if e.kind() == std::io::ErrorKind::AddrInUse && attempts < 5 => {
+                attempts += 1;
+                std::thread::sleep(ACCEPT_POLL_INTERVAL);
+            }
+            Err(e) => return Err(format!("Failed to bind {}: {}", addr, e)),
+        }
+    }
+}
+
+// ============================================================================
+// Token
+// ============================================================================
+
+/// Token file of the local API
+pub const TOKEN_FILE: &str = "api_token";
+
+fn get_token_file(app: &tauri::AppHandle, name: &str) -> Result<std::path::PathBuf, String> {
+    get_config_dir(app).map(|dir| dir.join(name))
+}
+
+fn generate_token() -> Result<String, String> {
+    let mut bytes = [0u8; 32];
+    getrandom::getrandom(&mut bytes).map_err(|e| format!("Failed to generate token: {}", e))?;
+    Ok(bytes.iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+fn write_token(path: &std::path::Path, token: &str) -> Result<(), String> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| format!("Failed to create app data dir: {}", e))?;
+    }
+    fs::write(path, token).map_err(|e| format!("Failed to write token: {}", e))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let _ = fs::set_permissions(path, fs::Permissions::from_mode(0o600));
+    }
+    Ok(())
+}
+
+/// Read a token (`TOKEN_FILE` or `remote::TOKEN_FILE`), creating one on first use
+pub fn load_or_create_token(app: &tauri::AppHandle, name: &str) -> Result<String, String> {
+    let path = get_token_file(app, name)?;
+    if let Ok(token) = fs::read_to_string(&path) {
+        let token = token.trim().to_string();
+        if !token.is_empty() {
+            return Ok(token);
+        }
+    }
+    let token = generate_token()?;
+    write_token(&path, &token)?;
+    Ok(token)
+}
+
+/// Replace a token; the caller restarts the server so it takes effect
+pub fn regenerate_token(app: &tauri::AppHandle, name: &str) -> Result<String, String> {
+    let token = generate_token()?;
+    write_token(&get_token_file(app, name)?, &token)?;
+    Ok(token)
+}
+
+/// Compare tokens without short-circuiting on the first differing byte
+pub fn token_matches(expected: &str, given: &str) -> bool {
+    expected.len() == given.len()
+        && expected
+            .bytes()
+            .zip(given.bytes())
+            .fold(0u8, |acc, (a, b)| acc | (a ^ b))
+            == 0
+}
+
+// ============================================================================
+// HTTP
+// ============================================================================
+
+struct Request {
+    method: String,
+    path: String,
+    query: HashMap<String, String>,
+    /// Header names are lowercased
+    headers: HashMap<String, String>,
+}
+
+fn parse_query(query: &str) -> HashMap<String, String> {
+    query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            let decode = |s: &str| {
+                urlencoding::decode(&s.replace('+', " "))
+                    .map(|d| d.into_owned())
+                    .unwrap_or_default()
+            };
+            (decode(key), decode(value))
+        })
+        .collect()
+}
+
+fn read_request(reader: &mut impl BufRead) -> Option<Request> {
+    let mut read = 0;
+    let mut request_line = String::new();
+    read += reader.read_line(&mut request_line).ok()?;
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next()?.to_string();
+    let target = parts.next()?;
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+
+    let mut headers = HashMap::new();
+    loop {
+        let mut line = String::new();
+        let n = reader.read_line(&mut line).ok()?;
+        read += n;
+        if n == 0 || read > MAX_HEADER_BYTES {
+            return None;
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            headers.insert(name.trim().to_ascii_lowercase(), value.trim().to_string());
+        }
+    }
+
+    Some(Request {
+        method,
+        path: path.to_string(),
+        query: parse_query(query),
+        headers,
+    })
+}
+
+fn status_text(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        503 => "Service Unavailable",
+        _ => "Internal Server Error",
+    }
+}
+
+fn write_response(stream: &mut TcpStream, status: u16, body: &str) {
+    let response = format!(
+        "HTTP/1.1 {} {}\r\n\
+         Content-Type: application/json\r\n\
+         Content-Length: {}\r\n\
+         Connection: close\r\n\r\n{}",
+        status,
+        status_text(status),
+        body.len(),
+        body
+    );
+    let _ = stream.write_all(response.as_bytes());
+}
+
+fn write_json<T: Serialize>(stream: &mut TcpStream, value: &T) {
+    match serde_json::to_string(value) {
+        Ok(body) => write_response(stream, 200, &body),
+        Err(e) => write_error(stream, 500, &e.to_string()),
+    }
+}
+
+fn write_error(stream: &mut TcpStream, status: u16, message: &str) {
+    let body = serde_json::json!({ "error": message }).to_string();
+    write_response(stream, status, &body);
+}
+
+fn is_authorized(token: &str, request: &Request) -> bool {
+    let bearer = request
+        .headers
+        .get("authorization")
+        .and_then(|value| value.strip_prefix("Bearer "));
+    // Query strings end up in logs and history, so only the WebSocket upgrade may use one
+    let query_token = request
+        .query
+        .get("token")
+        .filter(|_| request.path.trim_matches('/') == "api/ws");
+    bearer
+        .or(query_token.map(String::as_str))
+        .is_some_and(|given| token_matches(token, given.trim()))
+}
+
+fn handle_connection(ctx: &ApiContext, mut stream: TcpStream) {
+    let _ = stream.set_nonblocking(false);
+    let _ = stream.set_read_timeout(Some(READ_TIMEOUT));
+    let Ok(read_half) = stream.try_clone() else {
+        return;
+    };
+    let mut reader = BufReader::new(read_half);
+    let Some(request) = read_request(&mut reader) else {
+        write_error(&mut stream, 400, "Malformed request");
+        return;
+    };
+
+    if request.method != "GET" {
+        write_error(&mut stream, 405, "Only GET is supported");
+        return;
+    }
+    if !is_authorized(&ctx.token, &request) {
+        write_error(&mut stream, 401, "Missing or invalid token");
+        return;
+    }
+
+    let segments: Vec<&str> = request
+        .path
+        .trim_matches('/')
+        .split('/')
+        .filter(|s| !s.is_empty())
+        .collect();
+
+    match segments.as_slice() {
+        ["api", "dashboard"] => match ctx.state.lock() {
+            Ok(state) => write_json(&mut stream, &state.to_dashboard_data()),
+            Err(_) => write_error(&mut stream, 500, LOCK_ERROR),
+        },
+        ["api", "sessions"] => match ctx.state.lock() {
+            Ok(state) => write_json(&mut stream, &state.to_dashboard_data().sessions),
+            Err(_) => write_error(&mut stream, 500, LOCK_ERROR),
+        },
+        ["api", "sessions", session_id] => {
+            let session_id = urlencoding::decode(session_id)
+                .map(|s| s.into_owned())
+                .unwrap_or_default();
+            let session = match ctx.state.lock() {
+                Ok(state) => state.sessions.get(&session_id).cloned(),
+                Err(_) => return write_error(&mut stream, 500, LOCK_ERROR),
+            };
+            match session {
+                Some(session) => write_json(&mut stream, &session),
+                None => write_error(&mut stream, 404, "Session not found"),
+            }
+        }
+        ["api", "events"] => handle_events(ctx, &request, &mut stream),
+        ["api", "git"] => match request.query.get("project_dir") {
+            Some(project_dir) if !project_dir.is_empty() => {
+                write_json(&mut stream, &local_git_info(&ctx.app, project_dir))
+            }
+            _ => write_error(&mut stream, 400, "project_dir is required"),
+        },
+        ["api", "ws"] => handle_websocket(ctx, &request, stream, reader),
+        _ => write_error(&mut stream, 404, "Not found"),
+    }
+}
+
+fn handle_events(ctx: &ApiContext, request: &Request, stream: &mut TcpStream) {
+    let Some(history) = ctx.app.try_state::<Arc<EventStore>>() else {
+        write_error(stream, 503, "Event history is not available");
+        return;
+    };
+
+    let query = &request.query;
+    let non_empty = |key: &str| query.get(key).filter(|v| !v.is_empty()).cloned();
+    let event_types = match non_empty("event_types") {
+        Some(types) => {
+            let parsed: Result<Vec<_>, _> = types
+                .split(',')
+                .map(|t| serde_json::from_value(serde_json::Value::String(t.trim().to_string())))
+                .collect();
+            match parsed {
+                Ok(parsed) => Some(parsed),
+                Err(e) => return write_error(stream, 400, &format!("Invalid event_types: {}", e)),
+            }
+        }
+        None => None,
+    };
+    let filter = EventFilter {
+        project_dir: non_empty("project_dir"),
+        session_id: non_empty("session_id"),
+        event_types,
+        since: non_empty("since"),
+        until: non_empty("until"),
+    };
+    let offset = query
+        .get("offset")
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(0);
+    let limit = query
+        .get("limit")
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(100);
+
+    write_json(stream, &history.query(&filter, offset, limit));
+}
+
+// ============================================================================
+// WebSocket
+// ============================================================================
+
+const OPCODE_TEXT: u8 = 0x1;
+const OPCODE_CLOSE: u8 = 0x8;
+const OPCODE_PING: u8 = 0x9;
+const OPCODE_PONG: u8 = 0xA;
+
+fn write_frame(stream: &mut impl Write, opcode: u8, payload: &[u8]) -> std::io::Result<()> {
+    let mut frame = vec![0x80 | opcode];
+    match payload.len() {
+        len if len < 126 => frame.push(len as u8),
+        len if len <= u16::MAX as usize => {
+            frame.push(126);
+            frame.extend_from_slice(&(len as u16).to_be_bytes());
+        }
+        len => {
+            frame.push(127);
+            frame.extend_from_slice(&(len as u64).to_be_bytes());
+        }
+    }
+    frame.extend_from_slice(payload);
+    stream.write_all(&frame)
+}
+
+/// Read one client frame, returning its opcode and unmasked payload
+fn read_frame(reader: &mut impl Read) -> std::io::Result<(u8, Vec<u8>)> {
+    let mut header = [0u8; 2];
+    reader.read_exact(&mut header)?;
+    let opcode = header[0] & 0x0F;
+    let masked = header[1] & 0x80 != 0;
+    let len = match header[1] & 0x7F {
+        126 => {
+            let mut buf = [0u8; 2];
+            reader.read_exact(&mut buf)?;
+            u64::from(u16::from_be_bytes(buf))
+        }
+        127 => {
+            let mut buf = [0u8; 8];
+            reader.read_exact(&mut buf)?;
+            u64::from_be_bytes(buf)
+        }
+        len => u64::from(len),
+    };
+    if len > MAX_FRAME_BYTES {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            "WebSocket frame too large",
+        ));
+    }
+
+    let mut mask = [0u8; 4];
+    if masked {
+        reader.read_exact(&mut mask)?;
+    }
+    let mut payload = vec![0u8; len as usize];
+    reader.read_exact(&mut payload)?;
+    if masked {
+        for (i, byte) in payload.iter_mut().enumerate() {
+            *byte ^= mask[i % 4];
+        }
+    }
+    Ok((opcode, payload))
+}
+
+fn handle_websocket(
+    ctx: &ApiContext,
+    request: &Request,
+    mut stream: TcpStream,
+    mut reader: BufReader<TcpStream>,
+) {
+    let is_upgrade = request
+        .headers
+        .get("upgrade")
+        .is_some_and(|v| v.eq_ignore_ascii_case("websocket"));
+    let Some(key) = request
+        .headers
+        .get("sec-websocket-key")
+        .filter(|_| is_upgrade)
+    else {
+        write_error(&mut stream, 400, "Expected a WebSocket upgrade");
+        return;
+    };
+
+    let accept = websocket_accept(key);
+    let handshake = format!(
+        "HTTP/1.1 101 Switching Protocols\r\n\
+         Upgrade: websocket\r\n\
+         Connection: Upgrade\r\n\
+         Sec-WebSocket-Accept: {}\r\n\r\n",
+        accept
+    );
+    if stream.write_all(handshake.as_bytes()).is_err() {
+        return;
+    }
+
+    // Clients stay connected indefinitely; only the push side uses a timeout
+    let _ = reader.get_ref().set_read_timeout(None);
+    let writer = Arc::new(Mutex::new(stream));
+
+    // Reader: answer pings and stop on close
+    let reader_writer = Arc::clone(&writer);
+    std::thread::spawn(move || loop {
+        match read_frame(&mut reader) {
+            Ok((OPCODE_PING, payload)) => {
+                if let Ok(mut stream) = reader_writer.lock() {
+                    let _ = write_frame(&mut *stream, OPCODE_PONG, &payload);
+                }
+            }
+            Ok((OPCODE_CLOSE, _)) | Err(_) => {
+                if let Ok(mut stream) = reader_writer.lock() {
+                    let _ = write_frame(&mut *stream, OPCODE_CLOSE, &[]);
+                    let _ = stream.shutdown(std::net::Shutdown::Both);
+                }
+                break;
+            }
+            Ok(_) => {}
+        }
+    });
+
+    let (tx, rx) = mpsc::channel::<String>();
+    // Send the current state right away so clients need no separate fetch
+    if let Ok(state) = ctx.state.lock() {
+        let initial = serde_json::json!({
+            "event": "state-updated",
+            "payload": state.to_dashboard_data(),
+        });
+        let _ = tx.send(initial.to_string());
+    }
+    ctx.server.register_client(tx);
+
+    for message in rx {
+        let Ok(mut stream) = writer.lock() else {
+            break;
+        };
+        if write_frame(&mut *stream, OPCODE_TEXT, message.as_bytes()).is_err() {
+            break;
+        }
+    }
+    if let Ok(stream) = writer.lock() {
+        let _ = stream.shutdown(std::net::Shutdown::Both);
+    };
+}
+
+/// Sec-WebSocket-Accept value for a client's Sec-WebSocket-Key
+fn websocket_accept(key: &str) -> String {
+    base64::engine::general_purpose::STANDARD
+        .encode(sha1(format!("{}{}", key, WEBSOCKET_GUID).as_bytes()))
+}
+
+/// SHA-1 digest, only used for the WebSocket handshake
+fn sha1(data: &[u8]) -> [u8; 20] {
+    let mut h: [u32; 5] = [
+        0x6745_2301,
+        0xEFCD_AB89,
+        0x98BA_DCFE,
+        0x1032_5476,
+        0xC3D2_E1F0,
+    ];
+    let mut message = data.to_vec();
+    message.push(0x80);
+    while message.len() % 64 != 56 {
+        message.push(0);
+    }
+    message.extend_from_slice(&((data.len() as u64) * 8).to_be_bytes());
+
+    for block in message.chunks_exact(64) {
+        let mut w = [0u32; 80];
+        for (word, bytes) in w.iter_mut().zip(block.chunks_exact(4)) {
+            *word = u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+        }
+        for i in 16..80 {
+            w[i] = (w[i - 3] ^ w[i - 8] ^ w[i - 14] ^ w[i - 16]).rotate_left(1);
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e] = h;
+        for (i, word) in w.iter().enumerate() {
+            let (f, k) = match i {
+                0..=19 => ((b & c) | (!b & d), 0x5A82_7999),
+                20..=39 => (b ^ c ^ d, 0x6ED9_EBA1),
+                40..=59 => ((b & c) | (b & d) | (c & d), 0x8F1B_BCDC),
+                _ => (b ^ c ^ d, 0xCA62_C1D6),
+            };
+            let temp = a
+                .rotate_left(5)
+                .wrapping_add(f)
+                .wrapping_add(e)
+                .wrapping_add(k)
+                .wrapping_add(*word);
+            e = d;
+            d = c;
+            c = b.rotate_left(30);
+            b = a;
+            a = temp;
+        }
+        for (state, value) in h.iter_mut().zip([a, b, c, d, e]) {
+            *state = state.wrapping_add(value);
+        }
+    }
+
+    let mut digest = [0u8; 20];
+    for (bytes, word) in digest.chunks_exact_mut(4).zip(h) {
+        bytes.copy_from_slice(&word.to_be_bytes());
+    }
+    digest
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+
+    #[test]
+    fn sha1_matches_test_vectors() {
+        assert_eq!(hex(&sha1(b"")), "da39a3ee5e6b4b0d3255bfef95601890afd80709");
+        assert_eq!(
+            hex(&sha1(b"abc")),
+            "a9993e364706816aba3e25717850c26c9cd0d89d"
+        );
+        assert_eq!(
+            hex(&sha1(
+                b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq"
+            )),
+            "84983e441c3bd26ebaae4aa1f95129e5e54670f1"
+        );
+        assert_eq!(
+            hex(&sha1(&vec![b'a'; 1_000_000])),
+            "34aa973cd4c4daa4f61eeb2bdbad27316534016f"
+        );
+    }
+
+    #[test]
+    fn websocket_accept_matches_rfc_6455_example() {
+        assert_eq!(
+            websocket_accept("dGhlIHNhbXBsZSBub25jZQ=="),
+            "s3pPLMBiTxaQ9kYGzzhZRbK+xOo="
+        );
+    }
+
+    #[test]
+    fn read_frame_unmasks_client_frames() {
+        // RFC 6455 section 5.7: a masked "Hello"
+        let frame = [
+            0x81, 0x85, 0x37, 0xfa, 0x21, 0x3d, 0x7f, 0x9f, 0x4d, 0x51, 0x58,
+        ];
+        let (opcode, payload) = read_frame(&mut &frame[..]).unwrap();
+        assert_eq!(opcode, OPCODE_TEXT);
+        assert_eq!(payload, b"Hello");
+    }
+
+    #[test]
+    fn read_frame_reads_extended_lengths() {
+        let payload: Vec<u8> = (0..300u32).map(|i| i as u8).collect();
+
+        let mut frame = vec![0x82, 0x80 | 126];
+        frame.extend_from_slice(&300u16.to_be_bytes());
+        let mask = [1, 2, 3, 4];
+        frame.extend_from_slice(&mask);
+        frame.extend(payload.iter().enumerate().map(|(i, b)| b ^ mask[i % 4]));
+        assert_eq!(read_frame(&mut &frame[..]).unwrap(), (0x2, payload.clone()));
+
+        let mut frame = vec![0x82, 127];
+        frame.extend_from_slice(&300u64.to_be_bytes());
+        frame.extend_from_slice(&payload);
+        assert_eq!(read_frame(&mut &frame[..]).unwrap(), (0x2, payload.clone()));
+
+        let mut written = Vec::new();
+        write_frame(&mut written, OPCODE_TEXT, &payload).unwrap();
+        assert_eq!(&written[..4], &[0x81, 126, 0x01, 0x2c]);
+        assert_eq!(read_frame(&mut &written[..]).unwrap().1, payload);
+    }
+
+    #[test]
+    fn read_frame_rejects_oversized_frames() {
+        let mut frame = vec![0x82, 127];
+        frame.extend_from_slice(&(MAX_FRAME_BYTES + 1).to_be_bytes());
+        let err = read_frame(&mut &frame[..]).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn read_request_parses_target_and_headers() {
+        let raw = "GET /api/events?session_id=a%20b&event_types=stop+x HTTP/1.1\r\n\
+                   Authorization: Bearer abc\r\n\r\n";
+        let request = read_request(&mut raw.as_bytes()).unwrap();
+        assert_eq!(request.method, "GET");
+        assert_eq!(request.path, "/api/events");
+        assert_eq!(request.query["session_id"], "a b");
+        assert_eq!(request.query["event_types"], "stop x");
+        assert_eq!(request.headers["authorization"], "Bearer abc");
+    }
+
+    #[test]
+    fn query_token_is_only_accepted_on_the_websocket() {
+        let request = |target: &str| {
+            let raw = format!("GET {} HTTP/1.1\r\n\r\n", target);
+            read_request(&mut raw.as_bytes()).unwrap()
+        };
+        assert!(is_authorized("secret", &request("/api/ws?token=secret")));
+        assert!(!is_authorized("secret", &request("/api/ws?token=wrong")));
+        assert!(!is_authorized(
+            "secret",
+            &request("/api/sessions?token=secret")
+        ));
+
+        let raw = "GET /api/sessions HTTP/1.1\r\nAuthorization: Bearer secret\r\n\r\n";
+        assert!(is_authorized(
+            "secret",
+            &read_request(&mut raw.as_bytes()).unwrap()
+        ));
+    }
+
+    #[test]
+    fn read_request_rejects_malformed_requests() {
+        assert!(read_request(&mut "\r\n\r\n".as_bytes()).is_none());
+        assert!(read_request(&mut "GET\r\n\r\n".as_bytes()).is_none());
+        // Headers never terminated
+        assert!(read_request(&mut "GET / HTTP/1.1\r\nHost: x\r\n".as_bytes()).is_none());
+        let oversized = format!(
+            "GET / HTTP/1.1\r\nX: {}\r\n\r\n",
+            "a".repeat(MAX_HEADER_BYTES)
+        );
+        assert!(read_request(&mut oversized.as_bytes()).is_none());
+    }
+}
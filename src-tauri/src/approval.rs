@@ -0,0 +1,196 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::mpsc::{self, Receiver, Sender};
+
+use crate::events::describe_tool_call;
+use crate::state::{AppState, EventInfo, EventType, SessionStatus};
+
+/// Answer to a blocking permission hook
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "decision", rename_all = "snake_case")]
+pub enum ApprovalDecision {
+    Allow,
+    Deny {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        message: Option<String>,
+    },
+    /// No decision: Claude Code falls back to its own terminal prompt
+    Ask,
+}
+
+/// A tool call waiting for Allow/Deny from the dashboard or tray
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApprovalRequest {
+    pub id: String,
+    pub session_id: String,
+    pub project_name: String,
+    pub project_dir: String,
+    /// `permission_request` or `pre_tool_use`
+    pub hook_event: EventType,
+    pub tool_name: String,
+    pub tool_input: serde_json::Value,
+    pub requested_at: String,
+}
+
+struct PendingApproval {
+    request: ApprovalRequest,
+    responder: Sender<ApprovalDecision>,
+}
+
+/// Pending approvals; each holds the channel its hook connection is waiting on
+#[derive(Default)]
+pub struct ApprovalQueue {
+    pending: HashMap<String, PendingApproval>,
+    next_id: u64,
+}
+
+impl ApprovalQueue {
+    /// Queue an approval for `event` and return the receiver the hook connection waits on
+    pub fn add(&mut self, event: &EventInfo) -> (String, Receiver<ApprovalDecision>) {
+        self.next_id += 1;
+        let id = format!("{}-{}", event.session_key(), self.next_id);
+        let (tx, rx) = mpsc::channel();
+        let request = ApprovalRequest {
+            id: id.clone(),
+            session_id: event.session_key(),
+            project_name: event.project_name.clone(),
+            project_dir: event.project_dir.clone(),
+            hook_event: event.event_type.clone(),
+            tool_name: event.tool_name.clone(),
+            tool_input: event.tool_input.clone(),
+            requested_at: event.timestamp.clone(),
+        };
+        self.pending.insert(
+            id.clone(),
+            PendingApproval {
+                request,
+                responder: tx,
+            },
+        );
+        (id, rx)
+    }
+
+    /// Deliver a decision. Returns false if the request is gone (answered or timed out).
+    pub fn resolve(&mut self, id: &str, decision: ApprovalDecision) -> bool {
+        match self.pending.remove(id) {
+            Some(pending) => pending.responder.send(decision).is_ok(),
+            None => false,
+        }
+    }
+
+    /// Drop a request without answering (its hook already gave up)
+    pub fn remove(&mut self, id: &str) -> bool {
+        self.pending.remove(id).is_some()
+    }
+
+    /// Release all requests of a session, e.g. when it was answered in the terminal
+    pub fn cancel_session(&mut self, session_id: &str) -> bool {
+        let ids: Vec<String> = self
+            .pending
+            .values()
+            .filter(|p| p.request.session_id == session_id)
+            .map(|p| p.request.id.clone())
+            .collect();
+        for id in &ids {
+            self.resolve(id, ApprovalDecision::Ask);
+        }
+        !ids.is_empty()
+    }
+
+    pub fn cancel_all(&mut self) {
+        for (_, pending) in self.pending.drain() {
+            let _ = pending.responder.send(ApprovalDecision::Ask);
+        }
+    }
+
+    pub fn session_of(&self, id: &str) -> Option<String> {
+        self.pending.get(id).map(|p| p.request.session_id.clone())
+    }
+
+    pub fn has_session(&self, session_id: &str) -> bool {
+        self.pending
+            .values()
+            .any(|p| p.request.session_id == session_id)
+    }
+
+    /// Pending requests, oldest first
+    pub fn list(&self) -> Vec<ApprovalRequest> {
+        let mut requests: Vec<ApprovalRequest> =
+            self.pending.values().map(|p| p.request.clone()).collect();
+        requests.sort_by(|a, b| a.requested_at.cmp(&b.requested_at));
+        requests
+    }
+}
+
+/// Queue an approval for a blocking hook event and show its session as waiting
+pub fn begin_approval(
+    state: &mut AppState,
+    event: &EventInfo,
+) -> (String, Receiver<ApprovalDecision>) {
+    let key = event.session_key();
+    let (id, rx) = state.approvals.add(event);
+    if let Some(session) = state.sessions.get_mut(&key) {
+        if session.status != SessionStatus::WaitingPermission {
+            session.status = SessionStatus::WaitingPermission;
+            session.waiting_for = describe_tool_call(&event.tool_name, &event.tool_input);
+            state
+                .timelines
+                .entry(key)
+                .or_default()
+                .transition(&SessionStatus::WaitingPermission, &event.timestamp);
+        }
+    }
+    (id, rx)
+}
+
+/// Answer a pending approval and, once nothing else is pending, mark its session active again.
+/// Returns false if the request is no longer pending.
+pub fn resolve_approval(state: &mut AppState, id: &str, decision: ApprovalDecision) -> bool {
+    let session_id = state.approvals.session_of(id);
+    if !state.approvals.resolve(id, decision) {
+        return false;
+    }
+    if let Some(session_id) = session_id {
+        release_session(state, &session_id);
+    }
+    true
+}
+
+/// Mark a session waiting on permission active again, unless other approvals are still pending
+pub fn release_session(state: &mut AppState, session_id: &str) {
+    if state.approvals.has_session(session_id) {
+        return;
+    }
+    if let Some(session) = state.sessions.get_mut(session_id) {
+        if session.status == SessionStatus::WaitingPermission {
+            let now = now_timestamp();
+            session.status = SessionStatus::Active;
+            session.waiting_for.clear();
+            state
+                .timelines
+                .entry(session_id.to_string())
+                .or_default()
+                .transition(&SessionStatus::Active, &now);
+        }
+    }
+}
+
+/// History record of how a tool call was answered, e.g. "allow (policy rule 2)"
+pub fn decision_event(event: &EventInfo, decision: &ApprovalDecision, source: &str) -> EventInfo {
+    let outcome = match decision {
+        ApprovalDecision::Allow => "allow".to_string(),
+        ApprovalDecision::Deny { message: Some(m) } => format!("deny: {}", m),
+        ApprovalDecision::Deny { message: None } => "deny".to_string(),
+        ApprovalDecision::Ask => "ask".to_string(),
+    };
+    EventInfo {
+        timestamp: now_timestamp(),
+        event_type: EventType::ApprovalDecision,
+        message: format!("{} ({})", outcome, source),
+        ..event.clone()
+    }
+}
+
+fn now_timestamp() -> String {
+    chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true)
+}
@@ -0,0 +1,505 @@
+//! `eocc` - command-line client for Eyes on Claude Code.
+//!
+//! Talks to the running app over `~/.eocc/eocc.sock`. When the app is not running,
+//! reads (and for `clear`/`remove`, edits) `runtime_state.json` in the app data dir.
+
+use serde::Deserialize;
+use serde_json::Value;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+/// Must match `identifier` in tauri.conf.json
+const APP_IDENTIFIER: &str = "tech.joe-re.eocc";
+
+const USAGE: &str = "\
+Usage: eocc <command> [options]
+
+Commands:
+  status [--json]                    One-line summary of waiting sessions (for prompts / tmux)
+  sessions [--json]                  List sessions
+  events [--json] [--limit N] [--follow]
+                                     Show recent events; --follow streams new ones
+  clear                              Remove all sessions
+  remove <session>                   Remove a session (full id or unique prefix)
+";
+
+#[derive(Debug, Clone, Deserialize)]
+struct Session {
+    #[serde(default)]
+    session_id: String,
+    #[serde(default)]
+    project_name: String,
+    #[serde(default)]
+    status: String,
+    #[serde(default)]
+    last_event: String,
+    #[serde(default)]
+    waiting_for: String,
+    #[serde(default)]
+    alias: String,
+}
+
+impl Session {
+    /// Alias if set, otherwise the project name
+    fn name(&self) -> &str {
+        if self.alias.is_empty() {
+            &self.project_name
+        } else {
+            &self.alias
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct Event {
+    #[serde(default)]
+    timestamp: String,
+    #[serde(default)]
+    event: String,
+    #[serde(default)]
+    project_name: String,
+    #[serde(default)]
+    session_id: String,
+    #[serde(default)]
+    notification_type: String,
+    #[serde(default)]
+    tool_name: String,
+    #[serde(default)]
+    message: String,
+}
+
+/// Sessions and recent events, as raw JSON so `--json` passes through every field
+struct Snapshot {
+    sessions: Vec<Value>,
+    events: Vec<Value>,
+}
+
+fn status_emoji(status: &str) -> &'static str {
+    match status {
+        "Active" => "🟢",
+        "WaitingPermission" => "🔐",
+        "WaitingInput" => "⏳",
+        "Completed" => "✅",
+        "Disconnected" => "🔌",
+        _ => "❔",
+    }
+}
+
+fn short_id(session_id: &str) -> &str {
+    session_id.get(..8).unwrap_or(session_id)
+}
+
+// ============================================================================
+// Running app (Unix socket)
+// ============================================================================
+
+fn socket_path() -> Option<PathBuf> {
+    dirs::home_dir().map(|home| home.join(".eocc").join("eocc.sock"))
+}
+
+#[cfg(unix)]
+mod app {
+    use serde_json::Value;
+    use std::io::{BufRead, BufReader, Write};
+    use std::os::unix::net::UnixStream;
+    use std::time::Duration;
+
+    pub struct Connection {
+        reader: BufReader<UnixStream>,
+        writer: UnixStream,
+    }
+
+    impl Connection {
+        /// Connect to the running app; None if it is not listening
+        pub fn open() -> Option<Self> {
+            let stream = UnixStream::connect(super::socket_path()?).ok()?;
+            let _ = stream.set_read_timeout(Some(Duration::from_secs(5)));
+            let writer = stream.try_clone().ok()?;
+            Some(Self {
+                reader: BufReader::new(stream),
+                writer,
+            })
+        }
+
+        /// Send a control request and return its `data`
+        pub fn request(&mut self, request: Value) -> Result<Value, String> {
+            let mut line = request.to_string();
+            line.push('\n');
+            self.writer
+                .write_all(line.as_bytes())
+                .map_err(|e| format!("Failed to send request: {}", e))?;
+
+            let response = self.read_line()?.ok_or("The app closed the connection")?;
+            if response.get("ok").and_then(Value::as_bool) == Some(true) {
+                Ok(response.get("data").cloned().unwrap_or(Value::Null))
+            } else {
+                Err(response
+                    .get("error")
+                    .and_then(Value::as_str)
+                    .unwrap_or("Request failed")
+                    .to_string())
+            }
+        }
+
+        /// Read one JSON line; None at end of stream
+        pub fn read_line(&mut self) -> Result<Option<Value>, String> {
+            let mut line = String::new();
+            let n = self
+                .reader
+                .read_line(&mut line)
+                .map_err(|e| format!("Failed to read from app: {}", e))?;
+            if n == 0 {
+                return Ok(None);
+            }
+            serde_json::from_str(&line)
+                .map(Some)
+                .map_err(|e| format!("Invalid response from app: {}", e))
+        }
+
+        /// Block on the stream with no timeout (for --follow)
+        pub fn wait_forever(&mut self) {
+            let _ = self.reader.get_ref().set_read_timeout(None);
+        }
+    }
+}
+
+#[cfg(not(unix))]
+mod app {
+    use serde_json::Value;
+
+    pub struct Connection;
+
+    impl Connection {
+        pub fn open() -> Option<Self> {
+            None
+        }
+
+        pub fn request(&mut self, _request: Value) -> Result<Value, String> {
+            Err("Not supported on this platform".to_string())
+        }
+
+        pub fn read_line(&mut self) -> Result<Option<Value>, String> {
+            Ok(None)
+        }
+
+        pub fn wait_forever(&mut self) {}
+    }
+}
+
+use app::Connection;
+
+// ============================================================================
+// App not running (runtime_state.json)
+// ============================================================================
+
+fn runtime_state_path() -> Result<PathBuf, String> {
+    dirs::data_dir()
+        .map(|dir| dir.join(APP_IDENTIFIER).join("runtime_state.json"))
+        .ok_or_else(|| "Failed to get app data dir".to_string())
+}
+
+fn read_runtime_state() -> Result<Value, String> {
+    let path = runtime_state_path()?;
+    if !path.exists() {
+        return Ok(serde_json::json!({}));
+    }
+    let content =
+        std::fs::read_to_string(&path).map_err(|e| format!("Failed to read {:?}: {}", path, e))?;
+    serde_json::from_str(&content).map_err(|e| format!("Failed to parse {:?}: {}", path, e))
+}
+
+fn write_runtime_state(value: &Value) -> Result<(), String> {
+    let path = runtime_state_path()?;
+    let content = serde_json::to_string_pretty(value).map_err(|e| e.to_string())?;
+    std::fs::write(&path, content).map_err(|e| format!("Failed to write {:?}: {}", path, e))
+}
+
+fn offline_notice() {
+    eprintln!("eocc: app is not running; using saved state");
+}
+
+// ============================================================================
+// Commands
+// ============================================================================
+
+fn load_snapshot() -> Result<Snapshot, String> {
+    let (sessions, events) = match Connection::open() {
+        Some(mut conn) => {
+            let data = conn.request(serde_json::json!({ "command": "dashboard" }))?;
+            let list = |key: &str| data.get(key).and_then(Value::as_array).cloned();
+            (
+                list("sessions").unwrap_or_default(),
+                list("events").unwrap_or_default(),
+            )
+        }
+        None => {
+            offline_notice();
+            let state = read_runtime_state()?;
+            let mut sessions: Vec<Value> = state
+                .get("sessions")
+                .and_then(Value::as_object)
+                .map(|map| map.values().cloned().collect())
+                .unwrap_or_default();
+            // Newest first, like the dashboard
+            sessions.sort_by(|a, b| {
+                let last = |v: &Value| {
+                    v.get("last_event")
+                        .and_then(Value::as_str)
+                        .unwrap_or("")
+                        .to_string()
+                };
+                last(b).cmp(&last(a))
+            });
+            let events = state
+                .get("recent_events")
+                .and_then(Value::as_array)
+                .cloned()
+                .unwrap_or_default();
+            (sessions, events)
+        }
+    };
+    Ok(Snapshot { sessions, events })
+}
+
+fn parse_sessions(values: &[Value]) -> Vec<Session> {
+    values
+        .iter()
+        .filter_map(|v| serde_json::from_value(v.clone()).ok())
+        .collect()
+}
+
+fn print_json(value: &impl serde::Serialize) -> Result<(), String> {
+    let text = serde_json::to_string_pretty(value).map_err(|e| e.to_string())?;
+    println!("{}", text);
+    Ok(())
+}
+
+fn cmd_status(json: bool) -> Result<(), String> {
+    let sessions = parse_sessions(&load_snapshot()?.sessions);
+    let count = |status: &str| sessions.iter().filter(|s| s.status == status).count();
+    let permission = count("WaitingPermission");
+    let input = count("WaitingInput");
+
+    if json {
+        return print_json(&serde_json::json!({
+            "total": sessions.len(),
+            "waiting": permission + input,
+            "waiting_permission": permission,
+            "waiting_input": input,
+            "active": count("Active"),
+            "completed": count("Completed"),
+            "disconnected": count("Disconnected"),
+        }));
+    }
+
+    if permission + input == 0 {
+        println!("{} session(s), none waiting", sessions.len());
+        return Ok(());
+    }
+    let mut parts = Vec::new();
+    if permission > 0 {
+        parts.push(format!("🔐{}", permission));
+    }
+    if input > 0 {
+        parts.push(format!("⏳{}", input));
+    }
+    let waiting: Vec<&str> = sessions
+        .iter()
+        .filter(|s| s.status == "WaitingPermission" || s.status == "WaitingInput")
+        .map(|s| s.name())
+        .collect();
+    println!("{} {}", parts.join(" "), waiting.join(", "));
+    Ok(())
+}
+
+fn cmd_sessions(json: bool) -> Result<(), String> {
+    let snapshot = load_snapshot()?;
+    if json {
+        return print_json(&snapshot.sessions);
+    }
+    let sessions = parse_sessions(&snapshot.sessions);
+    if sessions.is_empty() {
+        println!("No sessions");
+        return Ok(());
+    }
+    for s in sessions {
+        let mut line = format!(
+            "{} {:<8}  {:<24} {:<17} {}",
+            status_emoji(&s.status),
+            short_id(&s.session_id),
+            s.name(),
+            s.status,
+            s.last_event
+        );
+        if !s.waiting_for.is_empty() {
+            line.push_str(&format!("  {}", s.waiting_for));
+        }
+        println!("{}", line);
+    }
+    Ok(())
+}
+
+fn format_event(value: &Value) -> String {
+    let Ok(e) = serde_json::from_value::<Event>(value.clone()) else {
+        return value.to_string();
+    };
+    let mut detail = match e.event.as_str() {
+        "notification" => e.notification_type.clone(),
+        "post_tool_use" => e.tool_name.clone(),
+        _ => String::new(),
+    };
+    if !e.message.is_empty() {
+        if !detail.is_empty() {
+            detail.push_str(": ");
+        }
+        detail.push_str(&e.message);
+    }
+    format!(
+        "{}  {:<8}  {:<24} {:<18} {}",
+        e.timestamp,
+        short_id(&e.session_id),
+        e.project_name,
+        e.event,
+        detail
+    )
+}
+
+fn cmd_events(json: bool, limit: usize, follow: bool) -> Result<(), String> {
+    let snapshot = load_snapshot()?;
+    // recent_events is oldest first; show the last `limit`
+    let skip = snapshot.events.len().saturating_sub(limit);
+    let recent = &snapshot.events[skip..];
+    if json && !follow {
+        return print_json(&recent);
+    }
+    for event in recent {
+        if json {
+            println!("{}", event);
+        } else {
+            println!("{}", format_event(event));
+        }
+    }
+
+    if !follow {
+        return Ok(());
+    }
+    let mut conn = Connection::open().ok_or("--follow requires the app to be running")?;
+    conn.request(serde_json::json!({ "command": "follow" }))?;
+    conn.wait_forever();
+    while let Some(event) = conn.read_line()? {
+        if json {
+            println!("{}", event);
+        } else {
+            println!("{}", format_event(&event));
+        }
+    }
+    Ok(())
+}
+
+fn cmd_clear() -> Result<(), String> {
+    match Connection::open() {
+        Some(mut conn) => {
+            conn.request(serde_json::json!({ "command": "clear" }))?;
+        }
+        None => {
+            offline_notice();
+            let mut state = read_runtime_state()?;
+            if let Some(obj) = state.as_object_mut() {
+                obj.insert("sessions".to_string(), serde_json::json!({}));
+                obj.insert("timelines".to_string(), serde_json::json!({}));
+            }
+            write_runtime_state(&state)?;
+        }
+    }
+    println!("Cleared all sessions");
+    Ok(())
+}
+
+/// Resolve a full session id or a unique prefix of one
+fn resolve_session(sessions: &[Session], query: &str) -> Result<String, String> {
+    if sessions.iter().any(|s| s.session_id == query) {
+        return Ok(query.to_string());
+    }
+    let matches: Vec<&Session> = sessions
+        .iter()
+        .filter(|s| s.session_id.starts_with(query))
+        .collect();
+    match matches.as_slice() {
+        [session] => Ok(session.session_id.clone()),
+        [] => Err(format!("No session matches {}", query)),
+        _ => Err(format!("{} matches several sessions", query)),
+    }
+}
+
+fn cmd_remove(query: &str) -> Result<(), String> {
+    let sessions = parse_sessions(&load_snapshot()?.sessions);
+    let session_id = resolve_session(&sessions, query)?;
+
+    match Connection::open() {
+        Some(mut conn) => {
+            conn.request(serde_json::json!({ "command": "remove", "session_id": session_id }))?;
+        }
+        None => {
+            let mut state = read_runtime_state()?;
+            for key in ["sessions", "timelines"] {
+                if let Some(map) = state.get_mut(key).and_then(Value::as_object_mut) {
+                    map.remove(&session_id);
+                }
+            }
+            write_runtime_state(&state)?;
+        }
+    }
+    println!("Removed session {}", session_id);
+    Ok(())
+}
+
+fn run(args: &[String]) -> Result<(), String> {
+    let Some((command, rest)) = args.split_first() else {
+        print!("{}", USAGE);
+        return Ok(());
+    };
+
+    let mut json = false;
+    let mut follow = false;
+    let mut limit = 20;
+    let mut positional = Vec::new();
+    let mut iter = rest.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--json" => json = true,
+            "--follow" | "-f" => follow = true,
+            "--limit" | "-n" => {
+                limit = iter
+                    .next()
+                    .and_then(|v| v.parse().ok())
+                    .ok_or("--limit needs a number")?;
+            }
+            flag if flag.starts_with('-') => return Err(format!("Unknown option {}", flag)),
+            value => positional.push(value),
+        }
+    }
+
+    match (command.as_str(), positional.as_slice()) {
+        ("status", []) => cmd_status(json),
+        ("sessions", []) => cmd_sessions(json),
+        ("events", []) => cmd_events(json, limit, follow),
+        ("clear", []) => cmd_clear(),
+        ("remove", [session]) => cmd_remove(session),
+        ("help" | "--help" | "-h", _) => {
+            print!("{}", USAGE);
+            Ok(())
+        }
+        _ => Err(format!("Invalid arguments\n\n{}", USAGE)),
+    }
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match run(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("eocc: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
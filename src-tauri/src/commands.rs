@@ -1,19 +1,37 @@
+use std::collections::BTreeMap;
 use std::path::Path;
 use std::sync::Arc;
-use tauri::{Manager, WebviewUrl, WebviewWindowBuilder};
+use tauri::{Emitter, Manager, WebviewUrl, WebviewWindowBuilder};
 
+use crate::api::{self, ApiInfo, ApiServer};
+use crate::approval::{self, ApprovalDecision};
 use crate::constants::{MINI_VIEW_HEIGHT, MINI_VIEW_WIDTH, SETUP_MODAL_HEIGHT, SETUP_MODAL_WIDTH};
+use crate::container::PathMapping;
+use crate::cost::{CostSummary, ModelPrice};
 use crate::difit::{
-    calculate_diff_hash, get_diff_content, start_difit_server_with_content, DiffType,
+    calculate_diff_hash, get_diff_content, start_difit_server_with_content, DiffSpec, DiffType,
     DifitProcessRegistry, HashCompareResult,
 };
-use crate::git::{get_git_info, GitInfo};
+use crate::dnd::{self, DndSettings, QuietHours};
+use crate::events::refresh_session_paths;
+use crate::git::GitInfo;
+use crate::git_cache::GitStatusCache;
+use crate::history::{EventFilter, EventPage, EventStore};
+use crate::notifications::NotificationSettings;
 use crate::persist::save_runtime_state;
+use crate::policy::{self, Policy};
+use crate::reminders::{ReminderScheduler, ReminderSettings};
+use crate::remote::{self, CommandRunner, RemoteInfo, RemoteSettings};
 use crate::settings::save_settings;
 use crate::setup::{self, SetupStatus};
-use crate::state::{DashboardData, ManagedState, Settings};
+use crate::snapshot::{self, TurnSummary};
+use crate::state::{
+    DashboardData, ManagedState, SessionPrefs, SessionStatus, SessionTimeline, Settings,
+};
+use crate::stats::{compute_stats, StatsReport};
 use crate::tmux::{self, TmuxPane, TmuxPaneSize};
 use crate::tray::{emit_state_update, update_tray_and_badge};
+use crate::webhooks::{self, WebhookConfig};
 
 const LOCK_ERROR: &str = "Failed to acquire state lock";
 
@@ -25,25 +43,42 @@ pub fn get_dashboard_data(state: tauri::State<'_, ManagedState>) -> Result<Dashb
 
 #[tauri::command]
 pub fn remove_session(
-    project_dir: String,
+    session_id: String,
     state: tauri::State<'_, ManagedState>,
     app: tauri::AppHandle,
 ) -> Result<(), String> {
     let mut state_guard = state.0.lock().map_err(|_| LOCK_ERROR)?;
-    state_guard.sessions.remove(&project_dir);
+    state_guard.remove_session(&session_id);
     update_tray_and_badge(&app, &state_guard);
     emit_state_update(&app, &state_guard);
     save_runtime_state(&app, &state_guard);
     Ok(())
 }
 
+/// Get the event timeline and status intervals of a single session
+#[tauri::command]
+pub fn get_session_timeline(
+    session_id: String,
+    state: tauri::State<'_, ManagedState>,
+) -> Result<SessionTimeline, String> {
+    let state_guard = state.0.lock().map_err(|_| LOCK_ERROR)?;
+    if !state_guard.sessions.contains_key(&session_id) {
+        return Err(format!("Session not found: {}", session_id));
+    }
+    Ok(state_guard
+        .timelines
+        .get(&session_id)
+        .cloned()
+        .unwrap_or_default())
+}
+
 #[tauri::command]
 pub fn clear_all_sessions(
     state: tauri::State<'_, ManagedState>,
     app: tauri::AppHandle,
 ) -> Result<(), String> {
     let mut state_guard = state.0.lock().map_err(|_| LOCK_ERROR)?;
-    state_guard.sessions.clear();
+    state_guard.clear_sessions();
     update_tray_and_badge(&app, &state_guard);
     emit_state_update(&app, &state_guard);
     save_runtime_state(&app, &state_guard);
@@ -74,6 +109,498 @@ pub fn set_always_on_top(
     Ok(())
 }
 
+#[tauri::command]
+pub fn set_history_retention_days(
+    days: u32,
+    state: tauri::State<'_, ManagedState>,
+    history: tauri::State<'_, Arc<EventStore>>,
+    app: tauri::AppHandle,
+) -> Result<(), String> {
+    let mut state_guard = state.0.lock().map_err(|_| LOCK_ERROR)?;
+    let days = days.max(1);
+    state_guard.settings.history_retention_days = days;
+    save_settings(&app, &state_guard.settings);
+    history.set_retention_days(days);
+    Ok(())
+}
+
+/// Set how long Disconnected sessions are kept before being archived (0 = forever)
+#[tauri::command]
+pub fn set_auto_archive_minutes(
+    minutes: u32,
+    state: tauri::State<'_, ManagedState>,
+    app: tauri::AppHandle,
+) -> Result<(), String> {
+    let mut state_guard = state.0.lock().map_err(|_| LOCK_ERROR)?;
+    state_guard.settings.auto_archive_disconnected_minutes = minutes;
+    save_settings(&app, &state_guard.settings);
+    Ok(())
+}
+
+/// Get estimated spend per day, project and session
+#[tauri::command]
+pub fn get_costs(state: tauri::State<'_, ManagedState>) -> Result<CostSummary, String> {
+    let state_guard = state.0.lock().map_err(|_| LOCK_ERROR)?;
+    Ok(CostSummary::from_state(&state_guard))
+}
+
+/// Replace the per-model price table used for cost estimates
+#[tauri::command]
+pub fn set_price_table(
+    prices: Vec<ModelPrice>,
+    state: tauri::State<'_, ManagedState>,
+    app: tauri::AppHandle,
+) -> Result<(), String> {
+    if prices.iter().any(|p| p.model_prefix.trim().is_empty()) {
+        return Err("Model prefix must not be empty".to_string());
+    }
+    let mut state_guard = state.0.lock().map_err(|_| LOCK_ERROR)?;
+    state_guard.settings.model_prices = prices;
+    save_settings(&app, &state_guard.settings);
+    Ok(())
+}
+
+/// Set daily and per-session budget thresholds in USD (0 = off)
+#[tauri::command]
+pub fn set_budgets(
+    daily_usd: f64,
+    session_usd: f64,
+    state: tauri::State<'_, ManagedState>,
+    app: tauri::AppHandle,
+) -> Result<(), String> {
+    if daily_usd < 0.0 || session_usd < 0.0 {
+        return Err("Budgets must not be negative".to_string());
+    }
+    let mut state_guard = state.0.lock().map_err(|_| LOCK_ERROR)?;
+    state_guard.settings.daily_budget_usd = daily_usd;
+    state_guard.settings.session_budget_usd = session_usd;
+    save_settings(&app, &state_guard.settings);
+    update_tray_and_badge(&app, &state_guard);
+    Ok(())
+}
+
+/// Replace which statuses raise desktop notifications and their actions
+#[tauri::command]
+pub fn set_notification_settings(
+    notifications: NotificationSettings,
+    state: tauri::State<'_, ManagedState>,
+    app: tauri::AppHandle,
+) -> Result<(), String> {
+    let mut state_guard = state.0.lock().map_err(|_| LOCK_ERROR)?;
+    state_guard.settings.notifications = notifications;
+    save_settings(&app, &state_guard.settings);
+    Ok(())
+}
+
+/// Replace the reminder thresholds (global and per project)
+#[tauri::command]
+pub fn set_reminder_settings(
+    reminders: ReminderSettings,
+    state: tauri::State<'_, ManagedState>,
+    app: tauri::AppHandle,
+) -> Result<(), String> {
+    reminders.validate()?;
+    let mut state_guard = state.0.lock().map_err(|_| LOCK_ERROR)?;
+    state_guard.settings.reminders = reminders;
+    save_settings(&app, &state_guard.settings);
+    Ok(())
+}
+
+/// Replace the Do Not Disturb weekly schedule
+#[tauri::command]
+pub fn set_dnd_schedule(
+    schedule: Vec<QuietHours>,
+    state: tauri::State<'_, ManagedState>,
+    app: tauri::AppHandle,
+) -> Result<(), String> {
+    DndSettings::validate_schedule(&schedule)?;
+    let mut state_guard = state.0.lock().map_err(|_| LOCK_ERROR)?;
+    state_guard.settings.dnd.schedule = schedule;
+    save_settings(&app, &state_guard.settings);
+    dnd::refresh(&app, &state_guard);
+    Ok(())
+}
+
+/// Pause notifications for the given minutes (0 = resume)
+#[tauri::command]
+pub fn pause_notifications(
+    minutes: u32,
+    state: tauri::State<'_, ManagedState>,
+    app: tauri::AppHandle,
+) -> Result<(), String> {
+    let mut state_guard = state.0.lock().map_err(|_| LOCK_ERROR)?;
+    dnd::pause(&app, &mut state_guard, minutes);
+    Ok(())
+}
+
+fn update_session_prefs(
+    session_id: &str,
+    state: &tauri::State<'_, ManagedState>,
+    app: &tauri::AppHandle,
+    update: impl FnOnce(&mut SessionPrefs),
+) -> Result<(), String> {
+    let mut state_guard = state.0.lock().map_err(|_| LOCK_ERROR)?;
+    state_guard.update_session_prefs(session_id, update)?;
+    update_tray_and_badge(app, &state_guard);
+    emit_state_update(app, &state_guard);
+    save_runtime_state(app, &state_guard);
+    Ok(())
+}
+
+/// Show the session under a custom name (empty = project name)
+#[tauri::command]
+pub fn set_session_alias(
+    session_id: String,
+    alias: String,
+    state: tauri::State<'_, ManagedState>,
+    app: tauri::AppHandle,
+) -> Result<(), String> {
+    update_session_prefs(&session_id, &state, &app, |prefs| {
+        prefs.alias = alias.trim().to_string()
+    })
+}
+
+#[tauri::command]
+pub fn set_session_pinned(
+    session_id: String,
+    pinned: bool,
+    state: tauri::State<'_, ManagedState>,
+    app: tauri::AppHandle,
+) -> Result<(), String> {
+    update_session_prefs(&session_id, &state, &app, |prefs| prefs.pinned = pinned)
+}
+
+/// Silence notifications, reminders and sounds for the session
+#[tauri::command]
+pub fn set_session_muted(
+    session_id: String,
+    muted: bool,
+    state: tauri::State<'_, ManagedState>,
+    app: tauri::AppHandle,
+) -> Result<(), String> {
+    update_session_prefs(&session_id, &state, &app, |prefs| prefs.muted = muted)
+}
+
+/// Silence the session until the given RFC 3339 time (None = unsnooze)
+#[tauri::command]
+pub fn snooze_session(
+    session_id: String,
+    until: Option<String>,
+    state: tauri::State<'_, ManagedState>,
+    app: tauri::AppHandle,
+) -> Result<(), String> {
+    let until = match until {
+        Some(until) => chrono::DateTime::parse_from_rfc3339(&until)
+            .map_err(|e| format!("Invalid snooze time {:?}: {}", until, e))?
+            .to_rfc3339(),
+        None => String::new(),
+    };
+    update_session_prefs(&session_id, &state, &app, |prefs| {
+        prefs.snoozed_until = until
+    })
+}
+
+/// Stop reminders for a session until it starts waiting again
+#[tauri::command]
+pub fn acknowledge_session(session_id: String, app: tauri::AppHandle) {
+    if let Some(scheduler) = app.try_state::<Arc<ReminderScheduler>>() {
+        scheduler.acknowledge(&session_id);
+    }
+}
+
+/// Replace the outgoing webhooks
+#[tauri::command]
+pub fn set_webhooks(
+    webhooks: Vec<WebhookConfig>,
+    state: tauri::State<'_, ManagedState>,
+    app: tauri::AppHandle,
+) -> Result<(), String> {
+    for webhook in &webhooks {
+        webhook.validate()?;
+    }
+    let mut state_guard = state.0.lock().map_err(|_| LOCK_ERROR)?;
+    state_guard.settings.webhooks = webhooks;
+    save_settings(&app, &state_guard.settings);
+    Ok(())
+}
+
+/// Send a sample payload to a webhook once and return the HTTP status code
+#[tauri::command]
+pub async fn test_webhook(webhook: WebhookConfig) -> Result<u16, String> {
+    tauri::async_runtime::spawn_blocking(move || webhooks::send_test(&webhook))
+        .await
+        .map_err(|e| format!("Test send failed: {}", e))?
+}
+
+/// Answer a pending permission prompt from the dashboard
+#[tauri::command]
+pub fn resolve_approval(
+    id: String,
+    allow: bool,
+    message: Option<String>,
+    state: tauri::State<'_, ManagedState>,
+    app: tauri::AppHandle,
+) -> Result<(), String> {
+    let mut state_guard = state.0.lock().map_err(|_| LOCK_ERROR)?;
+    let decision = if allow {
+        ApprovalDecision::Allow
+    } else {
+        ApprovalDecision::Deny {
+            message: message.filter(|m| !m.trim().is_empty()),
+        }
+    };
+    if !approval::resolve_approval(&mut state_guard, &id, decision) {
+        return Err("This request was already answered or timed out".to_string());
+    }
+    update_tray_and_badge(&app, &state_guard);
+    emit_state_update(&app, &state_guard);
+    Ok(())
+}
+
+/// Set how long a permission prompt waits in the dashboard before falling back to the terminal
+#[tauri::command]
+pub fn set_approval_timeout(
+    secs: u32,
+    state: tauri::State<'_, ManagedState>,
+    app: tauri::AppHandle,
+) -> Result<(), String> {
+    let mut state_guard = state.0.lock().map_err(|_| LOCK_ERROR)?;
+    state_guard.settings.approval_timeout_secs = secs.clamp(
+        Settings::MIN_APPROVAL_TIMEOUT_SECS,
+        Settings::MAX_APPROVAL_TIMEOUT_SECS,
+    );
+    save_settings(&app, &state_guard.settings);
+    Ok(())
+}
+
+/// Get the auto-approval rules
+#[tauri::command]
+pub fn get_policy(app: tauri::AppHandle) -> Policy {
+    policy::load_policy(&app)
+}
+
+/// Replace the auto-approval rules
+#[tauri::command]
+pub fn set_policy(policy: Policy, app: tauri::AppHandle) -> Result<(), String> {
+    policy.validate()?;
+    policy::save_policy(&app, &policy)
+}
+
+/// Get the local API status, URL and token
+#[tauri::command]
+pub fn get_api_info(
+    state: tauri::State<'_, ManagedState>,
+    api_server: tauri::State<'_, Arc<ApiServer>>,
+    app: tauri::AppHandle,
+) -> Result<ApiInfo, String> {
+    let (enabled, port) = {
+        let state_guard = state.0.lock().map_err(|_| LOCK_ERROR)?;
+        (
+            state_guard.settings.api_enabled,
+            state_guard.settings.api_port,
+        )
+    };
+    Ok(api_server.info(&app, enabled, port))
+}
+
+/// Enable or disable the local API, optionally changing its port
+#[tauri::command]
+pub fn set_api_enabled(
+    enabled: bool,
+    port: Option<u16>,
+    state: tauri::State<'_, ManagedState>,
+    api_server: tauri::State<'_, Arc<ApiServer>>,
+    app: tauri::AppHandle,
+) -> Result<ApiInfo, String> {
+    if port == Some(0) {
+        return Err("Port must not be 0".to_string());
+    }
+    let port = {
+        let mut state_guard = state.0.lock().map_err(|_| LOCK_ERROR)?;
+        state_guard.settings.api_enabled = enabled;
+        if let Some(port) = port {
+            state_guard.settings.api_port = port;
+        }
+        save_settings(&app, &state_guard.settings);
+        state_guard.settings.api_port
+    };
+
+    if enabled {
+        api_server.start(&app, Arc::clone(&state.0), port)?;
+    } else {
+        api_server.stop();
+    }
+    Ok(api_server.info(&app, enabled, port))
+}
+
+/// Replace the local API token, restarting the server if it is running
+#[tauri::command]
+pub fn regenerate_api_token(
+    state: tauri::State<'_, ManagedState>,
+    api_server: tauri::State<'_, Arc<ApiServer>>,
+    app: tauri::AppHandle,
+) -> Result<ApiInfo, String> {
+    api::regenerate_token(&app, api::TOKEN_FILE)?;
+    let (enabled, port) = {
+        let state_guard = state.0.lock().map_err(|_| LOCK_ERROR)?;
+        (
+            state_guard.settings.api_enabled,
+            state_guard.settings.api_port,
+        )
+    };
+    if api_server.is_running() {
+        api_server.start(&app, Arc::clone(&state.0), port)?;
+    }
+    Ok(api_server.info(&app, enabled, port))
+}
+
+/// Get the remote listener status, token and this machine's hostname
+#[tauri::command]
+pub fn get_remote_info(
+    state: tauri::State<'_, ManagedState>,
+    app: tauri::AppHandle,
+) -> Result<RemoteInfo, String> {
+    let state_guard = state.0.lock().map_err(|_| LOCK_ERROR)?;
+    Ok(remote::info(&app, &state_guard.settings.remote))
+}
+
+/// Enable or disable the TCP listener for remote hooks
+#[tauri::command]
+pub fn set_remote_listener(
+    enabled: bool,
+    bind_address: String,
+    port: u16,
+    state: tauri::State<'_, ManagedState>,
+    app: tauri::AppHandle,
+) -> Result<RemoteInfo, String> {
+    if port == 0 {
+        return Err("Port must not be 0".to_string());
+    }
+    let settings = {
+        let mut state_guard = state.0.lock().map_err(|_| LOCK_ERROR)?;
+        let settings = RemoteSettings {
+            enabled,
+            bind_address,
+            port,
+            ..state_guard.settings.remote.clone()
+        };
+        settings.validate()?;
+        state_guard.settings.remote = settings.clone();
+        save_settings(&app, &state_guard.settings);
+        settings
+    };
+    remote::apply(&app, &settings)?;
+    Ok(remote::info(&app, &settings))
+}
+
+/// Replace the remote token, restarting the listener if it is running
+#[tauri::command]
+pub fn regenerate_remote_token(
+    state: tauri::State<'_, ManagedState>,
+    app: tauri::AppHandle,
+) -> Result<RemoteInfo, String> {
+    api::regenerate_token(&app, remote::TOKEN_FILE)?;
+    let settings = {
+        let state_guard = state.0.lock().map_err(|_| LOCK_ERROR)?;
+        state_guard.settings.remote.clone()
+    };
+    if remote::is_running(&app) {
+        remote::apply(&app, &settings)?;
+    }
+    Ok(remote::info(&app, &settings))
+}
+
+/// Set the SSH destinations used for git and diffs of remote sessions, keyed by hostname
+#[tauri::command]
+pub fn set_remote_ssh_hosts(
+    ssh_hosts: BTreeMap<String, String>,
+    state: tauri::State<'_, ManagedState>,
+    app: tauri::AppHandle,
+) -> Result<(), String> {
+    let mut state_guard = state.0.lock().map_err(|_| LOCK_ERROR)?;
+    let settings = RemoteSettings {
+        ssh_hosts,
+        ..state_guard.settings.remote.clone()
+    };
+    settings.validate()?;
+    state_guard.settings.remote = settings;
+    save_settings(&app, &state_guard.settings);
+    let _ = app.emit("settings-updated", &state_guard.settings);
+    Ok(())
+}
+
+/// Set the container-to-host path mappings and re-map the current sessions
+#[tauri::command]
+pub fn set_path_mappings(
+    mappings: Vec<PathMapping>,
+    state: tauri::State<'_, ManagedState>,
+    app: tauri::AppHandle,
+) -> Result<(), String> {
+    PathMapping::validate(&mappings)?;
+    let mut state_guard = state.0.lock().map_err(|_| LOCK_ERROR)?;
+    state_guard.settings.path_mappings = mappings;
+    save_settings(&app, &state_guard.settings);
+    let _ = app.emit("settings-updated", &state_guard.settings);
+    refresh_session_paths(&mut state_guard);
+    update_tray_and_badge(&app, &state_guard);
+    emit_state_update(&app, &state_guard);
+    save_runtime_state(&app, &state_guard);
+    Ok(())
+}
+
+/// Where git commands for a session on `host` run
+fn command_runner(state: &ManagedState, host: Option<&str>) -> Result<CommandRunner, String> {
+    let state_guard = state.0.lock().map_err(|_| LOCK_ERROR)?;
+    CommandRunner::for_host(&state_guard.settings.remote, host.unwrap_or_default())
+}
+
+/// Query the durable event history, newest first
+#[tauri::command]
+pub fn query_events(
+    filter: Option<EventFilter>,
+    offset: Option<usize>,
+    limit: Option<usize>,
+    history: tauri::State<'_, Arc<EventStore>>,
+) -> EventPage {
+    history.query(
+        &filter.unwrap_or_default(),
+        offset.unwrap_or(0),
+        limit.unwrap_or(100),
+    )
+}
+
+/// Time-in-status and permission latency stats over the last `days` days (default 7)
+#[tauri::command]
+pub fn get_stats(
+    days: Option<u32>,
+    state: tauri::State<'_, ManagedState>,
+    history: tauri::State<'_, Arc<EventStore>>,
+) -> Result<StatsReport, String> {
+    let (live_sessions, retention_days) = {
+        let state_guard = state.0.lock().map_err(|_| LOCK_ERROR)?;
+        (
+            // A disconnected session's last status would otherwise run on until now
+            state_guard
+                .sessions
+                .iter()
+                .filter(|(_, s)| s.status != SessionStatus::Disconnected)
+                .map(|(key, _)| key.clone())
+                .collect(),
+            state_guard.settings.history_retention_days,
+        )
+    };
+
+    let days = days.unwrap_or(7).clamp(1, retention_days.max(1));
+    let until = chrono::Utc::now();
+    let since = until - chrono::Duration::days(i64::from(days));
+    let events = history.collect(&EventFilter {
+        since: Some(since.to_rfc3339()),
+        ..Default::default()
+    });
+
+    Ok(compute_stats(&events, &live_sessions, since, until))
+}
+
 /// Set window size for setup modal (enlarged) or normal miniview
 #[tauri::command]
 pub fn set_window_size_for_setup(enlarged: bool, app: tauri::AppHandle) -> Result<(), String> {
@@ -124,16 +651,73 @@ pub fn set_opacity_inactive(
 }
 
 #[tauri::command]
-pub fn get_repo_git_info(project_dir: String) -> GitInfo {
-    get_git_info(&project_dir)
+pub fn get_repo_git_info(
+    project_dir: String,
+    host: Option<String>,
+    state: tauri::State<'_, ManagedState>,
+    cache: tauri::State<'_, Arc<GitStatusCache>>,
+) -> Result<GitInfo, String> {
+    let runner = command_runner(&state, host.as_deref())?;
+    Ok(cache.get(host.as_deref().unwrap_or_default(), &runner, &project_dir))
+}
+
+const SESSION_ID_REQUIRED: &str = "session_id is required for this diff type";
+
+/// The session's checkpointed turns with what changed in each
+#[tauri::command]
+pub fn list_session_turns(
+    session_id: String,
+    state: tauri::State<'_, ManagedState>,
+) -> Result<Vec<TurnSummary>, String> {
+    let dir = {
+        let state_guard = state.0.lock().map_err(|_| LOCK_ERROR)?;
+        let session = state_guard
+            .sessions
+            .get(&session_id)
+            .ok_or_else(|| format!("Session not found: {}", session_id))?;
+        if session.turns == 0 {
+            return Ok(Vec::new());
+        }
+        session.project_path().to_string()
+    };
+    snapshot::list_turns(&dir, &session_id)
 }
 
-/// Generate a unique window label for a diff based on project and type
-fn generate_diff_window_label(project_dir: &str, diff_type: &str) -> String {
+/// Snapshot the session's working tree was compared against when it started
+fn session_start_snapshot(
+    state: &ManagedState,
+    runner: &CommandRunner,
+    session_id: Option<&str>,
+) -> Result<String, String> {
+    // Snapshots are only taken of repositories on this machine
+    if !runner.is_local() {
+        return Err("Session start snapshots are not available for remote sessions".to_string());
+    }
+    let session_id = session_id.ok_or(SESSION_ID_REQUIRED)?;
+    let state_guard = state.0.lock().map_err(|_| LOCK_ERROR)?;
+    let session = state_guard
+        .sessions
+        .get(session_id)
+        .ok_or_else(|| format!("Session not found: {}", session_id))?;
+    if session.start_snapshot.is_empty() {
+        return Err("No snapshot was taken when this session started".to_string());
+    }
+    Ok(session.start_snapshot.clone())
+}
+
+/// Generate a unique window label for a diff based on host, project and type
+fn generate_diff_window_label(
+    runner: &CommandRunner,
+    project_dir: &str,
+    diff_type: &str,
+) -> String {
     use std::collections::hash_map::DefaultHasher;
     use std::hash::{Hash, Hasher};
 
     let mut hasher = DefaultHasher::new();
+    if let CommandRunner::Ssh(destination) = runner {
+        destination.hash(&mut hasher);
+    }
     project_dir.hash(&mut hasher);
     diff_type.hash(&mut hasher);
     format!("difit-{:x}", hasher.finish())
@@ -182,37 +766,65 @@ const LOADING_HTML: &str = r#"
 "#;
 
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub fn open_diff(
     project_dir: String,
     diff_type: String,
     base_branch: Option<String>,
+    host: Option<String>,
+    session_id: Option<String>,
+    spec: Option<DiffSpec>,
     app: tauri::AppHandle,
     state: tauri::State<'_, ManagedState>,
     difit_registry: tauri::State<'_, Arc<DifitProcessRegistry>>,
 ) -> Result<(), String> {
-    // Validate project directory
+    let runner = command_runner(&state, host.as_deref())?;
+    // Validate project directory (a remote one is checked by git itself)
     let path = Path::new(&project_dir);
-    if !path.exists() {
-        return Err(format!("Directory does not exist: {}", project_dir));
-    }
-    if !path.is_dir() {
-        return Err(format!("Path is not a directory: {}", project_dir));
-    }
-    // Check if it's a git repository
-    if !path.join(".git").exists() {
-        return Err(format!("Not a git repository: {}", project_dir));
+    if runner.is_local() {
+        if !path.exists() {
+            return Err(format!("Directory does not exist: {}", project_dir));
+        }
+        if !path.is_dir() {
+            return Err(format!("Path is not a directory: {}", project_dir));
+        }
+        // Check if it's a git repository
+        if !path.join(".git").exists() {
+            return Err(format!("Not a git repository: {}", project_dir));
+        }
     }
 
-    // Generate unique window label based on project and diff type
-    let window_label = generate_diff_window_label(&project_dir, &diff_type);
-
     let diff = match diff_type.as_str() {
         "unstaged" => DiffType::Unstaged,
         "staged" => DiffType::Staged,
         "commit" => DiffType::LatestCommit,
         "branch" => DiffType::Branch,
-        _ => return Err(format!("Unknown diff type: {}", diff_type)),
+        "custom" => DiffType::Custom(spec.ok_or("spec is required for a custom diff")?),
+        "since_session_start" => DiffType::SinceSessionStart {
+            snapshot: session_start_snapshot(&state, &runner, session_id.as_deref())?,
+        },
+        other => match other.strip_prefix("turn:").map(str::parse) {
+            Some(Ok(turn)) => {
+                let session_id = session_id.as_deref().ok_or(SESSION_ID_REQUIRED)?;
+                if !runner.is_local() {
+                    return Err("Turn checkpoints are not available for remote sessions".into());
+                }
+                let (start, end) = snapshot::turn_range(&project_dir, session_id, turn)?;
+                DiffType::Turn { turn, start, end }
+            }
+            _ => return Err(format!("Unknown diff type: {}", diff_type)),
+        },
+    };
+
+    // Generate unique window label based on host, project and diff type (per session for
+    // session-relative diffs)
+    let label_key = match &diff {
+        DiffType::SinceSessionStart { snapshot } => format!("{}:{}", diff_type, snapshot),
+        DiffType::Turn { turn, start, .. } => format!("turn:{}:{}", turn, start),
+        DiffType::Custom(spec) => format!("{}:{:?}", diff_type, spec),
+        _ => diff_type.clone(),
     };
+    let window_label = generate_diff_window_label(&runner, &project_dir, &label_key);
 
     // Get cached npx path from state
     let npx_path = {
@@ -234,16 +846,17 @@ pub fn open_diff(
     // Check if window already exists
     if let Some(existing_window) = app.get_webview_window(&window_label) {
         // Get current diff content and calculate hash
-        let diff_content = match get_diff_content(&project_dir, diff, base_branch.as_deref()) {
-            Ok(content) => content,
-            Err(e) => {
-                // Show error in existing window (consistent with new window behavior)
-                let _ = existing_window.show();
-                let _ = existing_window.set_focus();
-                show_error_in_window(&existing_window, &e, &diff_type);
-                return Ok(());
-            }
-        };
+        let diff_content =
+            match get_diff_content(&runner, &project_dir, &diff, base_branch.as_deref()) {
+                Ok(content) => content,
+                Err(e) => {
+                    // Show error in existing window (consistent with new window behavior)
+                    let _ = existing_window.show();
+                    let _ = existing_window.set_focus();
+                    show_error_in_window(&existing_window, &e, &diff_type);
+                    return Ok(());
+                }
+            };
         let new_hash = calculate_diff_hash(&diff_content);
 
         // Atomically check if diff has changed and update hash
@@ -272,6 +885,7 @@ pub fn open_diff(
             app_handle: app.app_handle().clone(),
             registry: Arc::clone(&difit_registry),
             window_label,
+            runner,
             project_dir,
             diff_type_display: diff_type,
             port,
@@ -315,6 +929,7 @@ pub fn open_diff(
         app_handle: app.app_handle().clone(),
         registry: Arc::clone(&difit_registry),
         window_label,
+        runner,
         project_dir,
         diff_type_display: diff_type,
         port,
@@ -366,6 +981,7 @@ struct DifitSpawnContext {
     app_handle: tauri::AppHandle,
     registry: Arc<DifitProcessRegistry>,
     window_label: String,
+    runner: CommandRunner,
     project_dir: String,
     diff_type_display: String,
     port: u16,
@@ -399,7 +1015,7 @@ impl DifitSpawnContext {
 
 fn spawn_difit_server(ctx: DifitSpawnContext, diff: DiffType, base_branch: Option<String>) {
     std::thread::spawn(move || {
-        match get_diff_content(&ctx.project_dir, diff, base_branch.as_deref()) {
+        match get_diff_content(&ctx.runner, &ctx.project_dir, &diff, base_branch.as_deref()) {
             Ok(diff_content) => {
                 let hash = calculate_diff_hash(&diff_content);
                 ctx.registry.set_diff_hash(&ctx.window_label, hash);
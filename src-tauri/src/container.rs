@@ -0,0 +1,73 @@
+//! Sessions running inside a dev container or Docker container on this machine.
+//!
+//! The hook reports the container id, and the paths it reports are container paths such as
+//! `/workspaces/app`. Path mappings turn them into host paths before git, diff and transcript
+//! access. A container session counts as local even though its hostname differs.
+
+use serde::{Deserialize, Serialize};
+
+use crate::state::SessionInfo;
+
+/// Rewrites a container path prefix to the host path it is mounted from
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PathMapping {
+    /// Container id (a prefix such as the short id works) or hostname the hook reports;
+    /// empty matches every container session
+    #[serde(default)]
+    pub container: String,
+    /// e.g. "/workspaces/app"
+    pub container_prefix: String,
+    /// e.g. "/Users/me/src/app"
+    pub host_prefix: String,
+}
+
+impl PathMapping {
+    fn matches(&self, session: &SessionInfo) -> bool {
+        if self.container.is_empty() {
+            return !session.container_id.is_empty();
+        }
+        session.host == self.container
+            || (!session.container_id.is_empty()
+                && session.container_id.starts_with(&self.container))
+    }
+
+    /// Host path for `path`, if it lies under the container prefix
+    fn map(&self, path: &str) -> Option<String> {
+        let prefix = self.container_prefix.trim_end_matches('/');
+        let rest = path.strip_prefix(prefix)?;
+        if !rest.is_empty() && !rest.starts_with('/') {
+            return None;
+        }
+        Some(format!(
+            "{}{}",
+            self.host_prefix.trim_end_matches('/'),
+            rest
+        ))
+    }
+
+    pub fn validate(mappings: &[PathMapping]) -> Result<(), String> {
+        for mapping in mappings {
+            for prefix in [&mapping.container_prefix, &mapping.host_prefix] {
+                if !prefix.starts_with('/') {
+                    return Err(format!(
+                        "Path mapping prefix must be absolute: {:?}",
+                        prefix
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Host path of a path reported by the session's hook, using the first matching mapping
+pub fn map_session_path(
+    mappings: &[PathMapping],
+    session: &SessionInfo,
+    path: &str,
+) -> Option<String> {
+    mappings
+        .iter()
+        .filter(|m| m.matches(session))
+        .find_map(|m| m.map(path))
+}
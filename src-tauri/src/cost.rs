@@ -0,0 +1,328 @@
+use chrono::{Duration, Local};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use tauri::Emitter;
+
+use crate::history::parse_timestamp;
+use crate::notifications::show_message;
+use crate::settings::get_config_dir;
+use crate::state::{AppState, SessionInfo, Settings};
+use crate::transcript::{TokenUsage, UsageRecord};
+
+/// Days and sessions older than this are dropped from the ledger
+const LEDGER_RETENTION_DAYS: i64 = 90;
+
+/// Per-million-token prices (USD) for models whose id starts with `model_prefix`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelPrice {
+    pub model_prefix: String,
+    pub input_per_mtok: f64,
+    pub output_per_mtok: f64,
+    pub cache_write_per_mtok: f64,
+    pub cache_read_per_mtok: f64,
+}
+
+impl ModelPrice {
+    fn new(model_prefix: &str, input: f64, output: f64, cache_write: f64, cache_read: f64) -> Self {
+        Self {
+            model_prefix: model_prefix.to_string(),
+            input_per_mtok: input,
+            output_per_mtok: output,
+            cache_write_per_mtok: cache_write,
+            cache_read_per_mtok: cache_read,
+        }
+    }
+
+    pub fn cost_usd(&self, usage: &TokenUsage) -> f64 {
+        (usage.input_tokens as f64 * self.input_per_mtok
+            + usage.output_tokens as f64 * self.output_per_mtok
+            + usage.cache_creation_input_tokens as f64 * self.cache_write_per_mtok
+            + usage.cache_read_input_tokens as f64 * self.cache_read_per_mtok)
+            / 1_000_000.0
+    }
+}
+
+/// Default price table (public list prices); editable in settings
+pub fn default_price_table() -> Vec<ModelPrice> {
+    vec![
+        ModelPrice::new("claude-opus-4-5", 5.0, 25.0, 6.25, 0.5),
+        ModelPrice::new("claude-opus-4", 15.0, 75.0, 18.75, 1.5),
+        ModelPrice::new("claude-sonnet-4", 3.0, 15.0, 3.75, 0.3),
+        ModelPrice::new("claude-3-7-sonnet", 3.0, 15.0, 3.75, 0.3),
+        ModelPrice::new("claude-haiku-4-5", 1.0, 5.0, 1.25, 0.1),
+        ModelPrice::new("claude-3-5-haiku", 0.8, 4.0, 1.0, 0.08),
+    ]
+}
+
+/// Find the price entry with the longest prefix matching `model`
+fn price_for<'a>(prices: &'a [ModelPrice], model: &str) -> Option<&'a ModelPrice> {
+    prices
+        .iter()
+        .filter(|p| model.starts_with(&p.model_prefix))
+        .max_by_key(|p| p.model_prefix.len())
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DayCost {
+    pub total_usd: f64,
+    /// project_dir -> spend
+    pub by_project: BTreeMap<String, f64>,
+    #[serde(default)]
+    pub budget_alerted: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SessionCost {
+    pub project_name: String,
+    pub project_dir: String,
+    pub total_usd: f64,
+    pub usage: TokenUsage,
+    /// Timestamp of the newest counted transcript entry; older entries are skipped on re-read
+    pub counted_through: String,
+    #[serde(default)]
+    pub budget_alerted: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BudgetScope {
+    Daily,
+    Session,
+}
+
+/// Raised once when a daily or per-session budget is crossed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BudgetAlert {
+    pub scope: BudgetScope,
+    /// Day (`YYYY-MM-DD`) or session label
+    pub label: String,
+    pub spent_usd: f64,
+    pub budget_usd: f64,
+}
+
+/// Estimated spend per day, project and session, persisted in `cost_ledger.json`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CostLedger {
+    #[serde(default)]
+    pub days: BTreeMap<String, DayCost>,
+    #[serde(default)]
+    pub sessions: HashMap<String, SessionCost>,
+    #[serde(skip)]
+    pending_alerts: Vec<BudgetAlert>,
+}
+
+impl CostLedger {
+    /// Add newly read transcript usage for `session` and check budgets
+    pub fn record(&mut self, session: &SessionInfo, records: &[UsageRecord], settings: &Settings) {
+        if records.is_empty() {
+            return;
+        }
+
+        let entry = self
+            .sessions
+            .entry(session.session_id.clone())
+            .or_insert_with(|| SessionCost {
+                project_name: session.project_name.clone(),
+                project_dir: session.project_dir.clone(),
+                ..Default::default()
+            });
+        let counted_through = parse_timestamp(&entry.counted_through);
+
+        let mut touched_days = Vec::new();
+        for record in records {
+            let timestamp = parse_timestamp(&record.timestamp);
+            // Already counted before a restart (the transcript is re-read from the start)
+            if let (Some(ts), Some(through)) = (timestamp, counted_through) {
+                if ts <= through {
+                    continue;
+                }
+            }
+            let Some(price) = price_for(&settings.model_prices, &record.model) else {
+                log::warn!(target: "eocc.cost", "No price for model {}", record.model);
+                continue;
+            };
+            let cost = price.cost_usd(&record.usage);
+
+            entry.total_usd += cost;
+            entry.usage.add(&record.usage);
+            if timestamp.is_some() {
+                entry.counted_through = record.timestamp.clone();
+            }
+
+            let day = timestamp
+                .map(|ts| ts.with_timezone(&Local).date_naive())
+                .unwrap_or_else(|| Local::now().date_naive())
+                .format("%Y-%m-%d")
+                .to_string();
+            let day_cost = self.days.entry(day.clone()).or_default();
+            day_cost.total_usd += cost;
+            *day_cost
+                .by_project
+                .entry(session.project_dir.clone())
+                .or_default() += cost;
+            touched_days.push(day);
+        }
+
+        let session_budget = settings.session_budget_usd;
+        if session_budget > 0.0 && !entry.budget_alerted && entry.total_usd >= session_budget {
+            entry.budget_alerted = true;
+            self.pending_alerts.push(BudgetAlert {
+                scope: BudgetScope::Session,
+                label: session.project_name.clone(),
+                spent_usd: entry.total_usd,
+                budget_usd: session_budget,
+            });
+        }
+
+        let daily_budget = settings.daily_budget_usd;
+        if daily_budget > 0.0 {
+            for day in touched_days {
+                let Some(day_cost) = self.days.get_mut(&day) else {
+                    continue;
+                };
+                if !day_cost.budget_alerted && day_cost.total_usd >= daily_budget {
+                    day_cost.budget_alerted = true;
+                    self.pending_alerts.push(BudgetAlert {
+                        scope: BudgetScope::Daily,
+                        label: day,
+                        spent_usd: day_cost.total_usd,
+                        budget_usd: daily_budget,
+                    });
+                }
+            }
+        }
+    }
+
+    pub fn session_cost_usd(&self, session_id: &str) -> f64 {
+        self.sessions
+            .get(session_id)
+            .map(|s| s.total_usd)
+            .unwrap_or(0.0)
+    }
+
+    pub fn today_usd(&self) -> f64 {
+        let today = Local::now().date_naive().format("%Y-%m-%d").to_string();
+        self.days.get(&today).map(|d| d.total_usd).unwrap_or(0.0)
+    }
+
+    pub fn take_alerts(&mut self) -> Vec<BudgetAlert> {
+        std::mem::take(&mut self.pending_alerts)
+    }
+
+    /// Drop days and sessions past the ledger retention window
+    fn prune(&mut self) {
+        let cutoff = Local::now() - Duration::days(LEDGER_RETENTION_DAYS);
+        let cutoff_day = cutoff.date_naive().format("%Y-%m-%d").to_string();
+        self.days.retain(|day, _| *day >= cutoff_day);
+        self.sessions
+            .retain(|_, s| parse_timestamp(&s.counted_through).is_none_or(|ts| ts >= cutoff));
+    }
+}
+
+/// Spend summary returned to the frontend
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CostSummary {
+    pub today_usd: f64,
+    pub daily_budget_usd: f64,
+    pub session_budget_usd: f64,
+    pub days: BTreeMap<String, DayCost>,
+    pub sessions: HashMap<String, SessionCost>,
+}
+
+impl CostSummary {
+    pub fn from_state(state: &AppState) -> Self {
+        Self {
+            today_usd: state.cost.today_usd(),
+            daily_budget_usd: state.settings.daily_budget_usd,
+            session_budget_usd: state.settings.session_budget_usd,
+            days: state.cost.days.clone(),
+            sessions: state.cost.sessions.clone(),
+        }
+    }
+}
+
+/// Whether today's spend has reached the daily budget
+pub fn is_over_daily_budget(state: &AppState) -> bool {
+    let budget = state.settings.daily_budget_usd;
+    budget > 0.0 && state.cost.today_usd() >= budget
+}
+
+/// Emit pending budget alerts to the frontend and as desktop notifications (unless Do Not
+/// Disturb is on)
+pub fn emit_budget_alerts(app: &tauri::AppHandle, state: &mut AppState) {
+    let dnd_active = state.settings.dnd.is_active();
+    for alert in state.cost.take_alerts() {
+        log::warn!(
+            target: "eocc.cost",
+            "Budget exceeded: {:?} {} ${:.2} / ${:.2}",
+            alert.scope,
+            alert.label,
+            alert.spent_usd,
+            alert.budget_usd
+        );
+        let _ = app.emit("budget-alert", &alert);
+        if !dnd_active {
+            let scope = match alert.scope {
+                BudgetScope::Daily => "Daily",
+                BudgetScope::Session => "Session",
+            };
+            show_message(
+                format!("{} budget exceeded", scope),
+                format!(
+                    "{}: ${:.2} of ${:.2}",
+                    alert.label, alert.spent_usd, alert.budget_usd
+                ),
+            );
+        }
+    }
+}
+
+fn get_cost_ledger_file(app: &tauri::AppHandle) -> Result<std::path::PathBuf, String> {
+    get_config_dir(app).map(|dir| dir.join("cost_ledger.json"))
+}
+
+pub fn load_cost_ledger(app: &tauri::AppHandle) -> CostLedger {
+    let Ok(path) = get_cost_ledger_file(app) else {
+        return CostLedger::default();
+    };
+    if !path.exists() {
+        return CostLedger::default();
+    }
+    match fs::read_to_string(&path).map(|c| serde_json::from_str::<CostLedger>(&c)) {
+        Ok(Ok(mut ledger)) => {
+            ledger.prune();
+            ledger
+        }
+        Ok(Err(e)) => {
+            log::error!(target: "eocc.cost", "Failed to parse cost ledger: {:?}", e);
+            CostLedger::default()
+        }
+        Err(e) => {
+            log::error!(target: "eocc.cost", "Failed to read cost ledger: {:?}", e);
+            CostLedger::default()
+        }
+    }
+}
+
+pub fn save_cost_ledger(app: &tauri::AppHandle, ledger: &CostLedger) {
+    let path = match get_cost_ledger_file(app) {
+        Ok(path) => path,
+        Err(e) => {
+            log::error!(target: "eocc.cost", "Cannot determine app data dir: {}", e);
+            return;
+        }
+    };
+
+    let content = match serde_json::to_string_pretty(ledger) {
+        Ok(c) => c,
+        Err(e) => {
+            log::error!(target: "eocc.cost", "Failed to serialize cost ledger: {:?}", e);
+            return;
+        }
+    };
+
+    if let Err(e) = fs::write(&path, content) {
+        log::error!(target: "eocc.cost", "Failed to write cost ledger: {:?}", e);
+    }
+}
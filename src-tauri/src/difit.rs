@@ -7,6 +7,11 @@ use std::sync::mpsc;
 use std::sync::Mutex;
 use std::time::Duration;
 
+use serde::Deserialize;
+
+use crate::remote::CommandRunner;
+use crate::snapshot;
+
 /// Default base branch for branch diff comparison
 const DEFAULT_BASE_BRANCH: &str = "main";
 
@@ -14,7 +19,7 @@ const DEFAULT_BASE_BRANCH: &str = "main";
 const DEFAULT_DIFIT_PORT: u16 = 4966;
 
 /// Diff types supported by the application
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone)]
 pub enum DiffType {
     /// Unstaged changes (working directory vs index)
     Unstaged,
@@ -24,11 +29,83 @@ pub enum DiffType {
     LatestCommit,
     /// Branch diff (current branch vs main/master)
     Branch,
+    /// Working tree (untracked files included) vs the snapshot taken at session start
+    SinceSessionStart { snapshot: String },
+    /// What changed during one turn (prompt to stop) of a session
+    Turn {
+        turn: u32,
+        start: String,
+        end: String,
+    },
+    /// Any range, stash entry or upstream comparison
+    Custom(DiffSpec),
+}
+
+/// What a custom diff compares
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum DiffTarget {
+    /// `git diff <from> [<to>]`; without `to`, against the working tree
+    Revisions { from: String, to: Option<String> },
+    /// `git diff <from>...<to>`: changes on `to` since it branched off `from`
+    MergeBase { from: String, to: String },
+    /// A stash entry against the commit it was created on, e.g. 0 for stash@{0}
+    Stash { index: u32 },
+    /// Working tree against the upstream of the current branch
+    Upstream,
+}
+
+/// A custom diff, optionally limited to some paths
+#[derive(Debug, Clone, Deserialize)]
+pub struct DiffSpec {
+    #[serde(flatten)]
+    pub target: DiffTarget,
+    #[serde(default)]
+    pub paths: Vec<String>,
+}
+
+/// Reject arguments git would read as options
+fn validate_arg<'a>(kind: &str, value: &'a str) -> Result<&'a str, String> {
+    if value.is_empty() || value.starts_with('-') {
+        return Err(format!("Invalid {}: {:?}", kind, value));
+    }
+    Ok(value)
+}
+
+impl DiffSpec {
+    fn git_diff_args(&self) -> Result<Vec<String>, String> {
+        let mut args = vec!["diff".to_string()];
+        match &self.target {
+            DiffTarget::Revisions { from, to } => {
+                args.push(validate_arg("revision", from)?.to_string());
+                if let Some(to) = to.as_deref().filter(|to| !to.is_empty()) {
+                    args.push(validate_arg("revision", to)?.to_string());
+                }
+            }
+            DiffTarget::MergeBase { from, to } => args.push(format!(
+                "{}...{}",
+                validate_arg("revision", from)?,
+                validate_arg("revision", to)?
+            )),
+            DiffTarget::Stash { index } => {
+                args.push(format!("stash@{{{}}}^1", index));
+                args.push(format!("stash@{{{}}}", index));
+            }
+            DiffTarget::Upstream => args.push("@{upstream}".to_string()),
+        }
+        if !self.paths.is_empty() {
+            args.push("--".to_string());
+            for path in &self.paths {
+                args.push(validate_arg("path", path)?.to_string());
+            }
+        }
+        Ok(args)
+    }
 }
 
 impl DiffType {
     /// Get the git diff arguments for this diff type
-    fn git_diff_args(self, branch: Option<&str>) -> Result<Vec<String>, String> {
+    fn git_diff_args(&self, repo_path: &str, branch: Option<&str>) -> Result<Vec<String>, String> {
         match self {
             DiffType::Unstaged => Ok(vec!["diff".to_string()]),
             DiffType::Staged => Ok(vec!["diff".to_string(), "--cached".to_string()]),
@@ -38,17 +115,22 @@ impl DiffType {
                 "HEAD".to_string(),
             ]),
             DiffType::Branch => {
-                let base = branch.unwrap_or(DEFAULT_BASE_BRANCH);
                 // Validate branch name to prevent git option injection
-                if base.starts_with('-') {
-                    return Err(format!("Invalid branch name: {}", base));
-                }
+                let base = validate_arg("branch name", branch.unwrap_or(DEFAULT_BASE_BRANCH))?;
                 Ok(vec![
                     "diff".to_string(),
                     base.to_string(),
                     "HEAD".to_string(),
                 ])
             }
+            DiffType::SinceSessionStart { snapshot } => {
+                let current = snapshot::worktree_tree(repo_path)?;
+                Ok(vec!["diff".to_string(), snapshot.clone(), current])
+            }
+            DiffType::Turn { start, end, .. } => {
+                Ok(vec!["diff".to_string(), start.clone(), end.clone()])
+            }
+            DiffType::Custom(spec) => spec.git_diff_args(),
         }
     }
 }
@@ -205,11 +287,10 @@ impl Default for DifitProcessRegistry {
 }
 
 /// Get diff content for untracked files
-fn get_untracked_diff(repo_path: &str) -> Vec<u8> {
+fn get_untracked_diff(runner: &CommandRunner, repo_path: &str) -> Vec<u8> {
     // Get list of untracked files
-    let untracked_output = Command::new("git")
-        .args(["ls-files", "--others", "--exclude-standard"])
-        .current_dir(repo_path)
+    let untracked_output = runner
+        .git(repo_path, &["ls-files", "--others", "--exclude-standard"])
         .output();
 
     let untracked_files = match untracked_output {
@@ -228,9 +309,8 @@ fn get_untracked_diff(repo_path: &str) -> Vec<u8> {
     // Generate diff for each untracked file
     let mut combined_diff = Vec::new();
     for file in untracked_files {
-        let diff_output = Command::new("git")
-            .args(["diff", "--no-index", "--", "/dev/null", &file])
-            .current_dir(repo_path)
+        let diff_output = runner
+            .git(repo_path, &["diff", "--no-index", "--", "/dev/null", &file])
             .output();
 
         if let Ok(output) = diff_output {
@@ -246,16 +326,17 @@ fn get_untracked_diff(repo_path: &str) -> Vec<u8> {
 
 /// Get diff content for the specified repository and diff type
 pub fn get_diff_content(
+    runner: &CommandRunner,
     repo_path: &str,
-    diff_type: DiffType,
+    diff_type: &DiffType,
     base_branch: Option<&str>,
 ) -> Result<Vec<u8>, String> {
-    let git_args = diff_type.git_diff_args(base_branch)?;
+    let git_args = diff_type.git_diff_args(repo_path, base_branch)?;
+    let git_args: Vec<&str> = git_args.iter().map(String::as_str).collect();
 
     // Run git diff and capture output
-    let git_output = Command::new("git")
-        .args(&git_args)
-        .current_dir(repo_path)
+    let git_output = runner
+        .git(repo_path, &git_args)
         .output()
         .map_err(|e| format!("Failed to run git diff: {}", e))?;
 
@@ -268,7 +349,7 @@ pub fn get_diff_content(
 
     // For unstaged diff, also include untracked files
     if matches!(diff_type, DiffType::Unstaged) {
-        let untracked_diff = get_untracked_diff(repo_path);
+        let untracked_diff = get_untracked_diff(runner, repo_path);
         diff_content.extend(untracked_diff);
     }
 
@@ -305,8 +386,11 @@ pub fn start_difit_server_with_content(
     cmd.args(["difit", "--no-open", "--port", &port.to_string()])
         .stdin(Stdio::piped())
         .stdout(Stdio::piped())
-        .stderr(Stdio::piped())
-        .current_dir(repo_path);
+        .stderr(Stdio::piped());
+    // The repository of a remote session does not exist here; difit reads the diff from stdin
+    if std::path::Path::new(repo_path).is_dir() {
+        cmd.current_dir(repo_path);
+    }
 
     // If npx_path is provided, add its directory to PATH so `env node` can find node
     if let Some(path) = npx_path.filter(|p| !p.is_empty()) {
@@ -379,3 +463,91 @@ pub fn start_difit_server_with_content(
         process: difit_process,
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spec(target: DiffTarget, paths: &[&str]) -> DiffSpec {
+        DiffSpec {
+            target,
+            paths: paths.iter().map(|p| p.to_string()).collect(),
+        }
+    }
+
+    fn revisions(from: &str, to: Option<&str>) -> DiffTarget {
+        DiffTarget::Revisions {
+            from: from.to_string(),
+            to: to.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn custom_diff_args() {
+        let args = spec(revisions("main", Some("HEAD")), &["src", "README.md"]).git_diff_args();
+        assert_eq!(
+            args.unwrap(),
+            ["diff", "main", "HEAD", "--", "src", "README.md"]
+        );
+        // An empty `to` compares against the working tree
+        let args = spec(revisions("v1.0", Some("")), &[]).git_diff_args();
+        assert_eq!(args.unwrap(), ["diff", "v1.0"]);
+        let merge_base = DiffTarget::MergeBase {
+            from: "main".to_string(),
+            to: "feature".to_string(),
+        };
+        assert_eq!(
+            spec(merge_base, &[]).git_diff_args().unwrap(),
+            ["diff", "main...feature"]
+        );
+        assert_eq!(
+            spec(DiffTarget::Stash { index: 2 }, &[])
+                .git_diff_args()
+                .unwrap(),
+            ["diff", "stash@{2}^1", "stash@{2}"]
+        );
+        assert_eq!(
+            spec(DiffTarget::Upstream, &[]).git_diff_args().unwrap(),
+            ["diff", "@{upstream}"]
+        );
+    }
+
+    #[test]
+    fn custom_diff_rejects_options() {
+        let rejected = [
+            spec(revisions("--output=/tmp/x", None), &[]),
+            spec(revisions("main", Some("-p")), &[]),
+            spec(revisions("", None), &[]),
+            spec(
+                DiffTarget::MergeBase {
+                    from: "main".to_string(),
+                    to: "--ext-diff".to_string(),
+                },
+                &[],
+            ),
+            spec(
+                DiffTarget::MergeBase {
+                    from: "-R".to_string(),
+                    to: "main".to_string(),
+                },
+                &[],
+            ),
+            spec(revisions("main", None), &["src", "--output=/tmp/x"]),
+            spec(DiffTarget::Upstream, &[""]),
+        ];
+        for spec in rejected {
+            assert!(spec.git_diff_args().is_err(), "{:?}", spec);
+        }
+    }
+
+    #[test]
+    fn branch_diff_rejects_options() {
+        assert_eq!(
+            DiffType::Branch.git_diff_args("", Some("develop")).unwrap(),
+            ["diff", "develop", "HEAD"]
+        );
+        assert!(DiffType::Branch
+            .git_diff_args("", Some("--output=/tmp/x"))
+            .is_err());
+    }
+}
@@ -0,0 +1,277 @@
+//! Do Not Disturb: a weekly quiet-hours schedule plus a manual "pause for N minutes".
+//!
+//! While DND is active, desktop notifications are queued instead of shown, reminders and the
+//! dashboard sounds stay silent and the badge is hidden. When it ends, the queued
+//! notifications are delivered as one digest.
+
+use chrono::{DateTime, Datelike, Local, NaiveDateTime, NaiveTime, Weekday};
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::{Emitter, Manager};
+
+use crate::notifications::{show_message, status_text, Notifier};
+use crate::settings::save_settings;
+use crate::state::{AppState, SessionInfo};
+use crate::tray::{emit_state_update, update_tray_and_badge};
+
+/// Interval between checks for a schedule or pause starting or ending
+const CHECK_INTERVAL: Duration = Duration::from_secs(15);
+/// Sessions listed in the digest body before "and N more"
+const MAX_DIGEST_LINES: usize = 8;
+const MAX_PAUSE_MINUTES: u32 = 24 * 60;
+
+/// A recurring quiet period, e.g. 22:00-07:00 on weekdays
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuietHours {
+    /// Days the period starts on (empty = every day)
+    #[serde(default)]
+    pub days: Vec<Weekday>,
+    /// Local time "HH:MM"
+    pub start: String,
+    /// Local time "HH:MM"; an end before the start runs into the next day
+    pub end: String,
+}
+
+fn parse_time(value: &str) -> Option<NaiveTime> {
+    NaiveTime::parse_from_str(value, "%H:%M").ok()
+}
+
+impl QuietHours {
+    fn contains(&self, now: NaiveDateTime) -> bool {
+        let (Some(start), Some(end)) = (parse_time(&self.start), parse_time(&self.end)) else {
+            return false;
+        };
+        let starts_on = |day: Weekday| self.days.is_empty() || self.days.contains(&day);
+        let (day, time) = (now.weekday(), now.time());
+        if start < end {
+            starts_on(day) && start <= time && time < end
+        } else {
+            // Overnight (or 24 hours when start == end)
+            (starts_on(day) && time >= start) || (starts_on(day.pred()) && time < end)
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DndSettings {
+    #[serde(default)]
+    pub schedule: Vec<QuietHours>,
+    /// End of a manual pause (RFC 3339)
+    #[serde(default)]
+    pub paused_until: Option<String>,
+}
+
+impl DndSettings {
+    fn paused_until(&self) -> Option<DateTime<Local>> {
+        self.paused_until
+            .as_deref()
+            .and_then(|t| DateTime::parse_from_rfc3339(t).ok())
+            .map(|t| t.with_timezone(&Local))
+    }
+
+    pub fn is_active_at(&self, now: DateTime<Local>) -> bool {
+        self.paused_until().is_some_and(|until| until > now)
+            || self.schedule.iter().any(|q| q.contains(now.naive_local()))
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.is_active_at(Local::now())
+    }
+
+    /// Short description for the tray, or None when DND is off
+    pub fn status_label(&self) -> Option<String> {
+        let now = Local::now();
+        match self.paused_until().filter(|until| *until > now) {
+            Some(until) => Some(format!("Do Not Disturb until {}", until.format("%H:%M"))),
+            None => self
+                .is_active_at(now)
+                .then(|| "Do Not Disturb (quiet hours)".to_string()),
+        }
+    }
+
+    pub fn validate_schedule(schedule: &[QuietHours]) -> Result<(), String> {
+        for quiet in schedule {
+            for value in [&quiet.start, &quiet.end] {
+                if parse_time(value).is_none() {
+                    return Err(format!("Invalid time {:?}, expected HH:MM", value));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Remembers whether DND was active at the last check, to notice it ending
+#[derive(Default)]
+pub struct DndMonitor {
+    active: AtomicBool,
+}
+
+/// One desktop notification summarizing what happened while DND was on
+fn deliver_digest(app: &tauri::AppHandle, state: &AppState, queued: Vec<SessionInfo>) {
+    // Report each session once, in its current status
+    let sessions: Vec<&SessionInfo> = queued
+        .iter()
+        .filter_map(|s| state.sessions.get(&s.session_id))
+        .collect();
+    if sessions.is_empty() {
+        return;
+    }
+    let _ = app.emit("dnd-digest", &sessions);
+
+    let title = format!(
+        "🌙 {} update(s) while Do Not Disturb was on",
+        sessions.len()
+    );
+    let mut lines: Vec<String> = sessions
+        .iter()
+        .take(MAX_DIGEST_LINES)
+        .map(|s| {
+            format!(
+                "{} {} - {}",
+                s.status.emoji(),
+                s.display_name(),
+                status_text(&s.status)
+            )
+        })
+        .collect();
+    if sessions.len() > MAX_DIGEST_LINES {
+        lines.push(format!("and {} more", sessions.len() - MAX_DIGEST_LINES));
+    }
+    show_message(title, lines.join("\n"));
+}
+
+/// Pick up DND starting or ending: refresh the tray and dashboard, and deliver the digest
+pub fn refresh(app: &tauri::AppHandle, state: &AppState) {
+    let Some(monitor) = app.try_state::<Arc<DndMonitor>>() else {
+        return;
+    };
+    let active = state.settings.dnd.is_active();
+    if monitor.active.swap(active, Ordering::SeqCst) == active {
+        return;
+    }
+    log::info!(
+        target: "eocc.dnd",
+        "Do Not Disturb {}",
+        if active { "on" } else { "off" }
+    );
+    update_tray_and_badge(app, state);
+    emit_state_update(app, state);
+    if !active {
+        if let Some(notifier) = app.try_state::<Arc<Notifier>>() {
+            deliver_digest(app, state, notifier.take_digest());
+        }
+    }
+}
+
+/// Pause notifications for `minutes` from now (0 = resume)
+pub fn pause(app: &tauri::AppHandle, state: &mut AppState, minutes: u32) {
+    state.settings.dnd.paused_until = (minutes > 0).then(|| {
+        let minutes = minutes.min(MAX_PAUSE_MINUTES);
+        (Local::now() + chrono::Duration::minutes(i64::from(minutes))).to_rfc3339()
+    });
+    save_settings(app, &state.settings);
+    let _ = app.emit("settings-updated", &state.settings);
+    // The tray shows the pause end even when quiet hours were already active
+    update_tray_and_badge(app, state);
+    refresh(app, state);
+}
+
+/// Periodically check whether DND started or ended
+pub fn start_dnd_monitor(app_handle: tauri::AppHandle, state: Arc<Mutex<AppState>>) {
+    let monitor = Arc::new(DndMonitor::default());
+    if let Ok(state_guard) = state.lock() {
+        monitor
+            .active
+            .store(state_guard.settings.dnd.is_active(), Ordering::SeqCst);
+    }
+    app_handle.manage(monitor);
+
+    std::thread::spawn(move || loop {
+        std::thread::sleep(CHECK_INTERVAL);
+
+        let Ok(mut state_guard) = state.lock() else {
+            eprintln!("[eocc] Failed to acquire state lock in DND monitor");
+            continue;
+        };
+        // Drop an expired pause so the settings show DND as off
+        let dnd = &state_guard.settings.dnd;
+        if dnd.paused_until.is_some() && dnd.paused_until().is_none_or(|t| t <= Local::now()) {
+            state_guard.settings.dnd.paused_until = None;
+            save_settings(&app_handle, &state_guard.settings);
+            let _ = app_handle.emit("settings-updated", &state_guard.settings);
+        }
+        refresh(&app_handle, &state_guard);
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn quiet(days: &[Weekday], start: &str, end: &str) -> QuietHours {
+        QuietHours {
+            days: days.to_vec(),
+            start: start.to_string(),
+            end: end.to_string(),
+        }
+    }
+
+    /// 2025-01-06 is a Monday
+    fn at(day: u32, time: &str) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2025, 1, day)
+            .unwrap()
+            .and_time(parse_time(time).unwrap())
+    }
+
+    #[test]
+    fn daytime_period_is_half_open() {
+        let q = quiet(&[Weekday::Mon], "09:00", "17:00");
+        assert!(q.contains(at(6, "09:00")));
+        assert!(q.contains(at(6, "16:59")));
+        assert!(!q.contains(at(6, "17:00")));
+        assert!(!q.contains(at(6, "08:59")));
+        assert!(!q.contains(at(7, "10:00")));
+    }
+
+    #[test]
+    fn overnight_period_runs_into_the_next_day() {
+        let q = quiet(&[], "22:00", "07:00");
+        assert!(q.contains(at(6, "22:00")));
+        assert!(q.contains(at(6, "23:59")));
+        assert!(q.contains(at(7, "00:00")));
+        assert!(q.contains(at(7, "06:59")));
+        assert!(!q.contains(at(7, "07:00")));
+        assert!(!q.contains(at(6, "21:59")));
+    }
+
+    #[test]
+    fn overnight_period_follows_the_start_day() {
+        let q = quiet(&[Weekday::Fri], "22:00", "07:00");
+        // Friday night into Saturday morning
+        assert!(q.contains(at(10, "23:00")));
+        assert!(q.contains(at(11, "03:00")));
+        assert!(!q.contains(at(11, "23:00")));
+        // Friday morning belongs to Thursday's period
+        assert!(!q.contains(at(10, "03:00")));
+    }
+
+    #[test]
+    fn same_start_and_end_is_a_full_day() {
+        let q = quiet(&[Weekday::Mon], "08:00", "08:00");
+        assert!(q.contains(at(6, "08:00")));
+        assert!(q.contains(at(6, "20:00")));
+        assert!(q.contains(at(7, "07:59")));
+        assert!(!q.contains(at(7, "08:00")));
+        assert!(!q.contains(at(6, "07:59")));
+    }
+
+    #[test]
+    fn invalid_times_never_match() {
+        assert!(!quiet(&[], "25:00", "07:00").contains(at(6, "23:00")));
+        assert!(!quiet(&[], "22:00", "").contains(at(6, "23:00")));
+    }
+}
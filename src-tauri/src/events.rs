@@ -1,10 +1,48 @@
 use std::fs::File;
 use std::io::{BufRead, BufReader};
+use std::sync::Arc;
 use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::Manager;
 
+use crate::git::resolve_repo;
+use crate::history::EventStore;
+use crate::remote::is_remote_host;
 use crate::settings::get_events_file;
+use crate::snapshot::{SnapshotJob, SnapshotKind};
 use crate::state::{AppState, EventInfo, EventType, NotificationType, SessionInfo, SessionStatus};
 use crate::tmux::set_cached_tmux_path;
+use crate::transcript::update_session_transcript;
+
+/// Status a session enters when it receives `event`.
+/// Returns None for events that end the session or leave its status unchanged.
+pub fn status_for_event(event: &EventInfo) -> Option<SessionStatus> {
+    match event.event_type {
+        EventType::SessionStart | EventType::PostToolUse | EventType::UserPromptSubmit => {
+            Some(SessionStatus::Active)
+        }
+        EventType::Notification => Some(match event.notification_type {
+            NotificationType::PermissionPrompt => SessionStatus::WaitingPermission,
+            NotificationType::IdlePrompt => SessionStatus::WaitingInput,
+            NotificationType::Other => SessionStatus::Active,
+        }),
+        EventType::Stop => Some(SessionStatus::Completed),
+        EventType::PermissionRequest => Some(SessionStatus::WaitingPermission),
+        EventType::PreToolUse => Some(SessionStatus::Active),
+        EventType::Disconnected => Some(SessionStatus::Disconnected),
+        EventType::SessionEnd | EventType::ApprovalDecision | EventType::Unknown => None,
+    }
+}
+
+/// Short description of a tool call, e.g. "Bash: npm test"
+pub fn describe_tool_call(tool_name: &str, tool_input: &serde_json::Value) -> String {
+    let detail = ["command", "file_path", "path", "url", "pattern"]
+        .iter()
+        .find_map(|key| tool_input.get(key).and_then(|v| v.as_str()));
+    match detail {
+        Some(detail) => format!("{}: {}", tool_name, detail),
+        None => tool_name.to_string(),
+    }
+}
 
 pub fn process_event(state: &mut AppState, event: EventInfo) {
     state.recent_events.push_back(event.clone());
@@ -12,37 +50,39 @@ pub fn process_event(state: &mut AppState, event: EventInfo) {
         state.recent_events.pop_front();
     }
 
-    let key = if event.project_dir.is_empty() {
-        event.project_name.clone()
-    } else {
-        event.project_dir.clone()
-    };
+    let key = event.session_key();
 
     match event.event_type {
         EventType::SessionStart => {
-            state.cached_paths.update_from_event(&event);
-            set_cached_tmux_path(&event.tmux_path);
+            update_cached_paths(state, &event);
+            // A resumed session keeps its alias, pin and mute
+            let prefs = state.prefs_for(&key);
             state.sessions.insert(
-                key,
+                key.clone(),
                 SessionInfo {
+                    session_id: key.clone(),
                     project_name: event.project_name.clone(),
                     project_dir: event.project_dir.clone(),
                     status: SessionStatus::Active,
                     last_event: event.timestamp.clone(),
                     waiting_for: String::new(),
-                    tmux_pane: event.tmux_pane,
+                    tmux_pane: event.tmux_pane.clone(),
+                    claude_pid: event.claude_pid,
+                    host: event.host.clone(),
+                    container_id: event.container_id.clone(),
+                    remote_peer: event.remote_peer.clone(),
+                    disconnected_at: String::new(),
+                    transcript_path: event.transcript_path.clone(),
+                    prefs,
+                    ..Default::default()
                 },
             );
         }
         EventType::SessionEnd => {
-            state.sessions.remove(&key);
+            state.remove_session(&key);
         }
         EventType::Notification => {
-            let new_status = match event.notification_type {
-                NotificationType::PermissionPrompt => SessionStatus::WaitingPermission,
-                NotificationType::IdlePrompt => SessionStatus::WaitingInput,
-                NotificationType::Other => SessionStatus::Active,
-            };
+            let new_status = status_for_event(&event).unwrap_or(SessionStatus::Active);
             let waiting_info = if !event.message.is_empty() {
                 event.message.clone()
             } else if !event.tool_name.is_empty() {
@@ -50,32 +90,176 @@ pub fn process_event(state: &mut AppState, event: EventInfo) {
             } else {
                 String::new()
             };
-            state.upsert_session(key, &event, new_status, waiting_info);
+            state.upsert_session(key.clone(), &event, new_status, waiting_info);
         }
         EventType::Stop => {
-            state.cached_paths.update_from_event(&event);
-            set_cached_tmux_path(&event.tmux_path);
-            state.upsert_session(key, &event, SessionStatus::Completed, String::new());
+            update_cached_paths(state, &event);
+            state.upsert_session(key.clone(), &event, SessionStatus::Completed, String::new());
         }
         EventType::PostToolUse => {
-            state.upsert_session(key, &event, SessionStatus::Active, String::new());
+            state.upsert_session(key.clone(), &event, SessionStatus::Active, String::new());
         }
         EventType::UserPromptSubmit => {
             // User submitted a prompt - Claude is now actively working
-            state.upsert_session(key, &event, SessionStatus::Active, String::new());
+            state.upsert_session(key.clone(), &event, SessionStatus::Active, String::new());
+        }
+        EventType::PermissionRequest => {
+            let waiting_info = describe_tool_call(&event.tool_name, &event.tool_input);
+            state.upsert_session(
+                key.clone(),
+                &event,
+                SessionStatus::WaitingPermission,
+                waiting_info,
+            );
+        }
+        EventType::PreToolUse => {
+            state.upsert_session(key.clone(), &event, SessionStatus::Active, String::new());
         }
-        EventType::Unknown => {
+        EventType::ApprovalDecision | EventType::Disconnected | EventType::Unknown => {
             if let Some(session) = state.sessions.get_mut(&key) {
-                session.last_event = event.timestamp;
+                session.last_event = event.timestamp.clone();
             }
         }
     }
+
+    // The session moved on, so any prompt still pending here was answered in the terminal
+    if matches!(
+        event.event_type,
+        EventType::PostToolUse | EventType::UserPromptSubmit | EventType::Stop
+    ) {
+        state.approvals.cancel_session(&key);
+    }
+
+    // A new session or a finished turn may be on another branch
+    let refresh_repo = matches!(event.event_type, EventType::SessionStart | EventType::Stop);
+    state.update_local_dir(&key);
+    update_session_repo(state, &key, refresh_repo);
+    match event.event_type {
+        EventType::SessionStart => queue_snapshot(state, &key, SnapshotKind::Start),
+        EventType::UserPromptSubmit => queue_snapshot(state, &key, SnapshotKind::TurnStart),
+        EventType::Stop => queue_snapshot(state, &key, SnapshotKind::TurnEnd),
+        _ => {}
+    }
+    update_session_transcript(state, &key);
+    state.record_timeline(&key, &event);
+}
+
+/// Remember npx/tmux locations, unless they are paths inside a container or on another host
+fn update_cached_paths(state: &mut AppState, event: &EventInfo) {
+    if !event.container_id.is_empty()
+        || !event.remote_peer.is_empty()
+        || is_remote_host(&event.host)
+    {
+        return;
+    }
+    state.cached_paths.update_from_event(event);
+    set_cached_tmux_path(&event.tmux_path);
+}
+
+/// Re-map every session's project dir and re-resolve its repository (path mappings changed)
+pub fn refresh_session_paths(state: &mut AppState) {
+    state.repo_cache.clear();
+    let keys: Vec<String> = state.sessions.keys().cloned().collect();
+    for key in keys {
+        state.update_local_dir(&key);
+        update_session_repo(state, &key, true);
+    }
+}
+
+/// Attach the session's git repository and worktree, resolving each project dir once
+/// unless `refresh` is set
+fn update_session_repo(state: &mut AppState, key: &str, refresh: bool) {
+    let Some(session) = state.sessions.get(key) else {
+        return;
+    };
+    // The project dir is a path on another machine
+    if session.is_remote() || (session.repo.is_some() && !refresh) {
+        return;
+    }
+    let dir = session.project_path().to_string();
+    let repo = match state.repo_cache.get(&dir) {
+        Some(cached) if !refresh => cached.clone(),
+        _ => {
+            let repo = resolve_repo(&dir);
+            state.repo_cache.insert(dir, repo.clone());
+            repo
+        }
+    };
+    if let Some(session) = state.sessions.get_mut(key) {
+        session.repo = repo;
+    }
+}
+
+/// Queue a snapshot of the working tree of a session in a git repo: at its start, for "since
+/// session start" diffs, and when a prompt is submitted (a new turn starts) or Claude stops
+fn queue_snapshot(state: &mut AppState, key: &str, kind: SnapshotKind) {
+    let Some(session) = state.sessions.get(key) else {
+        return;
+    };
+    if session.repo.is_none()
+        || session.is_remote()
+        || (kind == SnapshotKind::Start && !session.start_snapshot.is_empty())
+    {
+        return;
+    }
+    let job = SnapshotJob {
+        key: key.to_string(),
+        dir: session.project_path().to_string(),
+        session_id: session.session_id.clone(),
+        kind,
+    };
+    state.snapshots.push(job);
+}
+
+/// Record an event in history and apply it to the state
+pub fn ingest_event(history: Option<&EventStore>, state: &mut AppState, event: EventInfo) {
+    if let Some(store) = history {
+        store.append(&event);
+    }
+    process_event(state, event);
+}
+
+/// Parse one JSONL event line; unparseable lines are logged and dropped
+pub fn parse_line(line: &str) -> Option<EventInfo> {
+    let line = line.trim();
+    if line.is_empty() {
+        return None;
+    }
+    match serde_json::from_str::<EventInfo>(line) {
+        Ok(event) => {
+            // Store raw event JSON in the app log (rotated by tauri-plugin-log).
+            log::info!(target: "eocc.events.raw", "{}", line);
+            Some(event)
+        }
+        Err(err) => {
+            log::error!(
+                target: "eocc.events.parse",
+                "Failed to parse event jsonl line (dropped): err={} line={}",
+                err,
+                line
+            );
+            None
+        }
+    }
+}
+
+/// Parse one JSONL event line, record it in history and apply it to the state
+pub fn ingest_line(
+    history: Option<&EventStore>,
+    state: &mut AppState,
+    line: &str,
+) -> Option<EventInfo> {
+    let event = parse_line(line)?;
+    ingest_event(history, state, event.clone());
+    Some(event)
 }
 
-/// Drain (consume) `events.jsonl` as a queue:
+/// Drain the offline fallback queue written while the app was not listening.
+///
+/// `events.jsonl` is consumed as a queue:
 /// - atomically rename `events.jsonl` to a processing file
 /// - recreate an empty `events.jsonl`
-/// - process each line (JSON) and append the raw JSON to the app log
+/// - process each line (JSON), append it to the event history and the raw JSON to the app log
 /// - delete the processing file
 ///
 /// Parse-failed lines are logged as error and dropped.
@@ -130,29 +314,16 @@ pub fn drain_events_queue(app: &tauri::AppHandle, state: &mut AppState) -> Vec<E
         );
     }
 
+    let history = app.try_state::<Arc<EventStore>>();
+
     // Process the rotated file.
     match File::open(&processing_path) {
         Ok(file) => {
             let reader = BufReader::new(file);
             for line in reader.lines().map_while(Result::ok) {
-                if line.is_empty() {
-                    continue;
-                }
-                match serde_json::from_str::<EventInfo>(&line) {
-                    Ok(event) => {
-                        process_event(state, event.clone());
-                        new_events.push(event);
-                        // Store raw event JSON in the app log (rotated by tauri-plugin-log).
-                        log::info!(target: "eocc.events.raw", "{}", line);
-                    }
-                    Err(err) => {
-                        log::error!(
-                            target: "eocc.events.parse",
-                            "Failed to parse event jsonl line (dropped): err={} line={}",
-                            err,
-                            line
-                        );
-                    }
+                if let Some(event) = ingest_line(history.as_deref().map(Arc::as_ref), state, &line)
+                {
+                    new_events.push(event);
                 }
             }
         }
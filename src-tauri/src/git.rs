@@ -1,8 +1,17 @@
 use serde::{Deserialize, Serialize};
-use std::path::Path;
-use std::process::Command;
+use std::collections::{HashMap, HashSet};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
 
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+use crate::remote::CommandRunner;
+
+/// How long the default branch and git dir of a repository are reused before looking again
+const REPO_META_TTL: Duration = Duration::from_secs(300);
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct GitInfo {
     pub branch: String,
     pub default_branch: String,
@@ -11,126 +20,640 @@ pub struct GitInfo {
     pub has_unstaged_changes: bool,
     pub has_staged_changes: bool,
     pub is_git_repo: bool,
+    /// Upstream tracking branch, e.g. "origin/main" (empty if none)
+    pub upstream: String,
+    /// Commits ahead of / behind the upstream
+    pub ahead: u32,
+    pub behind: u32,
+    /// Commits ahead of / behind the default branch
+    pub ahead_default: u32,
+    pub behind_default: u32,
+    pub stash_count: u32,
+    /// Merge, rebase, cherry-pick or revert in progress (local repositories only)
+    pub operation: Option<GitOperation>,
+    /// Paths with unresolved conflicts
+    pub unmerged_paths: Vec<String>,
+    /// Changed, unmerged and untracked files
+    pub files: Vec<FileStatus>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GitOperation {
+    Merge,
+    Rebase,
+    CherryPick,
+    Revert,
+}
+
+/// One entry of `git status --porcelain=v2`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FileStatus {
+    pub path: String,
+    /// Source path of a rename or copy
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub orig_path: String,
+    /// Status letter in the index: '.' (unchanged), 'M', 'A', 'D', 'R', 'C', 'T', 'U' or '?'
+    pub index: char,
+    /// Status letter in the working tree, as for `index`
+    pub worktree: char,
+    /// Lines added / removed against HEAD (None for binary and untracked files)
+    pub insertions: Option<u32>,
+    pub deletions: Option<u32>,
+}
+
+impl FileStatus {
+    fn new(path: &str, xy: &str) -> Self {
+        let mut letters = xy.chars();
+        Self {
+            path: path.to_string(),
+            index: letters.next().unwrap_or('.'),
+            worktree: letters.next().unwrap_or('.'),
+            ..Default::default()
+        }
+    }
+}
+
+/// The repository and worktree a directory belongs to
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RepoLocation {
+    /// Git dir shared by all worktrees; identifies the repository
+    pub common_dir: String,
+    /// Name of the main worktree (or of the bare repository)
+    pub name: String,
+    /// Top level of the worktree containing the directory
+    pub worktree: String,
+    /// Branch checked out in that worktree (short commit hash when detached)
+    pub branch: String,
+    pub is_main_worktree: bool,
+}
+
+/// One entry of `git worktree list`
+#[derive(Debug, Clone, Default)]
+pub struct Worktree {
+    pub path: String,
+    pub head: String,
+    /// Empty when detached
+    pub branch: String,
+    pub bare: bool,
+}
+
+/// Parse `git worktree list --porcelain`; the main worktree comes first
+fn parse_worktree_list(output: &str) -> Vec<Worktree> {
+    let mut worktrees: Vec<Worktree> = Vec::new();
+    for line in output.lines() {
+        let (key, value) = line.split_once(' ').unwrap_or((line, ""));
+        match key {
+            "worktree" => worktrees.push(Worktree {
+                path: value.to_string(),
+                ..Default::default()
+            }),
+            "HEAD" | "branch" | "bare" => {
+                let Some(worktree) = worktrees.last_mut() else {
+                    continue;
+                };
+                match key {
+                    "HEAD" => worktree.head = value.to_string(),
+                    "branch" => {
+                        worktree.branch = value.trim_start_matches("refs/heads/").to_string()
+                    }
+                    _ => worktree.bare = true,
+                }
+            }
+            _ => {}
+        }
+    }
+    worktrees
+}
+
+pub fn list_worktrees(repo_path: &str) -> Vec<Worktree> {
+    run_git_command(repo_path, &["worktree", "list", "--porcelain"])
+        .map(|output| parse_worktree_list(&output))
+        .unwrap_or_default()
+}
+
+fn same_path(a: &str, b: &str) -> bool {
+    let canonical = |p: &str| {
+        Path::new(p)
+            .canonicalize()
+            .unwrap_or_else(|_| PathBuf::from(p))
+    };
+    canonical(a) == canonical(b)
+}
+
+/// Resolve the repository and worktree of a directory (None if it is not in a git repo)
+pub fn resolve_repo(dir: &str) -> Option<RepoLocation> {
+    if dir.is_empty() || !Path::new(dir).exists() {
+        return None;
+    }
+    let output = run_git_command(dir, &["rev-parse", "--git-common-dir", "--show-toplevel"])?;
+    let mut lines = output.lines();
+    // The common dir may be relative to `dir`
+    let common_dir = Path::new(dir).join(lines.next()?);
+    let common_dir = common_dir.canonicalize().unwrap_or(common_dir);
+    let toplevel = lines.next().unwrap_or(dir).to_string();
+
+    let worktrees = list_worktrees(dir);
+    let main = worktrees.first();
+    let name_source = match main {
+        Some(main) if !main.bare => PathBuf::from(&main.path),
+        // Bare repository: name it after the git dir, minus ".git"
+        _ => common_dir.clone(),
+    };
+    let name = name_source
+        .file_name()
+        .map(|n| n.to_string_lossy().trim_end_matches(".git").to_string())
+        .unwrap_or_default();
+    let current = worktrees.iter().find(|w| same_path(&w.path, &toplevel));
+    let branch = match current {
+        Some(w) if !w.branch.is_empty() => w.branch.clone(),
+        Some(w) => w.head.chars().take(7).collect(),
+        None => get_current_branch(&CommandRunner::Local, dir).unwrap_or_default(),
+    };
+
+    let is_main_worktree = main.is_some_and(|m| same_path(&m.path, &toplevel));
+
+    Some(RepoLocation {
+        common_dir: common_dir.to_string_lossy().to_string(),
+        name,
+        worktree: toplevel,
+        branch,
+        is_main_worktree,
+    })
+}
+
+/// Directories whose changes can affect the status of a local repo
+#[derive(Debug, Clone, PartialEq)]
+pub struct StatusRoots {
+    /// Top of the working tree
+    pub worktree: PathBuf,
+    /// Its git dir and the common dir holding the refs (for linked worktrees)
+    pub git_dirs: Vec<PathBuf>,
+}
+
+pub fn status_roots(repo_path: &str) -> Option<StatusRoots> {
+    let output = run_git_command(
+        repo_path,
+        &[
+            "rev-parse",
+            "--show-toplevel",
+            "--absolute-git-dir",
+            "--git-common-dir",
+        ],
+    )?;
+    // The common dir may be relative to `repo_path`
+    let mut dirs = output.lines().map(|line| {
+        let dir = Path::new(repo_path).join(line);
+        dir.canonicalize().unwrap_or(dir)
+    });
+    let worktree = dirs.next()?;
+    let mut git_dirs: Vec<PathBuf> = Vec::new();
+    for dir in dirs {
+        if !git_dirs.iter().any(|root| dir.starts_with(root)) {
+            git_dirs.push(dir);
+        }
+    }
+    Some(StatusRoots { worktree, git_dirs })
+}
+
+/// Which of `paths` (inside the working tree of `repo_path`) git ignores
+pub fn ignored_paths(repo_path: &str, paths: &[PathBuf]) -> HashSet<PathBuf> {
+    if paths.is_empty() {
+        return HashSet::new();
+    }
+    let mut input = Vec::new();
+    for path in paths {
+        input.extend_from_slice(path.to_string_lossy().as_bytes());
+        input.push(0);
+    }
+    let mut command = CommandRunner::Local.git(repo_path, &["check-ignore", "--stdin", "-z"]);
+    command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null());
+    let Ok(mut child) = command.spawn() else {
+        return HashSet::new();
+    };
+    // Written from another thread so a long list cannot deadlock against the output
+    let writer = child.stdin.take().map(|mut stdin| {
+        std::thread::spawn(move || {
+            let _ = stdin.write_all(&input);
+        })
+    });
+    let output = child.wait_with_output();
+    if let Some(writer) = writer {
+        let _ = writer.join();
+    }
+    // Exits with 1 when nothing is ignored, and with 128 on errors (treated the same)
+    let Ok(output) = output else {
+        return HashSet::new();
+    };
+    if !output.status.success() {
+        return HashSet::new();
+    }
+    output
+        .stdout
+        .split(|&b| b == 0)
+        .filter(|path| !path.is_empty())
+        .map(|path| PathBuf::from(String::from_utf8_lossy(path).as_ref()))
+        .collect()
 }
 
 /// Get git information for a repository
 pub fn get_git_info(repo_path: &str) -> GitInfo {
-    let path = Path::new(repo_path);
-    if !path.exists() {
+    get_git_info_with(&CommandRunner::Local, repo_path)
+}
+
+/// Get git information for a repository on the runner's machine
+pub fn get_git_info_with(runner: &CommandRunner, repo_path: &str) -> GitInfo {
+    if runner.is_local() && !Path::new(repo_path).exists() {
         return GitInfo::default();
     }
 
-    // Check if it's a git repo
-    let is_git_repo = run_git_command(repo_path, &["rev-parse", "--git-dir"]).is_some();
-    if !is_git_repo {
+    // Fails outside a git repo. Without optional locks, status does not rewrite the index,
+    // which would wake the watchers in `git_cache` again.
+    let Some(status) = run_git(
+        runner,
+        repo_path,
+        &[
+            "--no-optional-locks",
+            "status",
+            "--porcelain=v2",
+            "--branch",
+            "--show-stash",
+            "-z",
+        ],
+    ) else {
         return GitInfo::default();
+    };
+    let mut info = parse_status(&status);
+    info.is_git_repo = true;
+
+    // Without a commit, the numstat of staged files is against the empty tree
+    let head = std::mem::take(&mut info.latest_commit_hash);
+    let has_head = !head.is_empty();
+    let base = if has_head { head.as_str() } else { "--cached" };
+    if let Some(numstat) = run_git(runner, repo_path, &["diff", base, "--numstat", "-z"]) {
+        apply_numstat(&mut info.files, &numstat);
+    }
+    if has_head {
+        info.latest_commit_time =
+            run_git(runner, repo_path, &["log", "-1", "--format=%cr", &head]).unwrap_or_default();
+        info.latest_commit_hash = head.chars().take(7).collect();
     }
 
-    let branch = get_current_branch(repo_path).unwrap_or_default();
-    let default_branch = get_default_branch(repo_path);
-    let (latest_commit_hash, latest_commit_time) = get_latest_commit(repo_path);
-    let has_unstaged_changes = check_unstaged_changes(repo_path);
-    let has_staged_changes = check_staged_changes(repo_path);
+    let meta = repo_meta(runner, repo_path);
+    info.default_branch = meta.default_branch;
+    if has_head && info.branch != info.default_branch {
+        let range = format!("{}...HEAD", info.default_branch);
+        if let Some((behind, ahead)) = run_git(
+            runner,
+            repo_path,
+            &["rev-list", "--left-right", "--count", &range],
+        )
+        .as_deref()
+        .and_then(parse_counts)
+        {
+            info.behind_default = behind;
+            info.ahead_default = ahead;
+        }
+    }
+    if runner.is_local() && !meta.git_dir.is_empty() {
+        info.operation = detect_operation(Path::new(&meta.git_dir));
+    }
+    info
+}
 
-    GitInfo {
-        branch,
-        default_branch,
-        latest_commit_hash,
-        latest_commit_time,
-        has_unstaged_changes,
-        has_staged_changes,
-        is_git_repo: true,
+/// "<left>\t<right>" from `rev-list --left-right --count`
+fn parse_counts(output: &str) -> Option<(u32, u32)> {
+    let (left, right) = output.split_once('\t')?;
+    Some((left.trim().parse().ok()?, right.trim().parse().ok()?))
+}
+
+/// Parse `git status --porcelain=v2 --branch --show-stash -z`. `latest_commit_hash` is the
+/// full object id of HEAD.
+fn parse_status(output: &str) -> GitInfo {
+    let mut info = GitInfo::default();
+    let mut entries = output.split('\0').filter(|e| !e.is_empty());
+    while let Some(entry) = entries.next() {
+        if let Some(header) = entry.strip_prefix("# ") {
+            let (key, value) = header.split_once(' ').unwrap_or((header, ""));
+            match key {
+                "branch.oid" if value != "(initial)" => info.latest_commit_hash = value.to_string(),
+                // Same as `rev-parse --abbrev-ref HEAD` when detached
+                "branch.head" if value == "(detached)" => info.branch = "HEAD".to_string(),
+                "branch.head" => info.branch = value.to_string(),
+                "branch.upstream" => info.upstream = value.to_string(),
+                "branch.ab" => {
+                    let mut counts = value
+                        .split(' ')
+                        .map(|c| c.trim_start_matches(['+', '-']).parse().unwrap_or(0));
+                    info.ahead = counts.next().unwrap_or(0);
+                    info.behind = counts.next().unwrap_or(0);
+                }
+                "stash" => info.stash_count = value.parse().unwrap_or(0),
+                _ => {}
+            }
+            continue;
+        }
+        // Fields before the path: "1 XY sub mH mI mW hH hI", "2 ... Xscore", "u ... h1 h2 h3"
+        let fields = match entry.as_bytes()[0] {
+            b'1' => 9,
+            b'2' => 10,
+            b'u' => 11,
+            b'?' => 2,
+            _ => continue,
+        };
+        let parts: Vec<&str> = entry.splitn(fields, ' ').collect();
+        let Some(path) = parts.last().filter(|_| parts.len() == fields) else {
+            continue;
+        };
+        let xy = if parts[0] == "?" { "??" } else { parts[1] };
+        let mut file = FileStatus::new(path, xy);
+        match parts[0] {
+            // The source path of a rename follows as its own entry
+            "2" => file.orig_path = entries.next().unwrap_or_default().to_string(),
+            "u" => info.unmerged_paths.push(file.path.clone()),
+            _ => {}
+        }
+        info.files.push(file);
     }
+    info.has_staged_changes = info.files.iter().any(|f| !matches!(f.index, '.' | '?'));
+    info.has_unstaged_changes = info.files.iter().any(|f| f.worktree != '.');
+    info
 }
 
-fn run_git_command(repo_path: &str, args: &[&str]) -> Option<String> {
-    let output = Command::new("git")
-        .args(["-C", repo_path])
-        .args(args)
-        .output()
-        .ok()?;
+/// Add line counts from `git diff --numstat -z` to the matching files
+fn apply_numstat(files: &mut [FileStatus], output: &str) {
+    let mut entries = output.split('\0');
+    while let Some(entry) = entries.next() {
+        let mut parts = entry.splitn(3, '\t');
+        let (Some(added), Some(deleted), Some(path)) = (parts.next(), parts.next(), parts.next())
+        else {
+            continue;
+        };
+        // A rename has an empty path followed by the source and destination entries
+        let path = if path.is_empty() {
+            entries.next();
+            entries.next().unwrap_or_default()
+        } else {
+            path
+        };
+        if let Some(file) = files.iter_mut().find(|f| f.path == path) {
+            // Binary files show "-"
+            file.insertions = added.parse().ok();
+            file.deletions = deleted.parse().ok();
+        }
+    }
+}
 
-    if output.status.success() {
-        Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
+/// In-progress operation, from the state files git keeps in the git dir
+fn detect_operation(git_dir: &Path) -> Option<GitOperation> {
+    if git_dir.join("rebase-merge").exists() || git_dir.join("rebase-apply").exists() {
+        Some(GitOperation::Rebase)
+    } else if git_dir.join("MERGE_HEAD").exists() {
+        Some(GitOperation::Merge)
+    } else if git_dir.join("CHERRY_PICK_HEAD").exists() {
+        Some(GitOperation::CherryPick)
+    } else if git_dir.join("REVERT_HEAD").exists() {
+        Some(GitOperation::Revert)
     } else {
         None
     }
 }
 
-fn get_current_branch(repo_path: &str) -> Option<String> {
-    run_git_command(repo_path, &["rev-parse", "--abbrev-ref", "HEAD"])
+fn run_git_command(repo_path: &str, args: &[&str]) -> Option<String> {
+    run_git(&CommandRunner::Local, repo_path, args)
 }
 
-fn get_latest_commit(repo_path: &str) -> (String, String) {
-    let hash = run_git_command(repo_path, &["rev-parse", "--short", "HEAD"]).unwrap_or_default();
-
-    let time = run_git_command(repo_path, &["log", "-1", "--format=%cr"]).unwrap_or_default();
+fn run_git(runner: &CommandRunner, repo_path: &str, args: &[&str]) -> Option<String> {
+    let output = runner.git(repo_path, args).output().ok()?;
 
-    (hash, time)
+    if output.status.success() {
+        Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    } else {
+        None
+    }
 }
 
-fn check_unstaged_changes(repo_path: &str) -> bool {
-    // Check for unstaged changes in tracked files using git diff
-    let has_tracked_changes = Command::new("git")
-        .args(["-C", repo_path, "diff", "--quiet"])
-        .status()
-        .map(|s| !s.success()) // exit code 1 means there are changes
-        .unwrap_or(false);
-
-    // Check for untracked files
-    let has_untracked = run_git_command(repo_path, &["ls-files", "--others", "--exclude-standard"])
-        .map(|output| !output.is_empty())
-        .unwrap_or(false);
-
-    has_tracked_changes || has_untracked
+fn get_current_branch(runner: &CommandRunner, repo_path: &str) -> Option<String> {
+    run_git(runner, repo_path, &["rev-parse", "--abbrev-ref", "HEAD"])
 }
 
-fn check_staged_changes(repo_path: &str) -> bool {
-    // Check for staged changes using git diff --cached
-    Command::new("git")
-        .args(["-C", repo_path, "diff", "--cached", "--quiet"])
-        .status()
-        .map(|s| !s.success()) // exit code 1 means there are staged changes
-        .unwrap_or(false)
+/// Facts about a repository that rarely change, cached per repo
+#[derive(Clone)]
+struct RepoMeta {
+    default_branch: String,
+    /// Absolute git dir (empty when unknown)
+    git_dir: String,
+    fetched: Instant,
 }
 
-fn get_default_branch(repo_path: &str) -> String {
-    // 1. Try to get default branch from remote HEAD (if remote exists)
-    if let Some(remote_head) =
-        run_git_command(repo_path, &["symbolic-ref", "refs/remotes/origin/HEAD"])
+fn repo_meta(runner: &CommandRunner, repo_path: &str) -> RepoMeta {
+    static CACHE: OnceLock<Mutex<HashMap<(CommandRunner, String), RepoMeta>>> = OnceLock::new();
+    let cache = CACHE.get_or_init(Default::default);
+    let key = (runner.clone(), repo_path.to_string());
+    if let Some(meta) = cache
+        .lock()
+        .ok()
+        .and_then(|entries| entries.get(&key).cloned())
+        .filter(|meta| meta.fetched.elapsed() < REPO_META_TTL)
     {
-        // Output is like "refs/remotes/origin/main"
-        if let Some(branch) = remote_head.strip_prefix("refs/remotes/origin/") {
-            if !branch.is_empty() {
+        return meta;
+    }
+
+    let meta = RepoMeta {
+        default_branch: get_default_branch(runner, repo_path),
+        git_dir: run_git(runner, repo_path, &["rev-parse", "--absolute-git-dir"])
+            .unwrap_or_default(),
+        fetched: Instant::now(),
+    };
+    if let Ok(mut entries) = cache.lock() {
+        entries.retain(|_, meta| meta.fetched.elapsed() < REPO_META_TTL);
+        entries.insert(key, meta.clone());
+    }
+    meta
+}
+
+fn get_default_branch(runner: &CommandRunner, repo_path: &str) -> String {
+    // origin's HEAD and the local branches in one go
+    let refs = run_git(
+        runner,
+        repo_path,
+        &[
+            "for-each-ref",
+            "--format=%(refname) %(symref)",
+            "refs/remotes/origin/HEAD",
+            "refs/heads/",
+        ],
+    )
+    .unwrap_or_default();
+    let mut branches = Vec::new();
+    for line in refs.lines() {
+        let (refname, target) = line.split_once(' ').unwrap_or((line, ""));
+        if refname == "refs/remotes/origin/HEAD" {
+            // 1. The remote's default branch, e.g. "refs/remotes/origin/main"
+            if let Some(branch) = target
+                .strip_prefix("refs/remotes/origin/")
+                .filter(|b| !b.is_empty())
+            {
                 return branch.to_string();
             }
+        } else if let Some(branch) = refname.strip_prefix("refs/heads/") {
+            branches.push(branch);
         }
     }
 
     // 2. Check git config for init.defaultBranch setting
-    if let Some(config_default) = run_git_command(repo_path, &["config", "init.defaultBranch"]) {
+    if let Some(config_default) = run_git(runner, repo_path, &["config", "init.defaultBranch"]) {
         if !config_default.is_empty() {
             return config_default;
         }
     }
 
-    // 3. Check if common default branches exist locally
-    for branch in ["main", "master", "develop"] {
-        if run_git_command(repo_path, &["rev-parse", "--verify", branch]).is_some() {
-            return branch.to_string();
-        }
+    // 3. Common default branches that exist locally, then the first local branch,
+    //    then main if nothing works
+    ["main", "master", "develop"]
+        .into_iter()
+        .find(|name| branches.contains(name))
+        .or_else(|| branches.first().copied())
+        .unwrap_or("main")
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STATUS: &str = concat!(
+        "# branch.oid 0123456789abcdef0123456789abcdef01234567\0",
+        "# branch.head feature/x\0",
+        "# branch.upstream origin/feature/x\0",
+        "# branch.ab +2 -1\0",
+        "# stash 3\0",
+        "1 .M N... 100644 100644 100644 aaaa aaaa src/lib.rs\0",
+        "1 A. N... 000000 100644 100644 0000 bbbb docs/new file.md\0",
+        "2 R. N... 100644 100644 100644 cccc cccc R100 src/new name.rs\0src/old name.rs\0",
+        "u UU N... 100644 100644 100644 100644 d1 d2 d3 conflict.txt\0",
+        "? untracked.txt\0",
+    );
+
+    #[test]
+    fn parse_status_reads_headers() {
+        let info = parse_status(STATUS);
+        assert_eq!(
+            info.latest_commit_hash,
+            "0123456789abcdef0123456789abcdef01234567"
+        );
+        assert_eq!(info.branch, "feature/x");
+        assert_eq!(info.upstream, "origin/feature/x");
+        assert_eq!((info.ahead, info.behind), (2, 1));
+        assert_eq!(info.stash_count, 3);
+        assert!(info.has_staged_changes);
+        assert!(info.has_unstaged_changes);
     }
 
-    // 4. Get the first local branch as last resort
-    if let Some(branches) = run_git_command(repo_path, &["branch", "--format=%(refname:short)"]) {
-        if let Some(first_branch) = branches.lines().next() {
-            let trimmed = first_branch.trim();
-            if !trimmed.is_empty() {
-                return trimmed.to_string();
-            }
-        }
+    #[test]
+    fn parse_status_reads_entries() {
+        let info = parse_status(STATUS);
+        let entries: Vec<(&str, &str, char, char)> = info
+            .files
+            .iter()
+            .map(|f| (f.path.as_str(), f.orig_path.as_str(), f.index, f.worktree))
+            .collect();
+        assert_eq!(
+            entries,
+            vec![
+                ("src/lib.rs", "", '.', 'M'),
+                ("docs/new file.md", "", 'A', '.'),
+                ("src/new name.rs", "src/old name.rs", 'R', '.'),
+                ("conflict.txt", "", 'U', 'U'),
+                ("untracked.txt", "", '?', '?'),
+            ]
+        );
+        assert_eq!(info.unmerged_paths, vec!["conflict.txt"]);
+    }
+
+    #[test]
+    fn parse_status_of_new_or_detached_repo() {
+        let info = parse_status("# branch.oid (initial)\0# branch.head main\0");
+        assert!(info.latest_commit_hash.is_empty());
+        assert_eq!(info.branch, "main");
+        assert!(info.files.is_empty());
+        assert!(!info.has_staged_changes && !info.has_unstaged_changes);
+
+        let info = parse_status("# branch.oid 0123456\0# branch.head (detached)\0");
+        assert_eq!(info.branch, "HEAD");
+    }
+
+    #[test]
+    fn apply_numstat_matches_files_and_renames() {
+        let mut info = parse_status(STATUS);
+        apply_numstat(
+            &mut info.files,
+            concat!(
+                "3\t1\tsrc/lib.rs\0",
+                "10\t0\tdocs/new file.md\0",
+                "2\t2\t\0src/old name.rs\0src/new name.rs\0",
+                "-\t-\timage.png\0",
+            ),
+        );
+        let counts: Vec<(&str, Option<u32>, Option<u32>)> = info
+            .files
+            .iter()
+            .map(|f| (f.path.as_str(), f.insertions, f.deletions))
+            .collect();
+        assert_eq!(
+            counts,
+            vec![
+                ("src/lib.rs", Some(3), Some(1)),
+                ("docs/new file.md", Some(10), Some(0)),
+                ("src/new name.rs", Some(2), Some(2)),
+                ("conflict.txt", None, None),
+                ("untracked.txt", None, None),
+            ]
+        );
     }
 
-    // 5. Fallback to main if nothing works
-    "main".to_string()
+    #[test]
+    fn apply_numstat_leaves_binary_files_without_counts() {
+        let mut files = vec![FileStatus::new("image.png", "M.")];
+        apply_numstat(&mut files, "-\t-\timage.png\0");
+        assert_eq!((files[0].insertions, files[0].deletions), (None, None));
+    }
+
+    #[test]
+    fn status_roots_and_ignored_paths() {
+        let repo = std::env::temp_dir().join(format!("eocc-git-test-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&repo);
+        std::fs::create_dir_all(repo.join("build/out")).unwrap();
+        std::fs::create_dir_all(repo.join("src")).unwrap();
+        std::fs::write(repo.join(".gitignore"), "build/\n*.log\n").unwrap();
+        let dir = repo.to_string_lossy().to_string();
+        assert!(run_git_command(&dir, &["init", "--quiet"]).is_some());
+
+        let roots = status_roots(&dir).unwrap();
+        let repo = repo.canonicalize().unwrap();
+        assert_eq!(roots.worktree, repo);
+        assert_eq!(roots.git_dirs, vec![repo.join(".git")]);
+
+        let paths = vec![
+            repo.join("build"),
+            repo.join("build/out/a.o"),
+            repo.join("src"),
+            repo.join("src/debug.log"),
+            repo.join("src/main.rs"),
+        ];
+        let ignored = ignored_paths(&dir, &paths);
+        let _ = std::fs::remove_dir_all(&repo);
+        assert_eq!(
+            ignored,
+            HashSet::from([
+                repo.join("build"),
+                repo.join("build/out/a.o"),
+                repo.join("src/debug.log"),
+            ])
+        );
+        assert!(ignored_paths(&dir, &[]).is_empty());
+    }
 }
@@ -0,0 +1,492 @@
+//! Cached git status per repository.
+//!
+//! `get_repo_git_info` answers from the cache instead of spawning git on every call. Local
+//! repositories are watched (working tree, git dir and common dir); after changes settle, the
+//! status is recomputed and `git-info-updated` is emitted only if it differs. Directories and
+//! files git ignores (build output, dependencies) are not watched or do not count. Repositories
+//! reached over SSH cannot be watched, so their entries expire after `UNWATCHED_TTL`; so do
+//! local ones once the system runs out of file watches, which the user is told about.
+
+use notify::{Config, ErrorKind, Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{channel, RecvTimeoutError};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::{Emitter, Manager};
+
+use crate::git::{get_git_info, get_git_info_with, ignored_paths, status_roots, GitInfo};
+use crate::notifications::show_message;
+use crate::remote::CommandRunner;
+use crate::state::AppState;
+
+/// Quiet period after the last change before the status is recomputed
+const DEBOUNCE: Duration = Duration::from_millis(500);
+/// Longest a stream of changes delays the refresh
+const MAX_DEBOUNCE: Duration = Duration::from_secs(5);
+/// Lifetime of entries that are not watched (SSH, not a repo, or watching failed)
+const UNWATCHED_TTL: Duration = Duration::from_secs(30);
+/// Interval between checks for repositories no session uses anymore
+const PRUNE_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Host as passed by the dashboard (empty for local) and project dir
+type RepoKey = (String, String);
+
+/// A watched directory and whether its subdirectories are watched too
+type Root = (PathBuf, RecursiveMode);
+
+struct Entry {
+    runner: CommandRunner,
+    info: GitInfo,
+    fetched: Instant,
+    /// Top of the working tree (local repositories only)
+    worktree: Option<PathBuf>,
+    /// Git dir and common dir (local repositories only)
+    git_dirs: Vec<PathBuf>,
+    /// Watched directories; empty when the entry is not watched
+    roots: Vec<Root>,
+}
+
+impl Entry {
+    fn is_fresh(&self) -> bool {
+        !self.roots.is_empty() || self.fetched.elapsed() < UNWATCHED_TTL
+    }
+}
+
+/// Payload of the `git-info-updated` event
+#[derive(Debug, Clone, Serialize)]
+pub struct GitInfoUpdate {
+    pub project_dir: String,
+    pub host: String,
+    pub info: GitInfo,
+}
+
+/// Changes in a watched repository
+struct Change {
+    key: RepoKey,
+    runner: CommandRunner,
+    worktree: PathBuf,
+    /// Changed paths in the working tree, outside the git dirs
+    worktree_paths: Vec<PathBuf>,
+    /// Whether something changed in the git dirs (refs, index, HEAD)
+    git_dir_changed: bool,
+}
+
+pub struct GitStatusCache {
+    entries: Mutex<HashMap<RepoKey, Entry>>,
+    watcher: Mutex<Option<RecommendedWatcher>>,
+}
+
+impl GitStatusCache {
+    /// Cached status of the repo at `dir`, computed on first use
+    pub fn get(&self, host: &str, runner: &CommandRunner, dir: &str) -> GitInfo {
+        let key = (host.to_string(), dir.to_string());
+        if let Ok(entries) = self.entries.lock() {
+            if let Some(entry) = entries
+                .get(&key)
+                .filter(|e| e.runner == *runner && e.is_fresh())
+            {
+                return entry.info.clone();
+            }
+        }
+
+        let info = get_git_info_with(runner, dir);
+        let plan = if runner.is_local() && info.is_git_repo {
+            watch_plan(dir)
+        } else {
+            None
+        };
+        let Ok(mut entries) = self.entries.lock() else {
+            return info;
+        };
+        let Ok(mut watcher) = self.watcher.lock() else {
+            return info;
+        };
+        let previous = entries.remove(&key);
+        let watched = watched_roots(&entries);
+        if let (Some(previous), Some(watcher)) = (previous, watcher.as_mut()) {
+            unwatch(watcher, &previous.roots, &watched);
+        }
+        let (worktree, git_dirs, roots) = match plan {
+            Some((worktree, git_dirs, roots)) => (Some(worktree), git_dirs, roots),
+            None => (None, Vec::new(), Vec::new()),
+        };
+        let roots = match watcher.as_mut() {
+            Some(watcher) => watch(watcher, roots, &watched).unwrap_or_else(|e| {
+                watch_failed(dir, &e);
+                Vec::new()
+            }),
+            None => Vec::new(),
+        };
+        entries.insert(
+            key,
+            Entry {
+                runner: runner.clone(),
+                info: info.clone(),
+                fetched: Instant::now(),
+                worktree,
+                git_dirs,
+                roots,
+            },
+        );
+        info
+    }
+
+    /// Changes of `paths` in each watched repository
+    fn affected(&self, paths: &HashSet<PathBuf>) -> Vec<Change> {
+        let Ok(entries) = self.entries.lock() else {
+            return Vec::new();
+        };
+        let mut changes = Vec::new();
+        for (key, entry) in entries.iter() {
+            let Some(worktree) = entry.worktree.as_ref().filter(|_| !entry.roots.is_empty()) else {
+                continue;
+            };
+            let mut change = Change {
+                key: key.clone(),
+                runner: entry.runner.clone(),
+                worktree: worktree.clone(),
+                worktree_paths: Vec::new(),
+                git_dir_changed: false,
+            };
+            for path in paths {
+                if entry.git_dirs.iter().any(|dir| path.starts_with(dir)) {
+                    change.git_dir_changed = true;
+                } else if path.starts_with(worktree) && path != worktree {
+                    change.worktree_paths.push(path.clone());
+                }
+            }
+            if change.git_dir_changed || !change.worktree_paths.is_empty() {
+                changes.push(change);
+            }
+        }
+        changes
+    }
+
+    /// Follow top-level directories created in or removed from the working tree, and return
+    /// the repositories to refresh: those whose git dirs or files git does not ignore changed
+    fn relevant(&self, changes: Vec<Change>) -> Vec<(RepoKey, CommandRunner)> {
+        let mut keys = Vec::new();
+        for change in changes {
+            let ignored = ignored_paths(&change.key.1, &change.worktree_paths);
+            self.follow_top_level(&change, &ignored);
+            if change.git_dir_changed || change.worktree_paths.iter().any(|p| !ignored.contains(p))
+            {
+                keys.push((change.key, change.runner));
+            }
+        }
+        keys
+    }
+
+    /// Watch new top-level directories unless git ignores them, and forget removed ones
+    fn follow_top_level(&self, change: &Change, ignored: &HashSet<PathBuf>) {
+        let top_level: Vec<&PathBuf> = change
+            .worktree_paths
+            .iter()
+            .filter(|path| path.parent() == Some(change.worktree.as_path()))
+            .filter(|path| path.file_name().is_some_and(|name| name != ".git"))
+            .collect();
+        if top_level.is_empty() {
+            return;
+        }
+        let (Ok(mut entries), Ok(mut watcher)) = (self.entries.lock(), self.watcher.lock()) else {
+            return;
+        };
+        let Some(watcher) = watcher.as_mut() else {
+            return;
+        };
+        let Some(mut entry) = entries.remove(&change.key) else {
+            return;
+        };
+        let watched = watched_roots(&entries);
+        for path in top_level {
+            if entry.roots.is_empty() {
+                break;
+            }
+            let root = (path.clone(), RecursiveMode::Recursive);
+            let is_watched = entry.roots.contains(&root);
+            if is_watched && !path.is_dir() {
+                entry.roots.retain(|r| *r != root);
+                unwatch(watcher, &[root], &watched);
+            } else if !is_watched && path.is_dir() && !ignored.contains(path) {
+                match watch(watcher, vec![root], &watched) {
+                    Ok(roots) => entry.roots.extend(roots),
+                    Err(e) => {
+                        watch_failed(&change.key.1, &e);
+                        unwatch(watcher, &entry.roots, &watched);
+                        entry.roots.clear();
+                    }
+                }
+            }
+        }
+        entries.insert(change.key.clone(), entry);
+    }
+
+    /// Paths touched by a watcher event; reads and git's object store do not change the status
+    fn changed_paths(&self, event: notify::Result<Event>) -> Vec<PathBuf> {
+        match event {
+            Ok(event) if !matches!(event.kind, EventKind::Access(_)) => event
+                .paths
+                .into_iter()
+                .filter(|path| {
+                    !path
+                        .components()
+                        .collect::<Vec<_>>()
+                        .windows(2)
+                        .any(|w| w[0].as_os_str() == ".git" && w[1].as_os_str() == "objects")
+                })
+                .collect(),
+            Ok(_) => Vec::new(),
+            Err(e) => {
+                self.watcher_error(e);
+                Vec::new()
+            }
+        }
+    }
+
+    /// The watcher ran out of watches following new directories: stop watching the
+    /// repositories involved, whose entries then expire like unwatched ones
+    fn watcher_error(&self, error: notify::Error) {
+        if !matches!(error.kind, ErrorKind::MaxFilesWatch) {
+            log::warn!(target: "eocc.git", "File watcher error: {}", error);
+            return;
+        }
+        let (Ok(mut entries), Ok(mut watcher)) = (self.entries.lock(), self.watcher.lock()) else {
+            return;
+        };
+        let keys: Vec<RepoKey> = entries
+            .iter()
+            .filter(|(_, entry)| {
+                entry
+                    .roots
+                    .iter()
+                    .any(|(root, _)| error.paths.iter().any(|path| path.starts_with(root)))
+            })
+            .map(|(key, _)| key.clone())
+            .collect();
+        if keys.is_empty() {
+            watch_failed("a watched repository", &error);
+        }
+        for key in keys {
+            let Some(mut entry) = entries.remove(&key) else {
+                continue;
+            };
+            if let Some(watcher) = watcher.as_mut() {
+                unwatch(watcher, &entry.roots, &watched_roots(&entries));
+            }
+            entry.roots.clear();
+            watch_failed(&key.1, &error);
+            entries.insert(key, entry);
+        }
+    }
+
+    /// Recompute the status of changed repositories and report the ones that differ
+    fn refresh(&self, app: &tauri::AppHandle, keys: Vec<(RepoKey, CommandRunner)>) {
+        for ((host, dir), runner) in keys {
+            let info = get_git_info_with(&runner, &dir);
+            let Ok(mut entries) = self.entries.lock() else {
+                return;
+            };
+            let Some(entry) = entries.get_mut(&(host.clone(), dir.clone())) else {
+                continue;
+            };
+            entry.fetched = Instant::now();
+            if entry.info == info {
+                continue;
+            }
+            entry.info = info.clone();
+            drop(entries);
+            log::debug!(target: "eocc.git", "Git status changed: {}", dir);
+            let _ = app.emit(
+                "git-info-updated",
+                &GitInfoUpdate {
+                    project_dir: dir,
+                    host,
+                    info,
+                },
+            );
+        }
+    }
+
+    /// Stop watching repositories no session is in anymore
+    fn prune(&self, state: &AppState) {
+        let in_use: HashSet<&str> = state.sessions.values().map(|s| s.project_path()).collect();
+        let (Ok(mut entries), Ok(mut watcher)) = (self.entries.lock(), self.watcher.lock()) else {
+            return;
+        };
+        let stale: Vec<RepoKey> = entries
+            .keys()
+            .filter(|(_, dir)| !in_use.contains(dir.as_str()))
+            .cloned()
+            .collect();
+        for key in stale {
+            let Some(entry) = entries.remove(&key) else {
+                continue;
+            };
+            if let Some(watcher) = watcher.as_mut() {
+                unwatch(watcher, &entry.roots, &watched_roots(&entries));
+            }
+        }
+    }
+}
+
+/// Status of a local repo, from the cache when it is running
+pub fn local_git_info(app: &tauri::AppHandle, dir: &str) -> GitInfo {
+    match app.try_state::<Arc<GitStatusCache>>() {
+        Some(cache) => cache.get("", &CommandRunner::Local, dir),
+        None => get_git_info(dir),
+    }
+}
+
+fn watched_roots(entries: &HashMap<RepoKey, Entry>) -> HashSet<PathBuf> {
+    entries
+        .values()
+        .flat_map(|e| e.roots.iter().map(|(root, _)| root.clone()))
+        .collect()
+}
+
+/// What to watch for a local repo: the top of the working tree itself, each of its
+/// subdirectories git does not ignore, and the git dirs
+fn watch_plan(dir: &str) -> Option<(PathBuf, Vec<PathBuf>, Vec<Root>)> {
+    let roots = status_roots(dir)?;
+    let subdirs: Vec<PathBuf> = std::fs::read_dir(&roots.worktree)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok())
+                .filter(|entry| entry.file_type().is_ok_and(|t| t.is_dir()))
+                .filter(|entry| entry.file_name() != ".git")
+                .map(|entry| entry.path())
+                .collect()
+        })
+        .unwrap_or_default();
+    let ignored = ignored_paths(dir, &subdirs);
+
+    let mut plan = vec![(roots.worktree.clone(), RecursiveMode::NonRecursive)];
+    plan.extend(
+        subdirs
+            .into_iter()
+            .filter(|subdir| !ignored.contains(subdir))
+            .map(|subdir| (subdir, RecursiveMode::Recursive)),
+    );
+    plan.extend(
+        roots
+            .git_dirs
+            .iter()
+            .map(|git_dir| (git_dir.clone(), RecursiveMode::Recursive)),
+    );
+    Some((roots.worktree, roots.git_dirs, plan))
+}
+
+/// Watch the roots not watched yet; returns the roots that are watched, or the error after
+/// undoing the watches added so far
+fn watch(
+    watcher: &mut RecommendedWatcher,
+    roots: Vec<Root>,
+    watched: &HashSet<PathBuf>,
+) -> notify::Result<Vec<Root>> {
+    let mut result = Vec::new();
+    for (root, mode) in roots {
+        if !watched.contains(&root) {
+            match watcher.watch(&root, mode) {
+                Ok(()) => {}
+                // Removed since it was listed
+                Err(e) if matches!(e.kind, ErrorKind::PathNotFound) => continue,
+                Err(e) => {
+                    // A recursive watch may have been added in part
+                    result.push((root, mode));
+                    unwatch(watcher, &result, watched);
+                    return Err(e);
+                }
+            }
+        }
+        result.push((root, mode));
+    }
+    Ok(result)
+}
+
+/// Unwatch the roots no other entry uses
+fn unwatch(watcher: &mut RecommendedWatcher, roots: &[Root], watched: &HashSet<PathBuf>) {
+    for (root, _) in roots.iter().filter(|(root, _)| !watched.contains(root)) {
+        let _ = watcher.unwatch(root);
+    }
+}
+
+/// Report a repository that cannot be watched. Running out of file watches is shown to the
+/// user the first time, as every repository past the limit is only refreshed on expiry.
+fn watch_failed(dir: &str, error: &notify::Error) {
+    static LIMIT_REPORTED: AtomicBool = AtomicBool::new(false);
+    if !matches!(error.kind, ErrorKind::MaxFilesWatch) {
+        log::warn!(target: "eocc.git", "Cannot watch {}: {}", dir, error);
+        return;
+    }
+    if LIMIT_REPORTED.swap(true, Ordering::Relaxed) {
+        log::warn!(target: "eocc.git", "Out of file watches, not watching {}", dir);
+        return;
+    }
+    log::error!(
+        target: "eocc.git",
+        "Out of file watches, not watching {}; its git status refreshes every {}s instead. \
+         On Linux, raise fs.inotify.max_user_watches",
+        dir,
+        UNWATCHED_TTL.as_secs()
+    );
+    show_message(
+        "Out of file watches".to_string(),
+        format!(
+            "Git status of {} refreshes every {} seconds instead of on changes. On Linux, \
+             raise fs.inotify.max_user_watches.",
+            Path::new(dir)
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_else(|| dir.to_string()),
+            UNWATCHED_TTL.as_secs()
+        ),
+    );
+}
+
+/// Create the cache and the thread that refreshes it on file changes
+pub fn start_git_cache(app_handle: tauri::AppHandle, state: Arc<Mutex<AppState>>) {
+    let (tx, rx) = channel();
+    let watcher = RecommendedWatcher::new(tx, Config::default())
+        .map_err(|e| log::error!(target: "eocc.git", "Failed to create git watcher: {}", e))
+        .ok();
+    let cache = Arc::new(GitStatusCache {
+        entries: Mutex::new(HashMap::new()),
+        watcher: Mutex::new(watcher),
+    });
+    app_handle.manage(Arc::clone(&cache));
+
+    std::thread::spawn(move || {
+        let mut last_prune = Instant::now();
+        loop {
+            let paths = match rx.recv_timeout(PRUNE_INTERVAL) {
+                Ok(event) => cache.changed_paths(event),
+                Err(RecvTimeoutError::Timeout) => Vec::new(),
+                Err(RecvTimeoutError::Disconnected) => break,
+            };
+            let mut pending: HashSet<PathBuf> = paths.into_iter().collect();
+            // Wait for the changes to settle (a checkout or build touches many files)
+            let started = Instant::now();
+            while !pending.is_empty() && started.elapsed() < MAX_DEBOUNCE {
+                match rx.recv_timeout(DEBOUNCE) {
+                    Ok(event) => pending.extend(cache.changed_paths(event)),
+                    Err(RecvTimeoutError::Timeout) => break,
+                    Err(RecvTimeoutError::Disconnected) => return,
+                }
+            }
+            if !pending.is_empty() {
+                let changes = cache.affected(&pending);
+                cache.refresh(&app_handle, cache.relevant(changes));
+            }
+
+            if last_prune.elapsed() >= PRUNE_INTERVAL {
+                last_prune = Instant::now();
+                if let Ok(state_guard) = state.lock() {
+                    cache.prune(&state_guard);
+                }
+            }
+        }
+    });
+}
@@ -0,0 +1,450 @@
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs::{self, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::state::{EventInfo, EventType};
+
+/// Prefix/suffix of daily segment files: `events-YYYY-MM-DD.jsonl`
+const SEGMENT_PREFIX: &str = "events-";
+const SEGMENT_SUFFIX: &str = ".jsonl";
+
+/// Upper bound for a single `query_events` page
+pub const MAX_QUERY_LIMIT: usize = 1000;
+
+/// Parse an RFC 3339 timestamp (as written by the hook) into UTC
+pub fn parse_timestamp(timestamp: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(timestamp)
+        .ok()
+        .map(|dt| dt.with_timezone(&Utc))
+}
+
+/// Filter for history queries. All set fields must match.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EventFilter {
+    #[serde(default)]
+    pub project_dir: Option<String>,
+    #[serde(default)]
+    pub session_id: Option<String>,
+    #[serde(default)]
+    pub event_types: Option<Vec<EventType>>,
+    /// Inclusive lower bound (RFC 3339)
+    #[serde(default)]
+    pub since: Option<String>,
+    /// Exclusive upper bound (RFC 3339)
+    #[serde(default)]
+    pub until: Option<String>,
+}
+
+/// One page of query results, newest first
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventPage {
+    pub total: usize,
+    pub events: Vec<EventInfo>,
+}
+
+struct StoredEvent {
+    timestamp: DateTime<Utc>,
+    event: EventInfo,
+}
+
+struct StoreInner {
+    dir: PathBuf,
+    retention_days: u32,
+    /// All retained events in append order
+    entries: Vec<StoredEvent>,
+    /// Segment day -> index of its first entry
+    day_starts: BTreeMap<NaiveDate, usize>,
+    by_project: HashMap<String, Vec<usize>>,
+    by_session: HashMap<String, Vec<usize>>,
+    by_type: HashMap<EventType, Vec<usize>>,
+}
+
+impl StoreInner {
+    fn segment_path(&self, day: NaiveDate) -> PathBuf {
+        self.dir.join(format!(
+            "{}{}{}",
+            SEGMENT_PREFIX,
+            day.format("%Y-%m-%d"),
+            SEGMENT_SUFFIX
+        ))
+    }
+
+    fn index_entry(&mut self, idx: usize) {
+        let event = &self.entries[idx].event;
+        self.by_project
+            .entry(event.project_dir.clone())
+            .or_default()
+            .push(idx);
+        self.by_session
+            .entry(event.session_key())
+            .or_default()
+            .push(idx);
+        self.by_type
+            .entry(event.event_type.clone())
+            .or_default()
+            .push(idx);
+    }
+
+    fn push_entry(&mut self, day: NaiveDate, stored: StoredEvent) {
+        let idx = self.entries.len();
+        self.day_starts.entry(day).or_insert(idx);
+        self.entries.push(stored);
+        self.index_entry(idx);
+    }
+
+    fn last_day(&self) -> Option<NaiveDate> {
+        self.day_starts.keys().next_back().copied()
+    }
+
+    /// Drop segments older than the retention window (files and in-memory entries)
+    fn apply_retention(&mut self, today: NaiveDate) {
+        let cutoff = today - Duration::days(i64::from(self.retention_days));
+        let expired: Vec<NaiveDate> = self
+            .day_starts
+            .keys()
+            .take_while(|day| **day < cutoff)
+            .copied()
+            .collect();
+        if expired.is_empty() {
+            return;
+        }
+
+        for day in &expired {
+            let path = self.segment_path(*day);
+            if let Err(e) = fs::remove_file(&path) {
+                log::warn!(target: "eocc.history", "Failed to remove segment {:?}: {:?}", path, e);
+            }
+        }
+
+        let first_kept = self
+            .day_starts
+            .range(cutoff..)
+            .next()
+            .map(|(_, idx)| *idx)
+            .unwrap_or(self.entries.len());
+        let kept: Vec<(NaiveDate, usize)> = self
+            .day_starts
+            .range(cutoff..)
+            .map(|(day, idx)| (*day, idx - first_kept))
+            .collect();
+
+        self.entries.drain(..first_kept);
+        self.day_starts = kept.into_iter().collect();
+        self.by_project.clear();
+        self.by_session.clear();
+        self.by_type.clear();
+        for idx in 0..self.entries.len() {
+            self.index_entry(idx);
+        }
+
+        log::info!(
+            target: "eocc.history",
+            "Pruned {} expired segment(s), {} event(s) retained",
+            expired.len(),
+            self.entries.len()
+        );
+    }
+
+    /// Candidate entry indices for the filter, in append order
+    fn candidates(&self, filter: &EventFilter, range: std::ops::Range<usize>) -> Vec<usize> {
+        let mut lists: Vec<Vec<usize>> = Vec::new();
+        if let Some(project_dir) = &filter.project_dir {
+            lists.push(
+                self.by_project
+                    .get(project_dir)
+                    .cloned()
+                    .unwrap_or_default(),
+            );
+        }
+        if let Some(session_id) = &filter.session_id {
+            lists.push(self.by_session.get(session_id).cloned().unwrap_or_default());
+        }
+        if let Some(event_types) = &filter.event_types {
+            let mut merged: Vec<usize> = event_types
+                .iter()
+                .filter_map(|t| self.by_type.get(t))
+                .flatten()
+                .copied()
+                .collect();
+            merged.sort_unstable();
+            merged.dedup();
+            lists.push(merged);
+        }
+
+        // Start from the smallest index list and intersect the others into it
+        lists.sort_by_key(|l| l.len());
+        let mut iter = lists.into_iter();
+        let Some(mut result) = iter.next() else {
+            return range.collect();
+        };
+        result.retain(|idx| range.contains(idx));
+        for other in iter {
+            result.retain(|idx| other.binary_search(idx).is_ok());
+        }
+        result
+    }
+
+    /// Indices of all entries matching the filter, in append order
+    fn matching(&self, filter: &EventFilter) -> Vec<usize> {
+        let since = filter.since.as_deref().and_then(parse_timestamp);
+        let until = filter.until.as_deref().and_then(parse_timestamp);
+
+        // Skip the segments before `since`. Late events are appended to the newest segment,
+        // however late they are, so `until` cannot narrow the scan and is only filtered on.
+        let start = since
+            .and_then(|s| self.day_starts.range(s.date_naive()..).next())
+            .map(|(_, idx)| *idx)
+            .unwrap_or(if since.is_some() {
+                self.entries.len()
+            } else {
+                0
+            });
+        self.candidates(filter, start..self.entries.len())
+            .into_iter()
+            .filter(|idx| {
+                let ts = self.entries[*idx].timestamp;
+                since.is_none_or(|s| ts >= s) && until.is_none_or(|u| ts < u)
+            })
+            .collect()
+    }
+}
+
+/// Append-only event history stored as daily JSONL segments under the app data dir.
+/// Retained events are kept in memory with indexes by project, session and event type.
+pub struct EventStore {
+    inner: Mutex<StoreInner>,
+}
+
+impl EventStore {
+    /// Open (or create) the store in `dir` and load retained segments
+    pub fn open(dir: PathBuf, retention_days: u32) -> Self {
+        if let Err(e) = fs::create_dir_all(&dir) {
+            log::error!(target: "eocc.history", "Failed to create history dir: {:?}", e);
+        }
+
+        let mut inner = StoreInner {
+            dir,
+            retention_days,
+            entries: Vec::new(),
+            day_starts: BTreeMap::new(),
+            by_project: HashMap::new(),
+            by_session: HashMap::new(),
+            by_type: HashMap::new(),
+        };
+
+        for (day, path) in list_segments(&inner.dir) {
+            load_segment(&mut inner, day, &path);
+        }
+        inner.apply_retention(Utc::now().date_naive());
+
+        log::info!(
+            target: "eocc.history",
+            "Loaded {} event(s) from history",
+            inner.entries.len()
+        );
+
+        Self {
+            inner: Mutex::new(inner),
+        }
+    }
+
+    /// Append an event to the current segment
+    pub fn append(&self, event: &EventInfo) {
+        let mut inner = match self.inner.lock() {
+            Ok(inner) => inner,
+            Err(e) => {
+                log::warn!(target: "eocc.history", "Failed to lock store for append: {}", e);
+                return;
+            }
+        };
+
+        let timestamp = parse_timestamp(&event.timestamp).unwrap_or_else(Utc::now);
+        // Never write into an older segment, so segments stay in append order
+        let day = match inner.last_day() {
+            Some(last) if last > timestamp.date_naive() => last,
+            _ => timestamp.date_naive(),
+        };
+        if inner.last_day().is_some_and(|last| last < day) {
+            inner.apply_retention(day);
+        }
+
+        let line = match serde_json::to_string(event) {
+            Ok(line) => line,
+            Err(e) => {
+                log::error!(target: "eocc.history", "Failed to serialize event: {:?}", e);
+                return;
+            }
+        };
+        let path = inner.segment_path(day);
+        let written = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .and_then(|mut file| writeln!(file, "{}", line));
+        if let Err(e) = written {
+            log::error!(target: "eocc.history", "Failed to append to {:?}: {:?}", path, e);
+            return;
+        }
+
+        inner.push_entry(
+            day,
+            StoredEvent {
+                timestamp,
+                event: event.clone(),
+            },
+        );
+    }
+
+    /// Change the retention window and prune immediately
+    pub fn set_retention_days(&self, retention_days: u32) {
+        if let Ok(mut inner) = self.inner.lock() {
+            inner.retention_days = retention_days;
+            inner.apply_retention(Utc::now().date_naive());
+        }
+    }
+
+    /// Query events matching `filter`, newest first
+    pub fn query(&self, filter: &EventFilter, offset: usize, limit: usize) -> EventPage {
+        let inner = match self.inner.lock() {
+            Ok(inner) => inner,
+            Err(e) => {
+                log::warn!(target: "eocc.history", "Failed to lock store for query: {}", e);
+                return EventPage {
+                    total: 0,
+                    events: Vec::new(),
+                };
+            }
+        };
+
+        let matched = inner.matching(filter);
+        let events = matched
+            .iter()
+            .rev()
+            .skip(offset)
+            .take(limit.min(MAX_QUERY_LIMIT))
+            .map(|idx| inner.entries[*idx].event.clone())
+            .collect();
+
+        EventPage {
+            total: matched.len(),
+            events,
+        }
+    }
+
+    /// All events matching `filter` in append order (unpaged, for aggregation)
+    pub fn collect(&self, filter: &EventFilter) -> Vec<EventInfo> {
+        match self.inner.lock() {
+            Ok(inner) => inner
+                .matching(filter)
+                .into_iter()
+                .map(|idx| inner.entries[idx].event.clone())
+                .collect(),
+            Err(e) => {
+                log::warn!(target: "eocc.history", "Failed to lock store for collect: {}", e);
+                Vec::new()
+            }
+        }
+    }
+}
+
+/// List segment files in `dir`, oldest first
+fn list_segments(dir: &Path) -> Vec<(NaiveDate, PathBuf)> {
+    let Ok(read_dir) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut segments: Vec<(NaiveDate, PathBuf)> = read_dir
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let day = name
+                .strip_prefix(SEGMENT_PREFIX)?
+                .strip_suffix(SEGMENT_SUFFIX)?;
+            let day = NaiveDate::parse_from_str(day, "%Y-%m-%d").ok()?;
+            Some((day, entry.path()))
+        })
+        .collect();
+    segments.sort_by_key(|(day, _)| *day);
+    segments
+}
+
+fn load_segment(inner: &mut StoreInner, day: NaiveDate, path: &Path) {
+    let file = match fs::File::open(path) {
+        Ok(file) => file,
+        Err(e) => {
+            log::error!(target: "eocc.history", "Failed to open segment {:?}: {:?}", path, e);
+            return;
+        }
+    };
+
+    for line in BufReader::new(file).lines().map_while(Result::ok) {
+        if line.is_empty() {
+            continue;
+        }
+        match serde_json::from_str::<EventInfo>(&line) {
+            Ok(event) => {
+                let timestamp = parse_timestamp(&event.timestamp)
+                    .unwrap_or_else(|| day.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc());
+                inner.push_entry(day, StoredEvent { timestamp, event });
+            }
+            Err(err) => {
+                log::warn!(
+                    target: "eocc.history",
+                    "Skipping unreadable history line in {:?}: {}",
+                    path,
+                    err
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(timestamp: DateTime<Utc>, session_id: &str) -> EventInfo {
+        serde_json::from_value(serde_json::json!({
+            "timestamp": timestamp.to_rfc3339(),
+            "event": "stop",
+            "matcher": "",
+            "project_name": "app",
+            "project_dir": "/home/me/app",
+            "session_id": session_id,
+            "message": "",
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn until_finds_events_appended_late() {
+        let dir = std::env::temp_dir().join(format!("eocc-history-test-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let store = EventStore::open(dir.clone(), 30);
+        let now = Utc::now();
+        store.append(&event(now, "recent"));
+        // Arrives after the newer segment exists, e.g. drained after a weekend offline
+        store.append(&event(now - Duration::days(5), "late"));
+
+        let filter = EventFilter {
+            until: Some((now - Duration::days(4)).to_rfc3339()),
+            ..Default::default()
+        };
+        let page = store.query(&filter, 0, 10);
+        let since = EventFilter {
+            since: Some((now - Duration::days(6)).to_rfc3339()),
+            ..filter
+        };
+        let bounded = store.query(&since, 0, 10);
+        let reopened = EventStore::open(dir.clone(), 30).query(&since, 0, 10);
+        let _ = fs::remove_dir_all(&dir);
+
+        for page in [page, bounded, reopened] {
+            assert_eq!(page.total, 1);
+            assert_eq!(page.events[0].session_id, "late");
+        }
+    }
+}
@@ -0,0 +1,563 @@
+//! Unix domain socket server receiving events from the hook and requests from the `eocc` CLI,
+//! plus an opt-in TCP listener for hooks on remote hosts.
+//!
+//! Each connection carries one or more JSONL lines:
+//! - Hook events are answered with `ok` as soon as they are parsed and queued, so the hook
+//!   never waits on the state lock. One worker thread applies them in arrival order.
+//! - Control requests (`{"command": ...}`) are answered with one JSON line
+//!   `{"ok": true, "data": ...}` or `{"ok": false, "error": ...}`. After `follow`, every newly
+//!   ingested event is streamed to the connection as a JSON line.
+//!
+//! A TCP connection must start with `{"command": "auth", "token": ...}` (the remote token) and
+//! may then only send hook events and approval requests. Its events are stamped with the peer
+//! address, and a missing host or one claiming to be this machine is replaced by it, so a remote
+//! hook can never pose as a local session.
+
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpStream};
+use std::os::unix::fs::PermissionsExt;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{channel, Sender};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::Manager;
+
+use crate::api::{bind_with_retry, token_matches};
+
+use crate::approval::{begin_approval, decision_event, release_session, ApprovalDecision};
+use crate::cost::emit_budget_alerts;
+use crate::events::{ingest_event, parse_line};
+use crate::history::EventStore;
+use crate::notifications::notify_status_changes;
+use crate::persist::save_runtime_state;
+use crate::policy::{load_policy, PolicyMatch};
+use crate::remote::{is_remote_host, RemoteSettings};
+use crate::settings::get_socket_path;
+use crate::state::{AppState, EventInfo, EventType, Settings};
+use crate::tray::{emit_state_update, update_tray_and_badge};
+
+/// A connected hook that stops sending is dropped after this long
+const READ_TIMEOUT: Duration = Duration::from_secs(5);
+/// A follower that stops reading is dropped rather than blocking ingestion
+const FOLLOW_WRITE_TIMEOUT: Duration = Duration::from_secs(1);
+const ACCEPT_POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Connections streaming new events (`eocc events --follow`)
+type Followers = Arc<Mutex<Vec<UnixStream>>>;
+
+/// Step run by the ingest worker under the state lock
+type LockedJob = Box<dyn FnOnce(Option<&EventStore>, &mut AppState) + Send>;
+
+/// Work for the ingest worker, applied to the state in arrival order
+enum IngestJob {
+    Event(Box<EventInfo>),
+    /// Approval requests, which wait for the result
+    Locked(LockedJob),
+}
+
+/// Shared by all connections of the socket server and the TCP listener
+struct IngestContext {
+    app: tauri::AppHandle,
+    state: Arc<Mutex<AppState>>,
+    followers: Followers,
+    queue: Sender<IngestJob>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "command", rename_all = "snake_case")]
+enum ControlRequest {
+    /// Current DashboardData
+    Dashboard,
+    Clear,
+    Remove {
+        session_id: String,
+    },
+    Follow,
+    /// Blocking permission hook, answered by a policy rule, the user or the timeout
+    Approval {
+        event: Box<EventInfo>,
+    },
+}
+
+/// First line of a TCP connection
+#[derive(Deserialize)]
+struct AuthRequest {
+    command: String,
+    token: String,
+}
+
+#[derive(Serialize)]
+struct ControlResponse {
+    ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    data: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+impl ControlResponse {
+    fn ok(data: Option<serde_json::Value>) -> Self {
+        Self {
+            ok: true,
+            data,
+            error: None,
+        }
+    }
+
+    fn error(message: impl Into<String>) -> Self {
+        Self {
+            ok: false,
+            data: None,
+            error: Some(message.into()),
+        }
+    }
+}
+
+/// Parse a control request; None means the line is a hook event
+fn parse_control_request(line: &str) -> Option<Result<ControlRequest, String>> {
+    let value: serde_json::Value = serde_json::from_str(line).ok()?;
+    value.get("command")?;
+    Some(serde_json::from_value(value).map_err(|e| format!("Invalid request: {}", e)))
+}
+
+fn handle_control_request(
+    app: &tauri::AppHandle,
+    state: &Mutex<AppState>,
+    request: ControlRequest,
+) -> ControlResponse {
+    let Ok(mut state_guard) = state.lock() else {
+        return ControlResponse::error("Failed to acquire state lock");
+    };
+    match request {
+        ControlRequest::Dashboard => {
+            ControlResponse::ok(serde_json::to_value(state_guard.to_dashboard_data()).ok())
+        }
+        ControlRequest::Clear => {
+            state_guard.clear_sessions();
+            update_tray_and_badge(app, &state_guard);
+            emit_state_update(app, &state_guard);
+            save_runtime_state(app, &state_guard);
+            ControlResponse::ok(None)
+        }
+        ControlRequest::Remove { session_id } => {
+            if !state_guard.sessions.contains_key(&session_id) {
+                return ControlResponse::error(format!("No session {}", session_id));
+            }
+            state_guard.remove_session(&session_id);
+            update_tray_and_badge(app, &state_guard);
+            emit_state_update(app, &state_guard);
+            save_runtime_state(app, &state_guard);
+            ControlResponse::ok(None)
+        }
+        // Handled by the caller, which owns the stream
+        ControlRequest::Follow | ControlRequest::Approval { .. } => ControlResponse::ok(None),
+    }
+}
+
+/// Answer a blocking hook event: by the first matching policy rule, otherwise (for permission
+/// prompts) by waiting for Allow/Deny from the dashboard or tray
+fn handle_approval_request(ctx: &IngestContext, event: EventInfo) -> ControlResponse {
+    let app = &ctx.app;
+    let policy_match = load_policy(app).evaluate(&event);
+    let policy_decision = policy_match.as_ref().and_then(PolicyMatch::decision);
+    // Pre-tool checks fire for every call, so only permission prompts wait for the user here
+    let wait = policy_decision.is_none() && event.event_type == EventType::PermissionRequest;
+
+    // Applied by the worker so the event stays in order with the ones queued before it
+    let (reply_tx, reply_rx) = channel();
+    let job_event = event.clone();
+    let job_decision = policy_decision.clone();
+    let job = IngestJob::Locked(Box::new(move |history, state_guard| {
+        ingest_event(history, state_guard, job_event.clone());
+        let pending = if wait {
+            Some(begin_approval(state_guard, &job_event))
+        } else {
+            release_session(state_guard, &job_event.session_key());
+            None
+        };
+        // A prompt that waits is recorded once answered; calls no rule matched are not recorded
+        if let (Some(store), Some(policy_match), false) = (history, &policy_match, wait) {
+            let decision = job_decision.unwrap_or(ApprovalDecision::Ask);
+            let source = format!("policy rule {}", policy_match.index + 1);
+            store.append(&decision_event(&job_event, &decision, &source));
+        }
+        let timeout_secs = state_guard
+            .settings
+            .approval_timeout_secs
+            .min(Settings::MAX_APPROVAL_TIMEOUT_SECS);
+        let _ = reply_tx.send((pending, timeout_secs));
+    }));
+    if ctx.queue.send(job).is_err() {
+        return ControlResponse::error("The ingest worker is not running");
+    }
+    let Ok((pending, timeout_secs)) = reply_rx.recv() else {
+        return ControlResponse::error("The ingest worker stopped");
+    };
+    notify_followers(&ctx.followers, &event);
+
+    let Some((id, rx)) = pending else {
+        let decision = policy_decision.unwrap_or(ApprovalDecision::Ask);
+        return ControlResponse::ok(serde_json::to_value(decision).ok());
+    };
+
+    let (decision, source) = match rx.recv_timeout(Duration::from_secs(u64::from(timeout_secs))) {
+        // Cancelled because the session moved on
+        Ok(ApprovalDecision::Ask) => (ApprovalDecision::Ask, "terminal"),
+        Ok(decision) => (decision, "dashboard"),
+        Err(_) => {
+            // Nobody answered: leave it to the terminal prompt
+            if let Ok(mut state_guard) = ctx.state.lock() {
+                if state_guard.approvals.remove(&id) {
+                    update_tray_and_badge(app, &state_guard);
+                    emit_state_update(app, &state_guard);
+                }
+            }
+            (ApprovalDecision::Ask, "timeout")
+        }
+    };
+    if let Some(store) = app.try_state::<Arc<EventStore>>() {
+        store.append(&decision_event(&event, &decision, source));
+    }
+    log::info!(
+        target: "eocc.approval",
+        "{} {} -> {:?} ({})",
+        event.session_key(),
+        event.tool_name,
+        decision,
+        source
+    );
+    ControlResponse::ok(serde_json::to_value(decision).ok())
+}
+
+fn write_json_line<T: Serialize>(stream: &mut impl Write, value: &T) -> bool {
+    let Ok(mut line) = serde_json::to_string(value) else {
+        return false;
+    };
+    line.push('\n');
+    stream.write_all(line.as_bytes()).is_ok()
+}
+
+/// Stream an ingested event to all followers, dropping disconnected ones
+fn notify_followers(followers: &Followers, event: &EventInfo) {
+    let Ok(mut followers) = followers.lock() else {
+        return;
+    };
+    followers.retain_mut(|stream| write_json_line(stream, event));
+}
+
+/// Bind the ingestion socket. A stale socket left by a crashed instance is replaced,
+/// but a socket another running instance still answers on is kept.
+fn bind(app: &tauri::AppHandle) -> Result<UnixListener, String> {
+    let path = get_socket_path(app)?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| format!("Failed to create socket dir: {}", e))?;
+    }
+
+    if path.exists() {
+        if UnixStream::connect(&path).is_ok() {
+            return Err(format!("Another instance is listening on {:?}", path));
+        }
+        let _ = std::fs::remove_file(&path);
+    }
+
+    let listener =
+        UnixListener::bind(&path).map_err(|e| format!("Failed to bind {:?}: {}", path, e))?;
+    // Only the current user may deliver events
+    let _ = std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600));
+    Ok(listener)
+}
+
+/// A connection from a local hook or CLI (Unix socket) or a remote hook (TCP)
+trait IngestStream: Read + Write + Sized {
+    fn clone_stream(&self) -> std::io::Result<Self>;
+    fn set_timeout(&self, timeout: Duration);
+    /// The stream to register as a follower; remote connections may not follow
+    fn into_follower(self) -> Option<UnixStream>;
+    /// Peer IP address of a remote connection (None for local ones)
+    fn remote_peer(&self) -> Option<String>;
+}
+
+impl IngestStream for UnixStream {
+    fn clone_stream(&self) -> std::io::Result<Self> {
+        self.try_clone()
+    }
+
+    fn set_timeout(&self, timeout: Duration) {
+        let _ = self.set_read_timeout(Some(timeout));
+    }
+
+    fn into_follower(self) -> Option<UnixStream> {
+        let _ = self.set_write_timeout(Some(FOLLOW_WRITE_TIMEOUT));
+        Some(self)
+    }
+
+    fn remote_peer(&self) -> Option<String> {
+        None
+    }
+}
+
+impl IngestStream for TcpStream {
+    fn clone_stream(&self) -> std::io::Result<Self> {
+        self.try_clone()
+    }
+
+    fn set_timeout(&self, timeout: Duration) {
+        let _ = self.set_read_timeout(Some(timeout));
+    }
+
+    fn into_follower(self) -> Option<UnixStream> {
+        None
+    }
+
+    fn remote_peer(&self) -> Option<String> {
+        Some(
+            self.peer_addr()
+                .map(|addr| addr.ip().to_string())
+                .unwrap_or_else(|_| "unknown".to_string()),
+        )
+    }
+}
+
+/// Record where an event came from. Only the connection decides whether it is remote.
+fn stamp_origin(event: &mut EventInfo, remote_peer: Option<&str>) {
+    match remote_peer {
+        None => event.remote_peer.clear(),
+        Some(peer) => {
+            event.remote_peer = peer.to_string();
+            // Containers and hostnames of this machine are only trusted from local hooks
+            event.container_id.clear();
+            if !is_remote_host(&event.host) {
+                event.host = peer.to_string();
+            }
+        }
+    }
+}
+
+fn is_authorized(line: Option<std::io::Result<String>>, token: &str) -> bool {
+    line.and_then(Result::ok)
+        .and_then(|line| serde_json::from_str::<AuthRequest>(&line).ok())
+        .is_some_and(|auth| auth.command == "auth" && token_matches(token, &auth.token))
+}
+
+/// Serve one connection. With `token`, the first line must authenticate with it and only hook
+/// events and approvals are accepted.
+fn handle_connection<S: IngestStream>(ctx: &IngestContext, stream: S, token: Option<&str>) {
+    stream.set_timeout(READ_TIMEOUT);
+    let Ok(mut writer) = stream.clone_stream() else {
+        return;
+    };
+    let remote_peer = stream.remote_peer();
+
+    let mut lines = BufReader::new(stream).lines();
+    if let Some(token) = token {
+        if !is_authorized(lines.next(), token) {
+            write_json_line(&mut writer, &ControlResponse::error("Unauthorized"));
+            return;
+        }
+    }
+
+    for line in lines.map_while(Result::ok) {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        if let Some(request) = parse_control_request(&line) {
+            let follow = matches!(request, Ok(ControlRequest::Follow));
+            let response = match request {
+                Ok(ControlRequest::Approval { mut event }) => {
+                    stamp_origin(&mut event, remote_peer.as_deref());
+                    handle_approval_request(ctx, *event)
+                }
+                Ok(_) if token.is_some() => {
+                    ControlResponse::error("Only hook events are accepted from remote hosts")
+                }
+                Ok(request) => handle_control_request(&ctx.app, &ctx.state, request),
+                Err(e) => ControlResponse::error(e),
+            };
+            if !write_json_line(&mut writer, &response) {
+                return;
+            }
+            if follow {
+                if let Some(follower) = writer.into_follower() {
+                    if let Ok(mut followers) = ctx.followers.lock() {
+                        followers.push(follower);
+                    }
+                }
+                return;
+            }
+            continue;
+        }
+
+        if let Some(mut event) = parse_line(&line) {
+            stamp_origin(&mut event, remote_peer.as_deref());
+            if ctx.queue.send(IngestJob::Event(Box::new(event))).is_err() {
+                log::error!(target: "eocc.ingest", "Ingest worker stopped; event dropped");
+            }
+        }
+        // Acknowledge even unparseable lines so the hook does not re-queue them
+        if writer.write_all(b"ok\n").is_err() {
+            return;
+        }
+    }
+}
+
+/// Apply queued jobs to the state. A burst of events is applied under one lock and reported to
+/// the frontend once.
+fn run_ingest_worker(
+    app: tauri::AppHandle,
+    state: Arc<Mutex<AppState>>,
+    followers: Followers,
+    jobs: std::sync::mpsc::Receiver<IngestJob>,
+) {
+    let history = app.try_state::<Arc<EventStore>>();
+    let history = history.as_deref().map(Arc::as_ref);
+    while let Ok(job) = jobs.recv() {
+        let Ok(mut state_guard) = state.lock() else {
+            log::error!(target: "eocc.ingest", "Failed to acquire state lock in ingest worker");
+            return;
+        };
+        let mut ingested = Vec::new();
+        for job in std::iter::once(job).chain(jobs.try_iter()) {
+            match job {
+                IngestJob::Event(event) => {
+                    ingest_event(history, &mut state_guard, (*event).clone());
+                    ingested.push(*event);
+                }
+                IngestJob::Locked(f) => f(history, &mut state_guard),
+            }
+        }
+        emit_budget_alerts(&app, &mut state_guard);
+        notify_status_changes(&app, &state_guard);
+        update_tray_and_badge(&app, &state_guard);
+        emit_state_update(&app, &state_guard);
+        save_runtime_state(&app, &state_guard);
+        drop(state_guard);
+        for event in &ingested {
+            notify_followers(&followers, event);
+        }
+    }
+}
+
+/// Start the ingestion server. Returns an error if the socket cannot be bound,
+/// in which case the caller should fall back to watching events.jsonl.
+pub fn start_ingest_server(
+    app_handle: tauri::AppHandle,
+    state: Arc<Mutex<AppState>>,
+) -> Result<(), String> {
+    let listener = bind(&app_handle)?;
+    log::info!(target: "eocc.ingest", "Listening for hook events on {:?}", listener.local_addr());
+
+    let followers: Followers = Arc::new(Mutex::new(Vec::new()));
+    let (queue, jobs) = channel();
+    {
+        let app_handle = app_handle.clone();
+        let state = Arc::clone(&state);
+        let followers = Arc::clone(&followers);
+        std::thread::spawn(move || run_ingest_worker(app_handle, state, followers, jobs));
+    }
+    let ctx = Arc::new(IngestContext {
+        app: app_handle.clone(),
+        state,
+        followers,
+        queue,
+    });
+    app_handle.manage(Arc::new(RemoteIngest::new(Arc::clone(&ctx))));
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            match stream {
+                Ok(stream) => {
+                    let ctx = Arc::clone(&ctx);
+                    std::thread::spawn(move || handle_connection(&ctx, stream, None));
+                }
+                Err(e) => {
+                    log::warn!(target: "eocc.ingest", "Failed to accept connection: {:?}", e);
+                }
+            }
+        }
+    });
+
+    Ok(())
+}
+
+/// TCP listener for hooks on remote hosts; at most one runs at a time
+pub struct RemoteIngest {
+    /// Shared with the socket server so `eocc events --follow` sees remote events too
+    ctx: Arc<IngestContext>,
+    /// Set to stop the accept loop of the running listener
+    stop: Mutex<Option<Arc<AtomicBool>>>,
+}
+
+impl RemoteIngest {
+    fn new(ctx: Arc<IngestContext>) -> Self {
+        Self {
+            ctx,
+            stop: Mutex::new(None),
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.stop.lock().map(|stop| stop.is_some()).unwrap_or(false)
+    }
+
+    /// Start listening with the given token, restarting if already running
+    pub fn start(&self, settings: &RemoteSettings, token: String) -> Result<(), String> {
+        self.stop();
+
+        let listener = bind_with_retry(settings.bind_ip()?, settings.port)?;
+        listener
+            .set_nonblocking(true)
+            .map_err(|e| format!("Failed to configure listener: {}", e))?;
+        let stop = Arc::new(AtomicBool::new(false));
+        if let Ok(mut current) = self.stop.lock() {
+            *current = Some(Arc::clone(&stop));
+        }
+
+        log::info!(
+            target: "eocc.ingest",
+            "Listening for remote hook events on {}",
+            SocketAddr::from((settings.bind_ip()?, settings.port))
+        );
+        let ctx = Arc::clone(&self.ctx);
+        let token: Arc<str> = token.into();
+        std::thread::spawn(move || {
+            while !stop.load(Ordering::Relaxed) {
+                match listener.accept() {
+                    Ok((stream, addr)) => {
+                        log::debug!(target: "eocc.ingest", "Remote connection from {}", addr);
+                        // The listener is non-blocking; connections are served blocking
+                        let _ = stream.set_nonblocking(false);
+                        let ctx = Arc::clone(&ctx);
+                        let token = Arc::clone(&token);
+                        std::thread::spawn(move || handle_connection(&ctx, stream, Some(&token)));
+                    }
+                    Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => {
+                        std::thread::sleep(ACCEPT_POLL_INTERVAL);
+                    }
+                    Err(e) => {
+                        log::warn!(target: "eocc.ingest", "Failed to accept remote connection: {:?}", e);
+                        std::thread::sleep(ACCEPT_POLL_INTERVAL);
+                    }
+                }
+            }
+            log::info!(target: "eocc.ingest", "Remote listener stopped");
+        });
+        Ok(())
+    }
+
+    pub fn stop(&self) {
+        if let Ok(mut current) = self.stop.lock() {
+            if let Some(stop) = current.take() {
+                stop.store(true, Ordering::Relaxed);
+            }
+        }
+    }
+}
+
+/// Remove the socket file so hooks fall back to the queue file while the app is down
+pub fn remove_socket(app: &tauri::AppHandle) {
+    if let Ok(path) = get_socket_path(app) {
+        let _ = std::fs::remove_file(path);
+    }
+}
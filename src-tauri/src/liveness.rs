@@ -0,0 +1,194 @@
+use std::collections::HashSet;
+use std::process::Command;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::Manager;
+
+use crate::history::{parse_timestamp, EventStore};
+use crate::notifications::notify_status_changes;
+use crate::persist::save_runtime_state;
+use crate::state::{AppState, EventInfo, EventType, SessionInfo, SessionStatus};
+use crate::tmux;
+use crate::tray::{emit_state_update, update_tray_and_badge};
+
+/// How long the snapshots of a removed session are kept when auto-archive is off
+const ENDED_SNAPSHOTS_MINUTES: u32 = 24 * 60;
+
+/// Interval between liveness checks
+const CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Check whether a process exists (signal 0 only checks permissions/existence)
+fn is_pid_alive(pid: u32) -> bool {
+    #[cfg(unix)]
+    {
+        Command::new("kill")
+            .args(["-0", &pid.to_string()])
+            .output()
+            .map(|o| o.status.success())
+            .unwrap_or(true)
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = pid;
+        true
+    }
+}
+
+/// Decide whether a session is dead. Unknown (no PID, no pane, other host) counts as alive.
+fn is_session_dead(session: &SessionInfo, live_panes: Option<&HashSet<String>>) -> bool {
+    if session.is_remote() {
+        return false;
+    }
+    // A PID reported from inside a container belongs to the container's PID namespace
+    if session.claude_pid != 0
+        && session.container_id.is_empty()
+        && !is_pid_alive(session.claude_pid)
+    {
+        return true;
+    }
+    if let Some(panes) = live_panes {
+        if !session.tmux_pane.is_empty() && !panes.contains(&session.tmux_pane) {
+            return true;
+        }
+    }
+    false
+}
+
+/// History record of a session found dead; it ends the session's status intervals in the stats
+fn disconnect_event(session: &SessionInfo, timestamp: &str) -> EventInfo {
+    EventInfo {
+        timestamp: timestamp.to_string(),
+        event_type: EventType::Disconnected,
+        matcher: String::new(),
+        project_name: session.project_name.clone(),
+        project_dir: session.project_dir.clone(),
+        session_id: session.session_id.clone(),
+        message: String::new(),
+        notification_type: Default::default(),
+        tool_name: String::new(),
+        tmux_pane: session.tmux_pane.clone(),
+        npx_path: String::new(),
+        tmux_path: String::new(),
+        claude_pid: session.claude_pid,
+        host: session.host.clone(),
+        container_id: session.container_id.clone(),
+        remote_peer: session.remote_peer.clone(),
+        transcript_path: session.transcript_path.clone(),
+        tool_input: serde_json::Value::Null,
+    }
+}
+
+/// Sessions that are not Disconnected yet, copied out so they can be probed without the lock
+fn sessions_to_probe(state: &AppState) -> Vec<(String, SessionInfo)> {
+    state
+        .sessions
+        .iter()
+        .filter(|(_, s)| s.status != SessionStatus::Disconnected)
+        .map(|(key, s)| (key.clone(), s.clone()))
+        .collect()
+}
+
+/// Probe processes and tmux panes; returns the key and last event of each dead session
+fn find_dead_sessions(sessions: Vec<(String, SessionInfo)>) -> Vec<(String, String)> {
+    let needs_panes = sessions.iter().any(|(_, s)| !s.tmux_pane.is_empty());
+    let live_panes: Option<HashSet<String>> = if needs_panes && tmux::is_tmux_available() {
+        tmux::list_panes()
+            .ok()
+            .map(|panes| panes.into_iter().map(|p| p.pane_id).collect())
+    } else {
+        None
+    };
+
+    sessions
+        .into_iter()
+        .filter(|(_, s)| is_session_dead(s, live_panes.as_ref()))
+        .map(|(key, s)| (key, s.last_event))
+        .collect()
+}
+
+/// Mark the sessions found dead as Disconnected (recording it in history), archive those
+/// disconnected for too long and delete expired snapshots of removed sessions. A session that sent an event since it was probed is left alone.
+/// Returns true if the state changed.
+pub fn check_sessions(
+    history: Option<&EventStore>,
+    state: &mut AppState,
+    dead: &[(String, String)],
+) -> bool {
+    let now = chrono::Utc::now();
+    let timestamp = now.to_rfc3339_opts(chrono::SecondsFormat::Millis, true);
+
+    let mut changed = false;
+    for (key, last_event) in dead {
+        let Some(session) = state
+            .sessions
+            .get(key)
+            .filter(|s| s.status != SessionStatus::Disconnected && s.last_event == *last_event)
+        else {
+            continue;
+        };
+        log::info!(target: "eocc.liveness", "Session {} is no longer running", key);
+        if let Some(store) = history {
+            store.append(&disconnect_event(session, &timestamp));
+        }
+        changed |= state.mark_disconnected(key, &timestamp);
+    }
+
+    let archive_minutes = state.settings.auto_archive_disconnected_minutes;
+    if archive_minutes > 0 {
+        let cutoff = now - chrono::Duration::minutes(i64::from(archive_minutes));
+        let expired: Vec<String> = state
+            .sessions
+            .iter()
+            .filter(|(_, s)| s.status == SessionStatus::Disconnected)
+            .filter(|(_, s)| parse_timestamp(&s.disconnected_at).is_some_and(|t| t < cutoff))
+            .map(|(key, _)| key.clone())
+            .collect();
+        for key in &expired {
+            log::info!(target: "eocc.liveness", "Archiving disconnected session {}", key);
+            state.archive_session(key);
+            changed = true;
+        }
+    }
+
+    // Ended and removed sessions keep their snapshots for the archive delay, in case they are
+    // resumed
+    let keep_minutes = if archive_minutes > 0 {
+        archive_minutes
+    } else {
+        ENDED_SNAPSHOTS_MINUTES
+    };
+    changed |=
+        state.discard_ended_snapshots(now - chrono::Duration::minutes(i64::from(keep_minutes)));
+
+    changed
+}
+
+/// Periodically check that sessions are still alive
+pub fn start_liveness_monitor(app_handle: tauri::AppHandle, state: Arc<Mutex<AppState>>) {
+    std::thread::spawn(move || loop {
+        std::thread::sleep(CHECK_INTERVAL);
+        let history = app_handle.try_state::<Arc<EventStore>>();
+
+        let Ok(sessions) = state
+            .lock()
+            .map(|state_guard| sessions_to_probe(&state_guard))
+        else {
+            eprintln!("[eocc] Failed to acquire state lock in liveness monitor");
+            continue;
+        };
+        // `kill -0` and tmux may be slow; events and commands must not wait for them
+        let dead = find_dead_sessions(sessions);
+
+        let Ok(mut state_guard) = state.lock() else {
+            eprintln!("[eocc] Failed to acquire state lock in liveness monitor");
+            continue;
+        };
+        if check_sessions(history.as_deref().map(Arc::as_ref), &mut state_guard, &dead) {
+            // Webhooks may be set up for Disconnected
+            notify_status_changes(&app_handle, &state_guard);
+            update_tray_and_badge(&app_handle, &state_guard);
+            emit_state_update(&app_handle, &state_guard);
+            save_runtime_state(&app_handle, &state_guard);
+        }
+    });
+}
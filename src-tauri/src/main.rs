@@ -1,17 +1,35 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+mod api;
+mod approval;
 mod commands;
 mod constants;
+mod container;
+mod cost;
 mod difit;
+mod dnd;
 mod events;
 mod git;
+mod git_cache;
+mod history;
+#[cfg(unix)]
+mod ingest;
+mod liveness;
 mod menu;
+mod notifications;
 mod persist;
+mod policy;
+mod reminders;
+mod remote;
 mod settings;
 mod setup;
+mod snapshot;
 mod state;
+mod stats;
 mod tmux;
+mod transcript;
 mod tray;
+mod webhooks;
 
 use notify::{Config, RecommendedWatcher, RecursiveMode, Watcher};
 use std::fs;
@@ -23,21 +41,35 @@ use tauri::{
     Emitter, Manager, WebviewUrl, WebviewWindowBuilder,
 };
 
+use api::ApiServer;
+use approval::ApprovalDecision;
 use difit::DifitProcessRegistry;
 use tauri_plugin_log::RotationStrategy;
 
 use commands::{
-    check_claude_settings, clear_all_sessions, get_always_on_top, get_dashboard_data,
-    get_repo_git_info, get_settings, get_setup_status, install_hook, open_claude_settings,
-    open_diff, open_tmux_viewer, remove_session, set_always_on_top, set_opacity_active,
-    set_opacity_inactive, set_window_size_for_setup, tmux_capture_pane, tmux_get_pane_size,
-    tmux_is_available, tmux_list_panes, tmux_send_keys,
+    acknowledge_session, check_claude_settings, clear_all_sessions, get_always_on_top,
+    get_api_info, get_costs, get_dashboard_data, get_policy, get_remote_info, get_repo_git_info,
+    get_session_timeline, get_settings, get_setup_status, get_stats, install_hook,
+    list_session_turns, open_claude_settings, open_diff, open_tmux_viewer, pause_notifications,
+    query_events, regenerate_api_token, regenerate_remote_token, remove_session, resolve_approval,
+    set_always_on_top, set_api_enabled, set_approval_timeout, set_auto_archive_minutes,
+    set_budgets, set_dnd_schedule, set_history_retention_days, set_notification_settings,
+    set_opacity_active, set_opacity_inactive, set_path_mappings, set_policy, set_price_table,
+    set_reminder_settings, set_remote_listener, set_remote_ssh_hosts, set_session_alias,
+    set_session_muted, set_session_pinned, set_webhooks, set_window_size_for_setup, snooze_session,
+    test_webhook, tmux_capture_pane, tmux_get_pane_size, tmux_is_available, tmux_list_panes,
+    tmux_send_keys,
 };
 use constants::{ICON_NORMAL, MINI_VIEW_HEIGHT, MINI_VIEW_WIDTH};
 use events::drain_events_queue;
-use menu::{build_app_menu, build_tray_menu, parse_opacity_menu_id};
+use history::EventStore;
+use menu::{
+    build_app_menu, build_tray_menu, parse_approval_menu_id, parse_dnd_menu_id,
+    parse_opacity_menu_id, parse_session_menu_id,
+};
+use notifications::{notify_status_changes, Notifier};
 use persist::{load_runtime_state, save_runtime_state};
-use settings::{get_app_log_dir, get_log_dir, load_settings, save_settings};
+use settings::{get_app_log_dir, get_history_dir, get_log_dir, load_settings, save_settings};
 use state::{AppState, ManagedState};
 use tray::{emit_state_update, update_tray_and_badge};
 
@@ -121,6 +153,8 @@ fn start_file_watcher(app_handle: tauri::AppHandle, state: Arc<Mutex<AppState>>)
                     let new_events = drain_events_queue(&app_handle, &mut state_guard);
 
                     if !new_events.is_empty() {
+                        cost::emit_budget_alerts(&app_handle, &mut state_guard);
+                        notify_status_changes(&app_handle, &state_guard);
                         update_tray_and_badge(&app_handle, &state_guard);
                         emit_state_update(&app_handle, &state_guard);
                         save_runtime_state(&app_handle, &state_guard);
@@ -138,6 +172,7 @@ fn start_file_watcher(app_handle: tauri::AppHandle, state: Arc<Mutex<AppState>>)
 fn main() {
     let state = Arc::new(Mutex::new(AppState::default()));
     let difit_registry = Arc::new(DifitProcessRegistry::new());
+    let api_server = Arc::new(ApiServer::new());
 
     let state_clone = Arc::clone(&state);
     let state_for_managed = Arc::clone(&state);
@@ -154,9 +189,11 @@ fn main() {
         .plugin(tauri_plugin_shell::init())
         .manage(ManagedState(state_for_managed))
         .manage(difit_registry_clone)
+        .manage(Arc::clone(&api_server))
         .invoke_handler(tauri::generate_handler![
             get_dashboard_data,
             remove_session,
+            get_session_timeline,
             clear_all_sessions,
             get_always_on_top,
             set_always_on_top,
@@ -165,6 +202,37 @@ fn main() {
             set_opacity_inactive,
             get_repo_git_info,
             open_diff,
+            query_events,
+            get_stats,
+            get_costs,
+            set_notification_settings,
+            set_reminder_settings,
+            set_dnd_schedule,
+            pause_notifications,
+            set_session_alias,
+            set_session_pinned,
+            set_session_muted,
+            snooze_session,
+            acknowledge_session,
+            set_webhooks,
+            test_webhook,
+            resolve_approval,
+            set_approval_timeout,
+            get_policy,
+            set_policy,
+            get_api_info,
+            set_api_enabled,
+            regenerate_api_token,
+            get_remote_info,
+            set_remote_listener,
+            regenerate_remote_token,
+            set_remote_ssh_hosts,
+            list_session_turns,
+            set_path_mappings,
+            set_price_table,
+            set_budgets,
+            set_history_retention_days,
+            set_auto_archive_minutes,
             set_window_size_for_setup,
             // Setup commands
             get_setup_status,
@@ -190,24 +258,56 @@ fn main() {
             }
 
             // Load settings and existing events
+            let use_file_watcher;
             {
                 let mut state_guard = state_for_tray.lock().map_err(|_| {
                     tauri::Error::Anyhow(anyhow::anyhow!("Failed to acquire state lock"))
                 })?;
                 state_guard.settings = load_settings(&app_handle);
+                state_guard.cost = cost::load_cost_ledger(&app_handle);
+                // Open the durable event history before draining queued events into it
+                match get_history_dir(&app_handle) {
+                    Ok(dir) => {
+                        let retention_days = state_guard.settings.history_retention_days;
+                        app.manage(Arc::new(EventStore::open(dir, retention_days)));
+                    }
+                    Err(e) => eprintln!("[eocc] Cannot open event history: {}", e),
+                }
                 // Restore previous in-memory state snapshot (sessions/recent events/cached paths)
                 if let Some(restored) = load_runtime_state(&app_handle) {
                     state_guard.sessions = restored.sessions;
+                    state_guard.timelines = restored.timelines;
                     state_guard.recent_events = restored.recent_events;
                     state_guard.cached_paths = restored.cached_paths.clone();
+                    state_guard.session_prefs = restored.session_prefs;
+                    state_guard.ended_snapshots = restored.ended_snapshots;
+                    state_guard.prune_session_prefs();
                     // Also set the cached tmux path in the tmux module
                     tmux::set_cached_tmux_path(&restored.cached_paths.tmux_path);
                 }
+                // Start listening before draining so new hook events go to the socket
+                // rather than the queue file; they are handled once this lock is released.
+                #[cfg(unix)]
+                let listening =
+                    match ingest::start_ingest_server(app_handle.clone(), Arc::clone(&state_clone))
+                    {
+                        Ok(()) => true,
+                        Err(e) => {
+                            log::error!(target: "eocc.ingest", "Socket server unavailable: {}", e);
+                            false
+                        }
+                    };
+                #[cfg(not(unix))]
+                let listening = false;
+                use_file_watcher = !listening;
+
                 // Drain any queued events written by the hook while app was not running
                 let new_events = drain_events_queue(&app_handle, &mut state_guard);
                 if !new_events.is_empty() {
                     save_runtime_state(&app_handle, &state_guard);
                 }
+                // Only transitions from here on raise desktop notifications
+                app.manage(Arc::new(Notifier::new(&state_guard)));
             }
 
             // Get initial settings
@@ -218,6 +318,9 @@ fn main() {
                 state_guard.settings.always_on_top
             };
 
+            // Serves git info to the dashboard, so it must exist before the window
+            git_cache::start_git_cache(app.handle().clone(), Arc::clone(&state_clone));
+
             // Create dashboard window
             let dashboard_window = create_dashboard_window(app, always_on_top)?;
 
@@ -346,7 +449,7 @@ fn main() {
                     },
                     "clear_sessions" => match state_for_tray_clone.lock() {
                         Ok(mut state_guard) => {
-                            state_guard.sessions.clear();
+                            state_guard.clear_sessions();
                             update_tray_and_badge(app, &state_guard);
                             emit_state_update(app, &state_guard);
                             save_runtime_state(app, &state_guard);
@@ -355,7 +458,59 @@ fn main() {
                             eprintln!("[eocc] Failed to acquire lock for clear_sessions: {:?}", e)
                         }
                     },
-                    _ => {}
+                    other => {
+                        if let Some(minutes) = parse_dnd_menu_id(other) {
+                            match state_for_tray_clone.lock() {
+                                Ok(mut state_guard) => {
+                                    dnd::pause(app, &mut state_guard, minutes);
+                                }
+                                Err(e) => {
+                                    eprintln!("[eocc] Failed to acquire lock for dnd: {:?}", e)
+                                }
+                            }
+                            return;
+                        }
+                        if let Some((action, session_id)) = parse_session_menu_id(other) {
+                            match state_for_tray_clone.lock() {
+                                Ok(mut state_guard) => {
+                                    let snooze_minutes =
+                                        state_guard.settings.notifications.snooze_minutes;
+                                    let updated = state_guard
+                                        .update_session_prefs(session_id, |prefs| {
+                                            action.apply(prefs, snooze_minutes)
+                                        });
+                                    if updated.is_ok() {
+                                        update_tray_and_badge(app, &state_guard);
+                                        emit_state_update(app, &state_guard);
+                                        save_runtime_state(app, &state_guard);
+                                    }
+                                }
+                                Err(e) => {
+                                    eprintln!("[eocc] Failed to acquire lock for session: {:?}", e)
+                                }
+                            }
+                            return;
+                        }
+                        let Some((id, allow)) = parse_approval_menu_id(other) else {
+                            return;
+                        };
+                        match state_for_tray_clone.lock() {
+                            Ok(mut state_guard) => {
+                                let decision = if allow {
+                                    ApprovalDecision::Allow
+                                } else {
+                                    ApprovalDecision::Deny { message: None }
+                                };
+                                if approval::resolve_approval(&mut state_guard, id, decision) {
+                                    update_tray_and_badge(app, &state_guard);
+                                    emit_state_update(app, &state_guard);
+                                }
+                            }
+                            Err(e) => {
+                                eprintln!("[eocc] Failed to acquire lock for approval: {:?}", e)
+                            }
+                        }
+                    }
                 })
                 .on_tray_icon_event(|_tray, event| {
                     if let TrayIconEvent::Click {
@@ -369,8 +524,40 @@ fn main() {
                 })
                 .build(app)?;
 
-            // Start file watcher
-            start_file_watcher(app.handle().clone(), Arc::clone(&state_clone));
+            // Without the socket server, keep picking up events from the queue file
+            if use_file_watcher {
+                start_file_watcher(app.handle().clone(), Arc::clone(&state_clone));
+            }
+
+            // Serve the local API if the user opted in
+            let api_port = state_for_tray
+                .lock()
+                .ok()
+                .filter(|s| s.settings.api_enabled)
+                .map(|s| s.settings.api_port);
+            if let Some(port) = api_port {
+                if let Err(e) = api_server.start(&app_handle, Arc::clone(&state_clone), port) {
+                    log::error!(target: "eocc.api", "Failed to start local API: {}", e);
+                }
+            }
+
+            // Accept hooks from remote hosts if the user opted in
+            let remote_settings = state_for_tray
+                .lock()
+                .ok()
+                .map(|s| s.settings.remote.clone())
+                .filter(|r| r.enabled);
+            if let Some(remote_settings) = remote_settings {
+                if let Err(e) = remote::apply(&app_handle, &remote_settings) {
+                    log::error!(target: "eocc.ingest", "Failed to start remote listener: {}", e);
+                }
+            }
+
+            // Detect sessions whose Claude process or tmux pane went away
+            liveness::start_liveness_monitor(app.handle().clone(), Arc::clone(&state_clone));
+            snapshot::start_snapshot_worker(app.handle().clone(), Arc::clone(&state_clone));
+            reminders::start_reminder_scheduler(app.handle().clone(), Arc::clone(&state_clone));
+            dnd::start_dnd_monitor(app.handle().clone(), Arc::clone(&state_clone));
 
             Ok(())
         })
@@ -395,6 +582,9 @@ fn main() {
             if let tauri::RunEvent::Exit = event {
                 // Kill all difit processes on app exit
                 difit_registry.kill_all();
+                // Hooks fall back to the queue file until the next launch
+                #[cfg(unix)]
+                ingest::remove_socket(_app_handle);
             }
         });
 }
@@ -1,13 +1,16 @@
 use tauri::{
     menu::{
-        AboutMetadata, CheckMenuItemBuilder, Menu, MenuBuilder, MenuItem, MenuItemBuilder,
+        AboutMetadata, CheckMenuItemBuilder, IsMenuItem, Menu, MenuBuilder, MenuItemBuilder,
         PredefinedMenuItem, Submenu, SubmenuBuilder,
     },
     Runtime,
 };
 
+use crate::cost::is_over_daily_budget;
+use crate::events::describe_tool_call;
 use crate::state::{
-    AppState, EventInfo, EventType, NotificationType, SessionInfo, SessionStatus, Settings,
+    group_by_repo, AppState, EventInfo, EventType, NotificationType, SessionInfo, SessionPrefs,
+    SessionStatus, Settings,
 };
 
 /// Get emoji for event type
@@ -23,6 +26,10 @@ fn get_event_emoji(event: &EventInfo) -> &'static str {
         EventType::SessionEnd => "🏁",
         EventType::PostToolUse => "🔧",
         EventType::UserPromptSubmit => "💬",
+        EventType::PermissionRequest => "🔐",
+        EventType::PreToolUse => "🛠️",
+        EventType::ApprovalDecision => "⚖️",
+        EventType::Disconnected => "🔌",
         EventType::Unknown => "📌",
     }
 }
@@ -36,10 +43,33 @@ fn get_event_name(event_type: &EventType) -> &'static str {
         EventType::Stop => "stop",
         EventType::PostToolUse => "post_tool_use",
         EventType::UserPromptSubmit => "user_prompt_submit",
+        EventType::PermissionRequest => "permission_request",
+        EventType::PreToolUse => "pre_tool_use",
+        EventType::ApprovalDecision => "approval_decision",
+        EventType::Disconnected => "disconnected",
         EventType::Unknown => "unknown",
     }
 }
 
+/// Parse an approval menu ID into (request id, allow)
+/// Menu ID format: "approval_{allow|deny}_{id}"
+pub fn parse_approval_menu_id(menu_id: &str) -> Option<(&str, bool)> {
+    if let Some(id) = menu_id.strip_prefix("approval_allow_") {
+        Some((id, true))
+    } else {
+        menu_id.strip_prefix("approval_deny_").map(|id| (id, false))
+    }
+}
+
+/// Parse a Do Not Disturb menu ID into pause minutes (0 = resume)
+/// Menu ID format: "dnd_pause_{minutes}" or "dnd_resume"
+pub fn parse_dnd_menu_id(menu_id: &str) -> Option<u32> {
+    if menu_id == "dnd_resume" {
+        return Some(0);
+    }
+    menu_id.strip_prefix("dnd_pause_")?.parse().ok()
+}
+
 /// Parse opacity menu ID and return (is_active, opacity_value) if valid
 /// Menu ID format: "opacity_{inactive|active}_{10|30|50|70|80|90|100}"
 pub fn parse_opacity_menu_id(menu_id: &str) -> Option<(bool, f64)> {
@@ -87,6 +117,28 @@ fn build_opacity_submenu<R: Runtime>(
         .build()
 }
 
+fn build_dnd_submenu<R: Runtime>(
+    app: &tauri::AppHandle<R>,
+    settings: &Settings,
+) -> tauri::Result<Submenu<R>> {
+    let mut builder = SubmenuBuilder::new(app, "🌙 Do Not Disturb");
+    for (minutes, label) in [
+        (30, "Pause for 30 minutes"),
+        (60, "Pause for 1 hour"),
+        (120, "Pause for 2 hours"),
+        (480, "Pause for 8 hours"),
+    ] {
+        builder = builder
+            .item(&MenuItemBuilder::with_id(format!("dnd_pause_{}", minutes), label).build(app)?);
+    }
+    if settings.dnd.paused_until.is_some() {
+        builder = builder
+            .separator()
+            .item(&MenuItemBuilder::with_id("dnd_resume", "Resume Notifications").build(app)?);
+    }
+    builder.build()
+}
+
 fn build_help_events_submenu<R: Runtime>(
     app: &tauri::AppHandle<R>,
     events: &std::collections::VecDeque<EventInfo>,
@@ -211,26 +263,221 @@ pub fn build_app_menu<R: Runtime>(
     Ok(menu)
 }
 
+/// Format a token count compactly (e.g. 950, 45k, 1.2M)
+fn format_tokens(tokens: u64) -> String {
+    if tokens >= 1_000_000 {
+        format!("{:.1}M", tokens as f64 / 1_000_000.0)
+    } else if tokens >= 1_000 {
+        format!("{}k", tokens / 1_000)
+    } else {
+        tokens.to_string()
+    }
+}
+
+/// Per-session actions offered in the tray
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SessionMenuAction {
+    Pin,
+    Unpin,
+    Mute,
+    Unmute,
+    Snooze,
+    Unsnooze,
+}
+
+impl SessionMenuAction {
+    const ALL: [SessionMenuAction; 6] = [
+        Self::Pin,
+        Self::Unpin,
+        Self::Mute,
+        Self::Unmute,
+        Self::Snooze,
+        Self::Unsnooze,
+    ];
+
+    fn key(&self) -> &'static str {
+        match self {
+            Self::Pin => "pin",
+            Self::Unpin => "unpin",
+            Self::Mute => "mute",
+            Self::Unmute => "unmute",
+            Self::Snooze => "snooze",
+            Self::Unsnooze => "unsnooze",
+        }
+    }
+
+    fn menu_id(&self, session_id: &str) -> String {
+        format!("session_{}_{}", self.key(), session_id)
+    }
+
+    pub fn apply(&self, prefs: &mut SessionPrefs, snooze_minutes: u32) {
+        match self {
+            Self::Pin | Self::Unpin => prefs.pinned = *self == Self::Pin,
+            Self::Mute | Self::Unmute => prefs.muted = *self == Self::Mute,
+            Self::Snooze => prefs.snooze_for(snooze_minutes),
+            Self::Unsnooze => prefs.snoozed_until.clear(),
+        }
+    }
+}
+
+/// Parse a session action menu ID into (action, session id)
+/// Menu ID format: "session_{pin|unpin|mute|unmute|snooze|unsnooze}_{session_id}"
+pub fn parse_session_menu_id(menu_id: &str) -> Option<(SessionMenuAction, &str)> {
+    let rest = menu_id.strip_prefix("session_")?;
+    SessionMenuAction::ALL.into_iter().find_map(|action| {
+        rest.strip_prefix(action.key())
+            .and_then(|r| r.strip_prefix('_'))
+            .map(|id| (action, id))
+    })
+}
+
+/// Pin, mute and snooze toggles for one session
+fn build_session_submenu<R: Runtime>(
+    app: &tauri::AppHandle<R>,
+    session: &SessionInfo,
+    title: &str,
+    snooze_minutes: u32,
+) -> tauri::Result<Submenu<R>> {
+    let prefs = &session.prefs;
+    let (pin, pin_label) = if prefs.pinned {
+        (SessionMenuAction::Unpin, "Unpin".to_string())
+    } else {
+        (SessionMenuAction::Pin, "📌 Pin to Top".to_string())
+    };
+    let (mute, mute_label) = if prefs.muted {
+        (SessionMenuAction::Unmute, "Unmute".to_string())
+    } else {
+        (SessionMenuAction::Mute, "🔕 Mute".to_string())
+    };
+    let (snooze, snooze_label) = if prefs.is_snoozed() {
+        (SessionMenuAction::Unsnooze, "Unsnooze".to_string())
+    } else {
+        (
+            SessionMenuAction::Snooze,
+            format!("💤 Snooze for {} minutes", snooze_minutes),
+        )
+    };
+    let mut builder = SubmenuBuilder::new(app, title);
+    for (action, label) in [(pin, pin_label), (mute, mute_label), (snooze, snooze_label)] {
+        builder = builder.item(
+            &MenuItemBuilder::with_id(action.menu_id(&session.session_id), label).build(app)?,
+        );
+    }
+    builder.build()
+}
+
+/// Session row label: the alias, else the branch inside a repo group, else the project name
+fn session_title(session: &SessionInfo, in_group: bool, same_worktree_count: usize) -> String {
+    let name = match &session.repo {
+        _ if !session.prefs.alias.is_empty() => session.prefs.alias.clone(),
+        Some(repo) if in_group => format!("⎇ {}", repo.branch),
+        Some(repo) => format!("{} ⎇ {}", session.project_name, repo.branch),
+        None => session.project_name.clone(),
+    };
+    let mut title = format!("{} {}", session.status.emoji(), name);
+    // Disambiguate multiple sessions in one worktree with a short session id
+    if same_worktree_count > 1 && session.prefs.alias.is_empty() {
+        let short_id: String = session.session_id.chars().take(8).collect();
+        title.push_str(&format!(" ({})", short_id));
+    }
+    if session.is_remote() {
+        title.push_str(&format!(" @{}", session.host));
+    }
+    if session.prefs.pinned {
+        title.push_str(" 📌");
+    }
+    if session.prefs.is_silenced() {
+        title.push_str(" 🔕");
+    }
+    if session.context_tokens > 0 {
+        title.push_str(&format!(" · {} ctx", format_tokens(session.context_tokens)));
+    }
+    title
+}
+
 fn build_session_items<R: Runtime>(
     app: &tauri::AppHandle<R>,
     sessions: &std::collections::HashMap<String, SessionInfo>,
-) -> tauri::Result<Vec<MenuItem<R>>> {
-    let mut items = Vec::new();
+    snooze_minutes: u32,
+) -> tauri::Result<Vec<Box<dyn IsMenuItem<R>>>> {
+    let mut items: Vec<Box<dyn IsMenuItem<R>>> = Vec::new();
 
     if !sessions.is_empty() {
         let header = MenuItemBuilder::with_id("sessions_header", "Sessions")
             .enabled(false)
             .build(app)?;
-        items.push(header);
-
-        for session in sessions.values() {
-            let emoji = session.status.emoji();
-            let title = format!("{} {}", emoji, session.project_name);
-            let item =
-                MenuItemBuilder::with_id(format!("session_{}", session.project_name), &title)
+        items.push(Box::new(header));
+
+        // Pinned sessions first, then sessions of the same repository next to each other
+        let mut sorted: Vec<&SessionInfo> = sessions.values().collect();
+        sorted.sort_by(|a, b| {
+            (
+                !a.prefs.pinned,
+                &a.project_name,
+                &a.project_dir,
+                &a.session_id,
+            )
+                .cmp(&(
+                    !b.prefs.pinned,
+                    &b.project_name,
+                    &b.project_dir,
+                    &b.session_id,
+                ))
+        });
+
+        // Sessions of a repository with several sessions are nested under its name
+        for (group_index, group) in group_by_repo(sorted.iter().copied()).iter().enumerate() {
+            let in_group = group.session_count() > 1;
+            let indent = if in_group { "    " } else { "" };
+            if in_group {
+                items.push(Box::new(
+                    MenuItemBuilder::with_id(
+                        format!("repo_header_{}", group_index),
+                        format!("📁 {}", group.name),
+                    )
                     .enabled(false)
-                    .build(app)?;
-            items.push(item);
+                    .build(app)?,
+                ));
+            }
+
+            for worktree in &group.worktrees {
+                for session in worktree
+                    .session_ids
+                    .iter()
+                    .filter_map(|id| sessions.get(id))
+                {
+                    let title = format!(
+                        "{}{}",
+                        indent,
+                        session_title(session, in_group, worktree.session_ids.len())
+                    );
+                    items.push(Box::new(build_session_submenu(
+                        app,
+                        session,
+                        &title,
+                        snooze_minutes,
+                    )?));
+
+                    // Show what Claude said last, truncated to keep the menu narrow
+                    if let Some(line) = session
+                        .last_assistant_message
+                        .lines()
+                        .find(|l| !l.is_empty())
+                    {
+                        let mut preview: String = line.chars().take(60).collect();
+                        if line.chars().count() > 60 {
+                            preview.push('…');
+                        }
+                        let message_item = MenuItemBuilder::with_id(
+                            format!("session_message_{}", session.session_id),
+                            format!("{}    💬 {}", indent, preview),
+                        )
+                        .enabled(false)
+                        .build(app)?;
+                        items.push(Box::new(message_item));
+                    }
+                }
+            }
         }
     }
 
@@ -274,10 +521,18 @@ pub fn build_tray_menu<R: Runtime>(
         })
         .count();
 
+    let disconnected_count = state.disconnected_session_count();
+
     let header_text = if waiting_count > 0 {
         format!("⚠️ {} session(s) waiting", waiting_count)
     } else if state.sessions.is_empty() {
         "No active sessions".to_string()
+    } else if disconnected_count > 0 {
+        format!(
+            "{} active session(s), {} disconnected",
+            state.sessions.len() - disconnected_count,
+            disconnected_count
+        )
     } else {
         format!("{} active session(s)", state.sessions.len())
     };
@@ -286,8 +541,62 @@ pub fn build_tray_menu<R: Runtime>(
         .enabled(false)
         .build(app)?;
 
+    let dnd_item = match state.settings.dnd.status_label() {
+        Some(label) => Some(
+            MenuItemBuilder::with_id("dnd_status", format!("🌙 {}", label))
+                .enabled(false)
+                .build(app)?,
+        ),
+        None => None,
+    };
+
+    // Today's estimated spend, flagged when over the daily budget
+    let today_usd = state.cost.today_usd();
+    let cost_item = if today_usd > 0.0 {
+        let marker = if is_over_daily_budget(state) {
+            "🚨"
+        } else {
+            "💸"
+        };
+        let label = format!("{} Today: ${:.2}", marker, today_usd);
+        Some(
+            MenuItemBuilder::with_id("cost_today", &label)
+                .enabled(false)
+                .build(app)?,
+        )
+    } else {
+        None
+    };
+
+    // Pending approvals, each with Allow/Deny
+    let mut approval_items = Vec::new();
+    for request in state.approvals.list() {
+        let mut label = format!(
+            "🔐 {}: {}",
+            request.project_name,
+            describe_tool_call(&request.tool_name, &request.tool_input)
+        );
+        if label.chars().count() > 60 {
+            label = format!("{}…", label.chars().take(59).collect::<String>());
+        }
+        let allow = MenuItemBuilder::with_id(format!("approval_allow_{}", request.id), "Allow")
+            .build(app)?;
+        let deny =
+            MenuItemBuilder::with_id(format!("approval_deny_{}", request.id), "Deny").build(app)?;
+        approval_items.push(
+            SubmenuBuilder::new(app, &label)
+                .item(&allow)
+                .item(&deny)
+                .build()?,
+        );
+    }
+
     // Session items
-    let session_items = build_session_items(app, &state.sessions)?;
+    let session_items = build_session_items(
+        app,
+        &state.sessions,
+        state.settings.notifications.snooze_minutes,
+    )?;
 
     // Events submenu
     let events_submenu = build_events_submenu(app, &state.recent_events)?;
@@ -296,12 +605,27 @@ pub fn build_tray_menu<R: Runtime>(
     let open_dashboard = MenuItemBuilder::with_id("open_dashboard", "Open Dashboard").build(app)?;
     let open_logs = MenuItemBuilder::with_id("open_logs", "Open Log Folder").build(app)?;
     let clear_sessions = MenuItemBuilder::with_id("clear_sessions", "Clear Sessions").build(app)?;
+    let dnd_submenu = build_dnd_submenu(app, &state.settings)?;
 
     // Build menu
-    let menu = Menu::with_items(app, &[&header, &PredefinedMenuItem::separator(app)?])?;
+    let menu = Menu::with_items(app, &[&header])?;
+    if let Some(item) = &dnd_item {
+        menu.append(item)?;
+    }
+    if let Some(item) = &cost_item {
+        menu.append(item)?;
+    }
+    menu.append(&PredefinedMenuItem::separator(app)?)?;
+
+    if !approval_items.is_empty() {
+        for item in &approval_items {
+            menu.append(item)?;
+        }
+        menu.append(&PredefinedMenuItem::separator(app)?)?;
+    }
 
     for item in &session_items {
-        menu.append(item)?;
+        menu.append(item.as_ref())?;
     }
 
     if !session_items.is_empty() {
@@ -313,9 +637,26 @@ pub fn build_tray_menu<R: Runtime>(
         menu.append(&PredefinedMenuItem::separator(app)?)?;
     }
 
+    menu.append(&dnd_submenu)?;
     menu.append(&open_dashboard)?;
     menu.append(&open_logs)?;
     menu.append(&clear_sessions)?;
 
     Ok(menu)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_dnd_menu_ids() {
+        assert_eq!(parse_dnd_menu_id("dnd_resume"), Some(0));
+        assert_eq!(parse_dnd_menu_id("dnd_pause_30"), Some(30));
+        assert_eq!(parse_dnd_menu_id("dnd_pause_1440"), Some(1440));
+        assert_eq!(parse_dnd_menu_id("dnd_pause_"), None);
+        assert_eq!(parse_dnd_menu_id("dnd_pause_-5"), None);
+        assert_eq!(parse_dnd_menu_id("dnd_pause_abc"), None);
+        assert_eq!(parse_dnd_menu_id("opacity_active_50"), None);
+    }
+}
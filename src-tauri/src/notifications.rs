@@ -0,0 +1,415 @@
+//! Native desktop notifications when a session starts waiting or completes.
+//!
+//! Linux uses `notify-send` (freedesktop notifications) and supports actions.
+//! macOS uses `osascript`, which shows the notification without actions.
+//! While Do Not Disturb is on, notifications are queued for the digest sent when it ends.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::process::Command;
+use std::sync::{Arc, Mutex};
+use tauri::Manager;
+
+use crate::persist::save_runtime_state;
+use crate::state::{AppState, ManagedState, SessionInfo, SessionStatus};
+use crate::tmux;
+use crate::tray::{emit_state_update, update_tray_and_badge};
+use crate::webhooks;
+
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+const APP_NAME: &str = "Eyes on Claude Code";
+const MAX_BODY_CHARS: usize = 200;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationAction {
+    OpenDashboard,
+    /// Only offered when the session runs in a local tmux pane
+    JumpToPane,
+    Snooze,
+}
+
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+impl NotificationAction {
+    fn key(&self) -> &'static str {
+        match self {
+            NotificationAction::OpenDashboard => "open_dashboard",
+            NotificationAction::JumpToPane => "jump_to_pane",
+            NotificationAction::Snooze => "snooze",
+        }
+    }
+
+    fn label(&self) -> &'static str {
+        match self {
+            NotificationAction::OpenDashboard => "Open dashboard",
+            NotificationAction::JumpToPane => "Jump to pane",
+            NotificationAction::Snooze => "Snooze",
+        }
+    }
+
+    fn from_key(key: &str) -> Option<Self> {
+        [Self::OpenDashboard, Self::JumpToPane, Self::Snooze]
+            .into_iter()
+            .find(|action| action.key() == key)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatusNotification {
+    pub enabled: bool,
+    #[serde(default)]
+    pub actions: Vec<NotificationAction>,
+}
+
+impl StatusNotification {
+    fn with_all_actions() -> Self {
+        Self {
+            enabled: true,
+            actions: vec![
+                NotificationAction::OpenDashboard,
+                NotificationAction::JumpToPane,
+                NotificationAction::Snooze,
+            ],
+        }
+    }
+}
+
+/// Which statuses raise a notification, and the actions each one offers
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NotificationSettings {
+    pub waiting_permission: StatusNotification,
+    pub waiting_input: StatusNotification,
+    pub completed: StatusNotification,
+    /// How long "Snooze" silences a session
+    pub snooze_minutes: u32,
+}
+
+impl Default for NotificationSettings {
+    fn default() -> Self {
+        Self {
+            waiting_permission: StatusNotification::with_all_actions(),
+            waiting_input: StatusNotification::with_all_actions(),
+            completed: StatusNotification {
+                enabled: true,
+                actions: vec![
+                    NotificationAction::OpenDashboard,
+                    NotificationAction::JumpToPane,
+                ],
+            },
+            snooze_minutes: 15,
+        }
+    }
+}
+
+impl NotificationSettings {
+    fn for_status(&self, status: &SessionStatus) -> Option<&StatusNotification> {
+        let config = match status {
+            SessionStatus::WaitingPermission => &self.waiting_permission,
+            SessionStatus::WaitingInput => &self.waiting_input,
+            SessionStatus::Completed => &self.completed,
+            SessionStatus::Active | SessionStatus::Disconnected => return None,
+        };
+        config.enabled.then_some(config)
+    }
+}
+
+#[derive(Default)]
+struct NotifierInner {
+    /// Last status seen per session, to notify only on transitions
+    statuses: HashMap<String, SessionStatus>,
+    /// Transitions held back while Do Not Disturb is on, latest last
+    digest: Vec<SessionInfo>,
+}
+
+/// Tracks session statuses between state updates and raises notifications on transitions
+pub struct Notifier {
+    inner: Mutex<NotifierInner>,
+}
+
+impl Notifier {
+    /// Start from the current statuses so restored sessions do not notify again
+    pub fn new(state: &AppState) -> Self {
+        let statuses = state
+            .sessions
+            .iter()
+            .map(|(id, s)| (id.clone(), s.status.clone()))
+            .collect();
+        Self {
+            inner: Mutex::new(NotifierInner {
+                statuses,
+                digest: Vec::new(),
+            }),
+        }
+    }
+
+    /// Hold notifications back for the digest sent when Do Not Disturb ends
+    fn queue_digest(&self, sessions: Vec<SessionInfo>) {
+        if let Ok(mut inner) = self.inner.lock() {
+            for session in sessions {
+                inner.digest.retain(|s| s.session_id != session.session_id);
+                inner.digest.push(session);
+            }
+        }
+    }
+
+    pub fn take_digest(&self) -> Vec<SessionInfo> {
+        self.inner
+            .lock()
+            .map(|mut inner| std::mem::take(&mut inner.digest))
+            .unwrap_or_default()
+    }
+
+    /// Sessions that entered a new status since the last call and are not muted or snoozed
+    fn take_transitions(&self, state: &AppState) -> Vec<SessionInfo> {
+        let Ok(mut inner) = self.inner.lock() else {
+            return Vec::new();
+        };
+        inner
+            .statuses
+            .retain(|id, _| state.sessions.contains_key(id));
+
+        let mut changed = Vec::new();
+        for (id, session) in &state.sessions {
+            if inner.statuses.get(id) == Some(&session.status) {
+                continue;
+            }
+            inner.statuses.insert(id.clone(), session.status.clone());
+            if !session.prefs.is_silenced() {
+                changed.push(session.clone());
+            }
+        }
+        changed
+    }
+}
+
+/// Notify (desktop and webhooks) about sessions that just changed status
+pub fn notify_status_changes(app: &tauri::AppHandle, state: &AppState) {
+    let Some(notifier) = app.try_state::<Arc<Notifier>>() else {
+        return;
+    };
+    let transitions = notifier.take_transitions(state);
+    if transitions.is_empty() {
+        return;
+    }
+    webhooks::dispatch(state, &transitions);
+
+    if state.settings.dnd.is_active() {
+        let settings = &state.settings.notifications;
+        notifier.queue_digest(
+            transitions
+                .into_iter()
+                .filter(|s| settings.for_status(&s.status).is_some())
+                .collect(),
+        );
+        return;
+    }
+    // The user is already looking at the dashboard
+    if is_dashboard_focused(app) {
+        return;
+    }
+    for session in &transitions {
+        notify_session(app, &state.settings.notifications, session);
+    }
+}
+
+pub fn is_dashboard_focused(app: &tauri::AppHandle) -> bool {
+    app.get_webview_window("dashboard")
+        .and_then(|w| w.is_focused().ok())
+        .unwrap_or(false)
+}
+
+/// Show a desktop notification for the session's current status, if enabled for it
+pub fn notify_session(
+    app: &tauri::AppHandle,
+    settings: &NotificationSettings,
+    session: &SessionInfo,
+) {
+    let Some(config) = settings.for_status(&session.status) else {
+        return;
+    };
+    let actions: Vec<NotificationAction> = config
+        .actions
+        .iter()
+        .copied()
+        .filter(|a| {
+            *a != NotificationAction::JumpToPane
+                || (!session.tmux_pane.is_empty() && !session.is_remote())
+        })
+        .collect();
+    show_notification(app, session, actions, settings.snooze_minutes);
+}
+
+pub fn status_text(status: &SessionStatus) -> &'static str {
+    match status {
+        SessionStatus::WaitingPermission => "Waiting for permission",
+        SessionStatus::WaitingInput => "Waiting for input",
+        SessionStatus::Completed => "Completed",
+        SessionStatus::Active => "Active",
+        SessionStatus::Disconnected => "Disconnected",
+    }
+}
+
+fn notification_text(session: &SessionInfo) -> (String, String) {
+    let title = format!(
+        "{} {} - {}",
+        session.status.emoji(),
+        session.display_name(),
+        status_text(&session.status)
+    );
+    let body = if !session.waiting_for.is_empty() {
+        session.waiting_for.clone()
+    } else {
+        session.last_assistant_message.clone()
+    };
+    let body = match body.char_indices().nth(MAX_BODY_CHARS) {
+        Some((end, _)) => format!("{}…", &body[..end]),
+        None => body,
+    };
+    (title, body)
+}
+
+fn handle_action(
+    app: &tauri::AppHandle,
+    action: NotificationAction,
+    session: &SessionInfo,
+    snooze_minutes: u32,
+) {
+    match action {
+        NotificationAction::OpenDashboard => {
+            if let Some(window) = app.get_webview_window("dashboard") {
+                let _ = window.show();
+                let _ = window.set_focus();
+            }
+        }
+        NotificationAction::JumpToPane => {
+            if let Err(e) = tmux::select_pane(&session.tmux_pane) {
+                log::warn!(target: "eocc.notifications", "Cannot jump to pane: {}", e);
+                let _ = crate::commands::open_tmux_viewer(session.tmux_pane.clone(), app.clone());
+            }
+        }
+        NotificationAction::Snooze => {
+            let Some(state) = app.try_state::<ManagedState>() else {
+                return;
+            };
+            let Ok(mut state_guard) = state.0.lock() else {
+                return;
+            };
+            if state_guard
+                .update_session_prefs(&session.session_id, |p| p.snooze_for(snooze_minutes))
+                .is_ok()
+            {
+                update_tray_and_badge(app, &state_guard);
+                emit_state_update(app, &state_guard);
+                save_runtime_state(app, &state_guard);
+            }
+        }
+    }
+}
+
+/// Whether the installed notify-send supports `--action` (libnotify 0.7.9+)
+#[cfg(target_os = "linux")]
+fn notify_send_supports_actions() -> bool {
+    static SUPPORTED: std::sync::OnceLock<bool> = std::sync::OnceLock::new();
+    *SUPPORTED.get_or_init(|| {
+        Command::new("notify-send")
+            .arg("--help")
+            .output()
+            .map(|o| String::from_utf8_lossy(&o.stdout).contains("--action"))
+            .unwrap_or(false)
+    })
+}
+
+fn show_notification(
+    app: &tauri::AppHandle,
+    session: &SessionInfo,
+    actions: Vec<NotificationAction>,
+    snooze_minutes: u32,
+) {
+    let (title, body) = notification_text(session);
+    let critical = session.status == SessionStatus::WaitingPermission;
+    let app = app.clone();
+    let session = session.clone();
+    send_notification(title, body, critical, actions, move |action| {
+        handle_action(&app, action, &session, snooze_minutes)
+    });
+}
+
+/// Show a notification without actions (the Do Not Disturb digest, budget alerts)
+pub fn show_message(title: String, body: String) {
+    send_notification(title, body, false, Vec::new(), |_| {});
+}
+
+#[cfg(target_os = "linux")]
+fn send_notification(
+    title: String,
+    body: String,
+    critical: bool,
+    actions: Vec<NotificationAction>,
+    on_action: impl FnOnce(NotificationAction) + Send + 'static,
+) {
+    let mut command = Command::new("notify-send");
+    command.arg(format!("--app-name={}", APP_NAME));
+    if critical {
+        command.arg("--urgency=critical");
+    }
+    let actions = if notify_send_supports_actions() {
+        actions
+    } else {
+        Vec::new()
+    };
+    for action in &actions {
+        command.arg(format!("--action={}={}", action.key(), action.label()));
+    }
+    command.arg("--").arg(title).arg(body);
+
+    // With actions, notify-send blocks until the notification is closed and prints the chosen action
+    std::thread::spawn(move || match command.output() {
+        Ok(output) => {
+            let chosen = String::from_utf8_lossy(&output.stdout);
+            if let Some(action) = NotificationAction::from_key(chosen.trim()) {
+                on_action(action);
+            }
+        }
+        Err(e) => {
+            log::warn!(target: "eocc.notifications", "Failed to run notify-send: {}", e);
+        }
+    });
+}
+
+#[cfg(target_os = "macos")]
+fn send_notification(
+    title: String,
+    body: String,
+    _critical: bool,
+    _actions: Vec<NotificationAction>,
+    _on_action: impl FnOnce(NotificationAction) + Send + 'static,
+) {
+    // Pass the text as arguments so it needs no AppleScript escaping
+    let mut command = Command::new("osascript");
+    command
+        .args([
+            "-e",
+            "on run argv",
+            "-e",
+            "display notification (item 2 of argv) with title (item 1 of argv)",
+            "-e",
+            "end run",
+        ])
+        .arg(title)
+        .arg(body);
+    std::thread::spawn(move || {
+        if let Err(e) = command.output() {
+            log::warn!(target: "eocc.notifications", "Failed to run osascript: {}", e);
+        }
+    });
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn send_notification(
+    _title: String,
+    _body: String,
+    _critical: bool,
+    _actions: Vec<NotificationAction>,
+    _on_action: impl FnOnce(NotificationAction) + Send + 'static,
+) {
+}
@@ -2,15 +2,86 @@ use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, VecDeque};
 use std::fs;
 
+use crate::cost::save_cost_ledger;
 use crate::settings::get_config_dir;
-use crate::state::{AppState, CachedPaths, EventInfo, SessionInfo};
+use crate::state::{
+    AppState, CachedPaths, EndedSnapshots, EventInfo, SessionInfo, SessionPrefs, SessionTimeline,
+};
+
+/// Current runtime_state.json format version
+/// - 1: sessions keyed by project_dir (files written before versioning have no field)
+/// - 2: sessions keyed by session_id
+const RUNTIME_STATE_VERSION: u32 = 2;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PersistedRuntimeState {
+    #[serde(default = "PersistedRuntimeState::legacy_version")]
+    pub version: u32,
     pub sessions: HashMap<String, SessionInfo>,
+    #[serde(default)]
+    pub timelines: HashMap<String, SessionTimeline>,
     pub recent_events: VecDeque<EventInfo>,
     #[serde(default)]
     pub cached_paths: CachedPaths,
+    #[serde(default)]
+    pub session_prefs: HashMap<String, SessionPrefs>,
+    #[serde(default)]
+    pub ended_snapshots: HashMap<String, EndedSnapshots>,
+}
+
+impl PersistedRuntimeState {
+    fn legacy_version() -> u32 {
+        1
+    }
+
+    /// Upgrade an older snapshot in place to the current format
+    fn migrate(&mut self) {
+        if self.version < 2 {
+            self.sessions = migrate_sessions_to_session_id(
+                std::mem::take(&mut self.sessions),
+                &self.recent_events,
+            );
+        }
+        self.version = RUNTIME_STATE_VERSION;
+    }
+}
+
+/// Re-key v1 sessions (keyed by project_dir) by session_id.
+/// The session_id is recovered from the newest recent event of the same project;
+/// if none is found the old key is kept so the session is not lost.
+fn migrate_sessions_to_session_id(
+    sessions: HashMap<String, SessionInfo>,
+    recent_events: &VecDeque<EventInfo>,
+) -> HashMap<String, SessionInfo> {
+    let mut migrated = HashMap::new();
+
+    for (old_key, mut session) in sessions {
+        let session_id = recent_events
+            .iter()
+            .rev()
+            .filter(|e| {
+                let event_project_key = if e.project_dir.is_empty() {
+                    &e.project_name
+                } else {
+                    &e.project_dir
+                };
+                *event_project_key == old_key
+            })
+            .map(|e| e.session_key())
+            .find(|key| *key != old_key)
+            .unwrap_or_else(|| old_key.clone());
+
+        log::info!(
+            target: "eocc.persist",
+            "Migrated session {} -> {}",
+            old_key,
+            session_id
+        );
+        session.session_id = session_id.clone();
+        migrated.insert(session_id, session);
+    }
+
+    migrated
 }
 
 fn get_runtime_state_file(app: &tauri::AppHandle) -> Result<std::path::PathBuf, String> {
@@ -23,7 +94,9 @@ pub fn load_runtime_state(app: &tauri::AppHandle) -> Option<PersistedRuntimeStat
         return None;
     }
     let content = fs::read_to_string(&path).ok()?;
-    serde_json::from_str(&content).ok()
+    let mut persisted: PersistedRuntimeState = serde_json::from_str(&content).ok()?;
+    persisted.migrate();
+    Some(persisted)
 }
 
 pub fn save_runtime_state(app: &tauri::AppHandle, state: &AppState) {
@@ -42,9 +115,13 @@ pub fn save_runtime_state(app: &tauri::AppHandle, state: &AppState) {
 
     let path = config_dir.join("runtime_state.json");
     let persisted = PersistedRuntimeState {
+        version: RUNTIME_STATE_VERSION,
         sessions: state.sessions.clone(),
+        timelines: state.timelines.clone(),
         recent_events: state.recent_events.clone(),
         cached_paths: state.cached_paths.clone(),
+        session_prefs: state.session_prefs.clone(),
+        ended_snapshots: state.ended_snapshots.clone(),
     };
 
     let content = match serde_json::to_string_pretty(&persisted) {
@@ -58,4 +135,6 @@ pub fn save_runtime_state(app: &tauri::AppHandle, state: &AppState) {
     if let Err(e) = fs::write(&path, content) {
         log::error!(target: "eocc.persist", "Failed to write runtime state: {:?}", e);
     }
+
+    save_cost_ledger(app, &state.cost);
 }
@@ -0,0 +1,434 @@
+//! Rule-based auto-approval for tool calls.
+//!
+//! `policy.json` in the app data dir holds an ordered list of rules. The first rule whose
+//! conditions all match a tool call decides it; unset conditions match anything.
+//! The file is re-read on every evaluation so hand edits apply immediately.
+
+use glob::{MatchOptions, Pattern};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+use crate::approval::ApprovalDecision;
+use crate::settings::get_config_dir;
+use crate::state::EventInfo;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PolicyAction {
+    Allow,
+    Deny,
+    /// Always leave the decision to the user
+    Ask,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PolicyRule {
+    pub action: PolicyAction,
+    /// Glob on the tool name, e.g. "Read" or "mcp__*"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool: Option<String>,
+    /// Glob on `tool_input.command` (Bash), matched against each command of a chain
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+    /// Glob on `tool_input.file_path` / `path` / `notebook_path`, after resolving `.` and `..`
+    /// (relative paths from the project dir). `*` stays within a directory, `**` crosses them.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    /// Glob on the session's project directory
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project_dir: Option<String>,
+    /// Reason shown to Claude when the rule denies a call
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Policy {
+    #[serde(default)]
+    pub rules: Vec<PolicyRule>,
+}
+
+/// The first rule matching a tool call (`index` is 0-based)
+#[derive(Debug, Clone)]
+pub struct PolicyMatch {
+    pub index: usize,
+    pub action: PolicyAction,
+    pub message: Option<String>,
+}
+
+impl PolicyMatch {
+    /// Answer for the hook, or None when the call is left to the user
+    pub fn decision(&self) -> Option<ApprovalDecision> {
+        match self.action {
+            PolicyAction::Allow => Some(ApprovalDecision::Allow),
+            PolicyAction::Deny => Some(ApprovalDecision::Deny {
+                message: Some(self.message.clone().unwrap_or_else(|| {
+                    format!(
+                        "Denied by Eyes on Claude Code policy rule {}",
+                        self.index + 1
+                    )
+                })),
+            }),
+            PolicyAction::Ask => None,
+        }
+    }
+}
+
+fn glob_matches(pattern: &Option<String>, value: Option<&str>) -> bool {
+    let Some(pattern) = pattern else {
+        return true;
+    };
+    let Some(value) = value else {
+        return false;
+    };
+    Pattern::new(pattern).is_ok_and(|p| p.matches(value))
+}
+
+/// Resolve `.` and `..` without touching the file system, taking relative paths from `base`.
+/// None if the result is still relative.
+fn normalize_path(path: &str, base: &str) -> Option<PathBuf> {
+    let mut normalized = PathBuf::new();
+    for component in Path::new(base).join(path).components() {
+        match component {
+            Component::CurDir => {}
+            // `..` at the root stays at the root, as it does on the file system
+            Component::ParentDir => {
+                if !normalized.pop() && !normalized.has_root() {
+                    return None;
+                }
+            }
+            _ => normalized.push(component),
+        }
+    }
+    normalized.is_absolute().then_some(normalized)
+}
+
+/// Whether a path glob matches the path of a tool call. An `allow` rule only matches the
+/// normalized absolute path, so `/home/me/app/*` does not allow `/home/me/app/../../.ssh/x`.
+/// `deny` and `ask` rules also match the path as given.
+fn path_matches(
+    pattern: &Option<String>,
+    path: Option<&str>,
+    project_dir: &str,
+    action: PolicyAction,
+) -> bool {
+    let Some(pattern) = pattern else {
+        return true;
+    };
+    let (Some(path), Ok(pattern)) = (path, Pattern::new(pattern)) else {
+        return false;
+    };
+    let options = MatchOptions {
+        require_literal_separator: true,
+        ..MatchOptions::new()
+    };
+    let normalized = normalize_path(path, project_dir)
+        .is_some_and(|normalized| pattern.matches_path_with(&normalized, options));
+    match action {
+        PolicyAction::Allow => normalized,
+        PolicyAction::Deny | PolicyAction::Ask => normalized || pattern.matches_with(path, options),
+    }
+}
+
+/// Split a shell command line on `;`, `&&`, `||`, `|`, `&` and newlines (outside quotes).
+/// None when the command cannot be judged by its parts: it has command substitution
+/// (`` ` ``, `$(`), a redirection or an unterminated quote.
+fn split_command(command: &str) -> Option<Vec<&str>> {
+    let mut parts = Vec::new();
+    let mut start = 0;
+    let mut quote = None;
+    let mut chars = command.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        match (quote, c) {
+            (Some('\''), '\'') => quote = None,
+            (Some('\''), _) => {}
+            (_, '\\') => {
+                chars.next();
+            }
+            (_, '`') => return None,
+            (_, '$') if chars.peek().is_some_and(|(_, next)| *next == '(') => return None,
+            (Some('"'), '"') => quote = None,
+            (Some(_), _) => {}
+            (None, '\'' | '"') => quote = Some(c),
+            (None, '<' | '>') => return None,
+            (None, ';' | '&' | '|' | '\n') => {
+                parts.push(command[start..i].trim());
+                start = i + c.len_utf8();
+            }
+            (None, _) => {}
+        }
+    }
+    if quote.is_some() {
+        return None;
+    }
+    parts.push(command[start..].trim());
+    parts.retain(|part| !part.is_empty());
+    (!parts.is_empty()).then_some(parts)
+}
+
+/// Whether a command glob matches a command line. An `allow` rule must match every command of
+/// a chain, so `git status*` does not allow `git status; rm -rf ~`. `deny` and `ask` rules
+/// match if the whole line or any of its commands matches.
+fn command_matches(pattern: &Option<String>, command: Option<&str>, action: PolicyAction) -> bool {
+    let Some(pattern) = pattern else {
+        return true;
+    };
+    let (Some(command), Ok(pattern)) = (command, Pattern::new(pattern)) else {
+        return false;
+    };
+    match action {
+        PolicyAction::Allow => {
+            split_command(command).is_some_and(|parts| parts.iter().all(|p| pattern.matches(p)))
+        }
+        PolicyAction::Deny | PolicyAction::Ask => {
+            pattern.matches(command)
+                || command
+                    .split([';', '&', '|', '\n', '`', '(', ')'])
+                    .map(str::trim)
+                    .any(|part| pattern.matches(part))
+        }
+    }
+}
+
+fn tool_input_str<'a>(event: &'a EventInfo, keys: &[&str]) -> Option<&'a str> {
+    keys.iter()
+        .find_map(|key| event.tool_input.get(key).and_then(|v| v.as_str()))
+}
+
+impl PolicyRule {
+    pub fn matches(&self, event: &EventInfo) -> bool {
+        glob_matches(&self.tool, Some(&event.tool_name))
+            && command_matches(
+                &self.command,
+                tool_input_str(event, &["command"]),
+                self.action,
+            )
+            && path_matches(
+                &self.path,
+                tool_input_str(event, &["file_path", "path", "notebook_path"]),
+                &event.project_dir,
+                self.action,
+            )
+            && glob_matches(&self.project_dir, Some(&event.project_dir))
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        for pattern in [&self.tool, &self.command, &self.path, &self.project_dir]
+            .into_iter()
+            .flatten()
+        {
+            Pattern::new(pattern).map_err(|e| format!("Invalid glob {:?}: {}", pattern, e))?;
+        }
+        Ok(())
+    }
+}
+
+impl Policy {
+    pub fn evaluate(&self, event: &EventInfo) -> Option<PolicyMatch> {
+        self.rules
+            .iter()
+            .enumerate()
+            .find(|(_, rule)| rule.matches(event))
+            .map(|(index, rule)| PolicyMatch {
+                index,
+                action: rule.action,
+                message: rule.message.clone(),
+            })
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        for (i, rule) in self.rules.iter().enumerate() {
+            rule.validate()
+                .map_err(|e| format!("Rule {}: {}", i + 1, e))?;
+        }
+        Ok(())
+    }
+}
+
+fn get_policy_file(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    get_config_dir(app).map(|dir| dir.join("policy.json"))
+}
+
+/// Load the policy file; a missing or invalid file means no rules
+pub fn load_policy(app: &tauri::AppHandle) -> Policy {
+    let Ok(path) = get_policy_file(app) else {
+        return Policy::default();
+    };
+    if !path.exists() {
+        return Policy::default();
+    }
+    match fs::read_to_string(&path).map(|c| serde_json::from_str::<Policy>(&c)) {
+        Ok(Ok(policy)) => policy,
+        Ok(Err(e)) => {
+            log::error!(target: "eocc.policy", "Failed to parse policy file: {:?}", e);
+            Policy::default()
+        }
+        Err(e) => {
+            log::error!(target: "eocc.policy", "Failed to read policy file: {:?}", e);
+            Policy::default()
+        }
+    }
+}
+
+pub fn save_policy(app: &tauri::AppHandle, policy: &Policy) -> Result<(), String> {
+    let path = get_policy_file(app)?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| format!("Failed to create app data dir: {}", e))?;
+    }
+    let content = serde_json::to_string_pretty(policy)
+        .map_err(|e| format!("Failed to serialize policy: {}", e))?;
+    fs::write(&path, content).map_err(|e| format!("Failed to write policy file: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bash_event(command: &str) -> EventInfo {
+        serde_json::from_value(serde_json::json!({
+            "timestamp": "2025-01-01T00:00:00Z",
+            "event": "pre_tool_use",
+            "matcher": "Bash",
+            "project_name": "app",
+            "project_dir": "/home/me/app",
+            "session_id": "s1",
+            "message": "",
+            "tool_name": "Bash",
+            "tool_input": { "command": command },
+        }))
+        .unwrap()
+    }
+
+    fn rule(action: PolicyAction, command: &str) -> PolicyRule {
+        PolicyRule {
+            action,
+            tool: Some("Bash".to_string()),
+            command: Some(command.to_string()),
+            path: None,
+            project_dir: None,
+            message: None,
+        }
+    }
+
+    #[test]
+    fn allow_matches_plain_and_fully_matching_chains() {
+        let allow = rule(PolicyAction::Allow, "git *");
+        assert!(allow.matches(&bash_event("git status")));
+        assert!(allow.matches(&bash_event("git status && git diff | git apply")));
+        assert!(allow.matches(&bash_event("git commit -m \"a; b && c | d\"")));
+        assert!(allow.matches(&bash_event("git log --format='%h | %s'")));
+    }
+
+    #[test]
+    fn allow_rejects_chained_commands() {
+        let allow = rule(PolicyAction::Allow, "git status*");
+        for command in [
+            "git status; rm -rf ~",
+            "git status && rm -rf ~",
+            "git status || rm -rf ~",
+            "git status | sh",
+            "git status & rm -rf ~",
+            "git status\nrm -rf ~",
+            "git status `rm -rf ~`",
+            "git status $(rm -rf ~)",
+            "git status \"$(rm -rf ~)\"",
+            "git status > ~/.bashrc",
+            "git status < /dev/null",
+            "git status 'unterminated",
+        ] {
+            assert!(!allow.matches(&bash_event(command)), "{}", command);
+        }
+    }
+
+    fn edit_event(file_path: &str) -> EventInfo {
+        let mut event = bash_event("");
+        event.tool_name = "Edit".to_string();
+        event.tool_input = serde_json::json!({ "file_path": file_path });
+        event
+    }
+
+    fn path_rule(action: PolicyAction, path: &str) -> PolicyRule {
+        PolicyRule {
+            action,
+            tool: Some("Edit".to_string()),
+            command: None,
+            path: Some(path.to_string()),
+            project_dir: None,
+            message: None,
+        }
+    }
+
+    #[test]
+    fn allow_matches_paths_inside_the_pattern() {
+        let allow = path_rule(PolicyAction::Allow, "/home/me/app/*");
+        assert!(allow.matches(&edit_event("/home/me/app/main.rs")));
+        assert!(allow.matches(&edit_event("/home/me/app/./main.rs")));
+        assert!(allow.matches(&edit_event("/home/me/app/src/../main.rs")));
+        // Relative paths are taken from the project dir
+        assert!(allow.matches(&edit_event("main.rs")));
+        assert!(allow.matches(&edit_event("./src/../main.rs")));
+        let recursive = path_rule(PolicyAction::Allow, "/home/me/app/**/*.rs");
+        assert!(recursive.matches(&edit_event("src/deep/lib.rs")));
+    }
+
+    #[test]
+    fn allow_rejects_path_traversal() {
+        let allow = path_rule(PolicyAction::Allow, "/home/me/app/*");
+        for path in [
+            "/home/me/app/../../.ssh/authorized_keys",
+            "/home/me/app/./../../.ssh/authorized_keys",
+            "../../.ssh/authorized_keys",
+            "../app-secrets/key",
+            "/home/me/app/src/main.rs",
+            "/home/me/app/src/../../other/x",
+            "/home/me/application/x",
+        ] {
+            assert!(!allow.matches(&edit_event(path)), "{}", path);
+        }
+        // Without a project dir a relative path cannot be resolved
+        let mut event = edit_event("main.rs");
+        event.project_dir = String::new();
+        assert!(!allow.matches(&event));
+        assert_eq!(normalize_path("../x", ""), None);
+        assert_eq!(normalize_path("/../x", ""), Some(PathBuf::from("/x")));
+    }
+
+    #[test]
+    fn deny_matches_normalized_and_given_paths() {
+        let deny = path_rule(PolicyAction::Deny, "/home/me/.ssh/*");
+        assert!(deny.matches(&edit_event("/home/me/app/../.ssh/authorized_keys")));
+        assert!(deny.matches(&edit_event("../.ssh/authorized_keys")));
+        assert!(!deny.matches(&edit_event("/home/me/app/.ssh.md")));
+    }
+
+    #[test]
+    fn deny_matches_any_command_of_a_chain() {
+        let deny = rule(PolicyAction::Deny, "rm *");
+        assert!(deny.matches(&bash_event("rm -rf build")));
+        assert!(deny.matches(&bash_event("ls && rm -rf ~")));
+        assert!(deny.matches(&bash_event("ls; rm -rf ~")));
+        assert!(deny.matches(&bash_event("echo $(rm -rf ~)")));
+        assert!(deny.matches(&bash_event("echo `rm -rf ~`")));
+        assert!(!deny.matches(&bash_event("ls -la")));
+    }
+
+    #[test]
+    fn evaluate_returns_the_first_matching_rule() {
+        let policy = Policy {
+            rules: vec![
+                rule(PolicyAction::Deny, "rm *"),
+                rule(PolicyAction::Allow, "*"),
+            ],
+        };
+        let denied = policy.evaluate(&bash_event("ls; rm -rf ~")).unwrap();
+        assert_eq!(denied.index, 0);
+        assert!(matches!(
+            denied.decision(),
+            Some(ApprovalDecision::Deny { .. })
+        ));
+        let allowed = policy.evaluate(&bash_event("ls -la")).unwrap();
+        assert_eq!(allowed.index, 1);
+        assert!(policy.evaluate(&bash_event("ls > out")).is_none());
+    }
+}
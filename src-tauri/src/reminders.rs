@@ -0,0 +1,265 @@
+//! Escalating reminders for sessions left waiting.
+//!
+//! While a session stays in WaitingPermission or WaitingInput, each configured threshold it
+//! passes raises one more level of reminder:
+//! 1. repeat the notification and sound, and blink the tray tooltip
+//! 2. also request window attention
+//! 3. and later: also raise and un-hide the dashboard
+//!
+//! Any status change starts over, and acknowledging the session stops it for the current wait.
+//! Levels passed during Do Not Disturb are skipped silently.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::{Emitter, Manager, UserAttentionType};
+
+use crate::history::parse_timestamp;
+use crate::notifications::{is_dashboard_focused, notify_session};
+use crate::state::{AppState, SessionInfo, SessionStatus};
+use crate::tray::update_tray_and_badge;
+
+/// Interval between reminder checks
+const CHECK_INTERVAL: Duration = Duration::from_secs(10);
+const BLINK_INTERVAL: Duration = Duration::from_millis(500);
+const BLINK_COUNT: usize = 6;
+
+/// Thresholds (minutes spent waiting) at which reminders fire
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReminderSettings {
+    pub enabled: bool,
+    pub intervals_minutes: Vec<u32>,
+    /// Overrides keyed by project directory
+    #[serde(default)]
+    pub project_intervals: BTreeMap<String, Vec<u32>>,
+}
+
+impl Default for ReminderSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            intervals_minutes: vec![2, 10, 30],
+            project_intervals: BTreeMap::new(),
+        }
+    }
+}
+
+impl ReminderSettings {
+    fn intervals_for(&self, project_dir: &str) -> &[u32] {
+        self.project_intervals
+            .get(project_dir)
+            .unwrap_or(&self.intervals_minutes)
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        for intervals in
+            std::iter::once(&self.intervals_minutes).chain(self.project_intervals.values())
+        {
+            if intervals.contains(&0) {
+                return Err("Reminder intervals must be at least 1 minute".to_string());
+            }
+            if intervals.windows(2).any(|w| w[0] >= w[1]) {
+                return Err("Reminder intervals must be increasing".to_string());
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Payload of the `session-reminder` event
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionReminder {
+    pub session_id: String,
+    pub project_name: String,
+    pub waiting_for: String,
+    /// 1-based escalation level
+    pub level: usize,
+    pub waiting_minutes: i64,
+}
+
+struct ReminderState {
+    /// Start of the wait this entry belongs to
+    since: String,
+    /// Highest level already fired
+    level: usize,
+}
+
+/// Tracks the reminder level reached by each waiting session
+#[derive(Default)]
+pub struct ReminderScheduler {
+    waits: Mutex<HashMap<String, ReminderState>>,
+}
+
+/// Start time of the session's current status interval, falling back to its last event
+fn waiting_since(state: &AppState, session: &SessionInfo) -> String {
+    state
+        .timelines
+        .get(&session.session_id)
+        .and_then(|t| t.intervals.back())
+        .filter(|i| i.end.is_none() && i.status == session.status)
+        .map(|i| i.start.clone())
+        .unwrap_or_else(|| session.last_event.clone())
+}
+
+impl ReminderScheduler {
+    /// Stop reminding about the session until it waits again
+    pub fn acknowledge(&self, session_id: &str) {
+        if let Ok(mut waits) = self.waits.lock() {
+            if let Some(wait) = waits.get_mut(session_id) {
+                wait.level = usize::MAX;
+            }
+        }
+    }
+
+    /// Sessions that passed a new threshold, with their new level and minutes waited
+    fn due(&self, state: &AppState) -> Vec<(SessionInfo, usize, i64)> {
+        let Ok(mut waits) = self.waits.lock() else {
+            return Vec::new();
+        };
+        let settings = &state.settings.reminders;
+        let now = chrono::Utc::now();
+        let mut due = Vec::new();
+
+        waits.retain(|id, _| {
+            state.sessions.get(id).is_some_and(|s| {
+                matches!(
+                    s.status,
+                    SessionStatus::WaitingPermission | SessionStatus::WaitingInput
+                )
+            })
+        });
+        for session in state.sessions.values().filter(|s| {
+            matches!(
+                s.status,
+                SessionStatus::WaitingPermission | SessionStatus::WaitingInput
+            )
+        }) {
+            let since = waiting_since(state, session);
+            let minutes = parse_timestamp(&since).map(|t| (now - t).num_minutes());
+            let level = minutes.map_or(0, |minutes| {
+                settings
+                    .intervals_for(&session.project_dir)
+                    .iter()
+                    .filter(|m| i64::from(**m) <= minutes)
+                    .count()
+            });
+            // Waits already underway when first seen (e.g. at startup) do not fire at once
+            let wait = waits
+                .entry(session.session_id.clone())
+                .or_insert_with(|| ReminderState {
+                    since: since.clone(),
+                    level,
+                });
+            // A new wait (e.g. the next prompt) starts from scratch
+            if wait.since != since {
+                *wait = ReminderState { since, level: 0 };
+            }
+            let Some(minutes) = minutes else {
+                continue;
+            };
+            if settings.enabled && level > wait.level {
+                wait.level = level;
+                due.push((session.clone(), level, minutes));
+            }
+        }
+        due
+    }
+}
+
+/// Alternate the tray tooltip with the reminder text, then restore it from the state
+fn blink_tray(app: &tauri::AppHandle, state: &Arc<Mutex<AppState>>, text: String) {
+    let Some(tray) = app.tray_by_id("main") else {
+        return;
+    };
+    let app = app.clone();
+    let state = Arc::clone(state);
+    std::thread::spawn(move || {
+        for i in 0..BLINK_COUNT {
+            let tooltip = if i % 2 == 0 { text.as_str() } else { "" };
+            let _ = tray.set_tooltip(Some(tooltip));
+            std::thread::sleep(BLINK_INTERVAL);
+        }
+        if let Ok(state_guard) = state.lock() {
+            update_tray_and_badge(&app, &state_guard);
+        }
+    });
+}
+
+fn escalate(
+    app: &tauri::AppHandle,
+    state: &Arc<Mutex<AppState>>,
+    state_guard: &AppState,
+    session: &SessionInfo,
+    level: usize,
+    waiting_minutes: i64,
+) {
+    log::info!(
+        target: "eocc.reminders",
+        "Reminder level {} for {} (waiting {} min)",
+        level,
+        session.session_id,
+        waiting_minutes
+    );
+    let reminder = SessionReminder {
+        session_id: session.session_id.clone(),
+        project_name: session.display_name().to_string(),
+        waiting_for: session.waiting_for.clone(),
+        level,
+        waiting_minutes,
+    };
+    // The dashboard repeats the sound
+    let _ = app.emit("session-reminder", &reminder);
+    if !is_dashboard_focused(app) {
+        notify_session(app, &state_guard.settings.notifications, session);
+    }
+    blink_tray(
+        app,
+        state,
+        format!(
+            "{} {} waiting for {} min",
+            session.status.emoji(),
+            session.display_name(),
+            waiting_minutes
+        ),
+    );
+
+    let Some(window) = app.get_webview_window("dashboard") else {
+        return;
+    };
+    if level >= 2 {
+        let attention = if level >= 3 {
+            UserAttentionType::Critical
+        } else {
+            UserAttentionType::Informational
+        };
+        let _ = window.request_user_attention(Some(attention));
+    }
+    if level >= 3 {
+        let _ = window.unminimize();
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+}
+
+/// Periodically re-notify about sessions that are still waiting
+pub fn start_reminder_scheduler(app_handle: tauri::AppHandle, state: Arc<Mutex<AppState>>) {
+    let scheduler = Arc::new(ReminderScheduler::default());
+    app_handle.manage(Arc::clone(&scheduler));
+
+    std::thread::spawn(move || loop {
+        std::thread::sleep(CHECK_INTERVAL);
+
+        let Ok(state_guard) = state.lock() else {
+            eprintln!("[eocc] Failed to acquire state lock in reminder scheduler");
+            continue;
+        };
+        let dnd_active = state_guard.settings.dnd.is_active();
+        for (session, level, minutes) in scheduler.due(&state_guard) {
+            if dnd_active || session.prefs.is_silenced() {
+                continue;
+            }
+            escalate(&app_handle, &state, &state_guard, &session, level, minutes);
+        }
+    });
+}
@@ -0,0 +1,205 @@
+//! Sessions running on other machines (SSH hosts, VMs).
+//!
+//! Hooks on a remote host reach the dashboard through a forwarded Unix socket (`ssh -R`) or
+//! over TCP with a shared token (see `ingest`). Sessions are told apart by the `host` their
+//! events report. Git and diff commands for a remote session run over SSH when its host has an
+//! SSH destination configured; tmux and liveness checks only apply to local sessions.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::net::IpAddr;
+use std::process::Command;
+use std::sync::{Arc, OnceLock};
+
+use crate::api::load_or_create_token;
+
+/// Token file shared with the hooks on remote hosts
+pub const TOKEN_FILE: &str = "remote_token";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemoteSettings {
+    /// Accept hook events over TCP
+    pub enabled: bool,
+    /// 127.0.0.1 for SSH port forwarding, 0.0.0.0 to accept connections from the network
+    pub bind_address: String,
+    pub port: u16,
+    /// SSH destinations keyed by the hostname the hook reports, e.g. "devbox" -> "me@devbox.lan"
+    #[serde(default)]
+    pub ssh_hosts: BTreeMap<String, String>,
+}
+
+impl Default for RemoteSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            bind_address: "127.0.0.1".to_string(),
+            port: 17381,
+            ssh_hosts: BTreeMap::new(),
+        }
+    }
+}
+
+impl RemoteSettings {
+    pub fn bind_ip(&self) -> Result<IpAddr, String> {
+        self.bind_address
+            .parse()
+            .map_err(|_| format!("Invalid bind address: {}", self.bind_address))
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        self.bind_ip()?;
+        for (host, destination) in &self.ssh_hosts {
+            // A leading '-' would be read by ssh as an option
+            if host.is_empty()
+                || destination.is_empty()
+                || destination.starts_with('-')
+                || destination.contains(char::is_whitespace)
+            {
+                return Err(format!(
+                    "Invalid SSH destination for {:?}: {:?}",
+                    host, destination
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Connection details shown to the user to configure hooks on remote hosts
+#[derive(Debug, Clone, Serialize)]
+pub struct RemoteInfo {
+    pub enabled: bool,
+    pub running: bool,
+    pub bind_address: String,
+    pub port: u16,
+    pub token: String,
+    /// Hostname of this machine; sessions reporting another host are remote
+    pub hostname: String,
+}
+
+/// Hostname of this machine, resolved once
+pub fn local_hostname() -> &'static str {
+    static HOSTNAME: OnceLock<String> = OnceLock::new();
+    HOSTNAME.get_or_init(|| {
+        Command::new("hostname")
+            .output()
+            .ok()
+            .filter(|o| o.status.success())
+            .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+            .unwrap_or_default()
+    })
+}
+
+/// Whether a session's host is another machine (an unknown host counts as local)
+pub fn is_remote_host(host: &str) -> bool {
+    !host.is_empty() && host != local_hostname()
+}
+
+/// Where git commands for a session run
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum CommandRunner {
+    Local,
+    /// Over `ssh` to this destination
+    Ssh(String),
+}
+
+/// Quote an argument for the remote shell
+fn shell_quote(arg: &str) -> String {
+    format!("'{}'", arg.replace('\'', r"'\''"))
+}
+
+impl CommandRunner {
+    /// Runner for a session on `host`; remote hosts need an SSH destination
+    pub fn for_host(settings: &RemoteSettings, host: &str) -> Result<Self, String> {
+        if !is_remote_host(host) {
+            return Ok(Self::Local);
+        }
+        settings
+            .ssh_hosts
+            .get(host)
+            .map(|destination| Self::Ssh(destination.clone()))
+            .ok_or_else(|| format!("No SSH destination configured for host {}", host))
+    }
+
+    pub fn is_local(&self) -> bool {
+        *self == Self::Local
+    }
+
+    /// `git -C <dir> <args>` on the session's machine
+    pub fn git(&self, dir: &str, args: &[&str]) -> Command {
+        match self {
+            Self::Local => {
+                let mut command = Command::new("git");
+                command.args(["-C", dir]).args(args);
+                command
+            }
+            Self::Ssh(destination) => {
+                let remote_command = ["git", "-C", dir]
+                    .iter()
+                    .chain(args)
+                    .map(|arg| shell_quote(arg))
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                let mut command = Command::new("ssh");
+                command
+                    .args(["-o", "BatchMode=yes", "-o", "ConnectTimeout=10", "--"])
+                    .arg(destination)
+                    .arg(remote_command);
+                command
+            }
+        }
+    }
+}
+
+#[cfg(unix)]
+fn listener(app: &tauri::AppHandle) -> Option<tauri::State<'_, Arc<crate::ingest::RemoteIngest>>> {
+    use tauri::Manager;
+    app.try_state()
+}
+
+pub fn is_running(app: &tauri::AppHandle) -> bool {
+    #[cfg(unix)]
+    {
+        listener(app).is_some_and(|l| l.is_running())
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = app;
+        false
+    }
+}
+
+/// Start (or restart) the TCP listener when enabled, stop it otherwise
+pub fn apply(app: &tauri::AppHandle, settings: &RemoteSettings) -> Result<(), String> {
+    #[cfg(unix)]
+    {
+        // Without the socket server (another instance owns it) there is nothing to forward to
+        let listener = listener(app).ok_or("The event socket server is not running")?;
+        if settings.enabled {
+            let token = load_or_create_token(app, TOKEN_FILE)?;
+            listener.start(settings, token)
+        } else {
+            listener.stop();
+            Ok(())
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = app;
+        if settings.enabled {
+            return Err("Remote hosts are not supported on this platform".to_string());
+        }
+        Ok(())
+    }
+}
+
+pub fn info(app: &tauri::AppHandle, settings: &RemoteSettings) -> RemoteInfo {
+    RemoteInfo {
+        enabled: settings.enabled,
+        running: is_running(app),
+        bind_address: settings.bind_address.clone(),
+        port: settings.port,
+        token: load_or_create_token(app, TOKEN_FILE).unwrap_or_default(),
+        hostname: local_hostname().to_string(),
+    }
+}
@@ -26,10 +26,22 @@ pub fn get_app_log_dir(app: &tauri::AppHandle) -> Result<PathBuf, String> {
         .map_err(|e| format!("Failed to get app log dir: {:?}", e))
 }
 
+/// Get the event ingestion socket path (~/.eocc/eocc.sock)
+/// This path matches the hook script's socket path
+pub fn get_socket_path(_app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    let home = dirs::home_dir().ok_or("Failed to get home directory")?;
+    Ok(home.join(".eocc").join("eocc.sock"))
+}
+
 pub fn get_events_file(app: &tauri::AppHandle) -> Result<PathBuf, String> {
     get_log_dir(app).map(|dir| dir.join("events.jsonl"))
 }
 
+/// Get the event history directory (app data dir/history)
+pub fn get_history_dir(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    get_config_dir(app).map(|dir| dir.join("history"))
+}
+
 pub fn get_settings_file(app: &tauri::AppHandle) -> Result<PathBuf, String> {
     get_config_dir(app).map(|dir| dir.join("settings.json"))
 }
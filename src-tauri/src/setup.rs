@@ -34,8 +34,13 @@ mod hook_types {
     pub const STOP: &str = "Stop";
     pub const POST_TOOL_USE: &str = "PostToolUse";
     pub const USER_PROMPT_SUBMIT: &str = "UserPromptSubmit";
+    pub const PERMISSION_REQUEST: &str = "PermissionRequest";
+    pub const PRE_TOOL_USE: &str = "PreToolUse";
 }
 
+/// Seconds Claude Code waits on the blocking permission hook (above the app's own approval timeout)
+const PERMISSION_HOOK_TIMEOUT_SECS: u32 = 600;
+
 /// Global storage for initialization error (set during app startup)
 static INIT_ERROR: Mutex<Option<String>> = Mutex::new(None);
 
@@ -85,6 +90,18 @@ fn generate_hooks_config(hook_script_path: &str) -> serde_json::Value {
         (USER_PROMPT_SUBMIT): [
             { "hooks": [{ "type": "command", "command": format!("{} user_prompt_submit", hook_script_path) }] }
         ],
+        (PRE_TOOL_USE): [
+            { "hooks": [{ "type": "command", "command": format!("{} pre_tool_use", hook_script_path) }] }
+        ],
+        (PERMISSION_REQUEST): [
+            {
+                "hooks": [{
+                    "type": "command",
+                    "command": format!("{} permission_request", hook_script_path),
+                    "timeout": PERMISSION_HOOK_TIMEOUT_SECS
+                }]
+            }
+        ],
         (SESSION_START): [
             {
                 "matcher": "startup",
@@ -109,6 +126,8 @@ pub struct HookStatus {
     pub stop: bool,
     pub post_tool_use: bool,
     pub user_prompt_submit: bool,
+    pub pre_tool_use: bool,
+    pub permission_request: bool,
     pub notification_permission: bool,
     pub notification_idle: bool,
 }
@@ -259,6 +278,8 @@ pub fn check_claude_settings() -> HookStatus {
         stop: false,
         post_tool_use: false,
         user_prompt_submit: false,
+        pre_tool_use: false,
+        permission_request: false,
         notification_permission: false,
         notification_idle: false,
     };
@@ -317,6 +338,16 @@ pub fn check_claude_settings() -> HookStatus {
         .map(|h| has_eocc_hook_in_array(h, None))
         .unwrap_or(false);
 
+    let pre_tool_use = hooks
+        .get(PRE_TOOL_USE)
+        .map(|h| has_eocc_hook_in_array(h, None))
+        .unwrap_or(false);
+
+    let permission_request = hooks
+        .get(PERMISSION_REQUEST)
+        .map(|h| has_eocc_hook_in_array(h, None))
+        .unwrap_or(false);
+
     let notification_permission = hooks
         .get(NOTIFICATION)
         .map(|h| has_eocc_hook_in_array(h, Some("permission_prompt")))
@@ -333,6 +364,8 @@ pub fn check_claude_settings() -> HookStatus {
         stop,
         post_tool_use,
         user_prompt_submit,
+        pre_tool_use,
+        permission_request,
         notification_permission,
         notification_idle,
     }
@@ -0,0 +1,446 @@
+//! Working tree snapshots stored as commits under `refs/eocc/`.
+//!
+//! A snapshot commits every tracked and untracked (not ignored) file through a temporary index,
+//! so the user's index, stash and branches are left alone. Its parent is HEAD at the time.
+//! Diffs between snapshots show what a session changed, untracked files included.
+//!
+//! Events only queue snapshots (`SnapshotQueue`); a worker takes them off the state lock, in
+//! the order the events arrived, and records the results in the sessions afterwards.
+
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::mpsc::{channel, Sender};
+use std::sync::{Arc, Mutex};
+
+use crate::persist::save_runtime_state;
+use crate::remote::CommandRunner;
+use crate::state::AppState;
+use crate::tray::emit_state_update;
+
+/// Namespace of the refs kept per session
+const SESSION_REFS: &str = "refs/eocc/sessions";
+/// Identity of snapshot commits (git refuses to commit without one)
+const SNAPSHOT_NAME: &str = "eocc";
+const SNAPSHOT_EMAIL: &str = "eocc@localhost";
+
+/// A recorded snapshot
+#[derive(Debug, Clone)]
+pub struct Snapshot {
+    /// HEAD when the snapshot was taken (empty before the first commit)
+    pub head: String,
+    pub commit: String,
+}
+
+fn git(dir: &str, args: &[&str]) -> Command {
+    CommandRunner::Local.git(dir, args)
+}
+
+fn output(mut command: Command) -> Result<String, String> {
+    let output = command
+        .output()
+        .map_err(|e| format!("Failed to run git: {}", e))?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Ref for a session's snapshot; session ids are used as a ref component, so only plain ones
+/// are accepted
+pub fn session_ref(session_id: &str, name: &str) -> Result<String, String> {
+    if session_id.is_empty()
+        || !session_id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(format!(
+            "Unsupported session id for snapshots: {}",
+            session_id
+        ));
+    }
+    Ok(format!("{}/{}/{}", SESSION_REFS, session_id, name))
+}
+
+/// Temporary index seeded from the real one, so unchanged files are not hashed again
+struct TempIndex(PathBuf);
+
+impl TempIndex {
+    fn new(dir: &str) -> Result<Self, String> {
+        static COUNTER: AtomicU32 = AtomicU32::new(0);
+        let path = std::env::temp_dir().join(format!(
+            "eocc-index-{}-{}",
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        let index = output(git(dir, &["rev-parse", "--git-path", "index"]))?;
+        // The path may be relative to `dir`; a repo without commits may have no index yet
+        let _ = std::fs::copy(Path::new(dir).join(index), &path);
+        Ok(Self(path))
+    }
+
+    fn git(&self, dir: &str, args: &[&str]) -> Command {
+        let mut command = git(dir, args);
+        command.env("GIT_INDEX_FILE", &self.0);
+        command
+    }
+}
+
+impl Drop for TempIndex {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
+
+/// Tree of the working tree as it is now, untracked files included
+pub fn worktree_tree(dir: &str) -> Result<String, String> {
+    let index = TempIndex::new(dir)?;
+    output(index.git(dir, &["add", "--all"]))?;
+    output(index.git(dir, &["write-tree"]))
+}
+
+fn head(dir: &str) -> String {
+    output(git(dir, &["rev-parse", "--verify", "--quiet", "HEAD"])).unwrap_or_default()
+}
+
+/// Commit the working tree on top of HEAD and point `refname` at it
+pub fn save_snapshot(dir: &str, refname: &str, message: &str) -> Result<Snapshot, String> {
+    let head = head(dir);
+    let tree = worktree_tree(dir)?;
+    let mut command = git(dir, &["commit-tree", &tree, "-m", message]);
+    if !head.is_empty() {
+        command.args(["-p", &head]);
+    }
+    command
+        .env("GIT_AUTHOR_NAME", SNAPSHOT_NAME)
+        .env("GIT_AUTHOR_EMAIL", SNAPSHOT_EMAIL)
+        .env("GIT_COMMITTER_NAME", SNAPSHOT_NAME)
+        .env("GIT_COMMITTER_EMAIL", SNAPSHOT_EMAIL);
+    let commit = output(command)?;
+    output(git(dir, &["update-ref", refname, &commit]))?;
+    Ok(Snapshot { head, commit })
+}
+
+/// The snapshot `refname` points at, if any
+pub fn read_snapshot(dir: &str, refname: &str) -> Option<Snapshot> {
+    let commit = format!("{}^{{commit}}", refname);
+    let commit = output(git(dir, &["rev-parse", "--verify", "--quiet", &commit])).ok()?;
+    let parent = format!("{}^", commit);
+    let head = output(git(dir, &["rev-parse", "--verify", "--quiet", &parent])).unwrap_or_default();
+    Some(Snapshot { head, commit })
+}
+
+/// Snapshot taken when the session started; a resumed session keeps its original one
+pub fn session_start(dir: &str, session_id: &str) -> Result<Snapshot, String> {
+    let refname = session_ref(session_id, "start")?;
+    if let Some(snapshot) = read_snapshot(dir, &refname) {
+        return Ok(snapshot);
+    }
+    save_snapshot(
+        dir,
+        &refname,
+        &format!("eocc: start of session {}", session_id),
+    )
+}
+
+/// Ref of a turn's checkpoint: taken at the prompt ("start") and when Claude stopped ("end")
+fn turn_ref(session_id: &str, turn: u32, name: &str) -> Result<String, String> {
+    session_ref(session_id, &format!("turns/{}/{}", turn, name))
+}
+
+/// Checkpoint the working tree at the start or end of a turn
+pub fn save_turn(dir: &str, session_id: &str, turn: u32, name: &str) -> Result<Snapshot, String> {
+    let refname = turn_ref(session_id, turn, name)?;
+    save_snapshot(
+        dir,
+        &refname,
+        &format!("eocc: {} of turn {} in session {}", name, turn, session_id),
+    )
+}
+
+/// One checkpointed turn of a session
+#[derive(Debug, Clone, Serialize)]
+pub struct TurnSummary {
+    /// 1-based turn number
+    pub turn: u32,
+    /// When the prompt was submitted (RFC 3339)
+    pub prompt_time: String,
+    /// When Claude stopped (empty while the turn is in progress)
+    pub end_time: String,
+    pub files_changed: u32,
+    pub insertions: u32,
+    pub deletions: u32,
+}
+
+#[derive(Default)]
+struct TurnRefs {
+    start: Option<(String, String)>,
+    end: Option<(String, String)>,
+}
+
+/// Checkpoint commits and times of each turn, by turn number
+fn turn_refs(dir: &str, session_id: &str) -> Result<BTreeMap<u32, TurnRefs>, String> {
+    let prefix = session_ref(session_id, "turns/")?;
+    let listing = output(git(
+        dir,
+        &[
+            "for-each-ref",
+            "--format=%(refname) %(objectname) %(committerdate:iso-strict)",
+            &prefix,
+        ],
+    ))?;
+    let mut turns: BTreeMap<u32, TurnRefs> = BTreeMap::new();
+    for line in listing.lines() {
+        let mut fields = line.split(' ');
+        let (Some(refname), Some(commit), Some(time)) =
+            (fields.next(), fields.next(), fields.next())
+        else {
+            continue;
+        };
+        let Some((turn, name)) = refname
+            .strip_prefix(&prefix)
+            .and_then(|rest| rest.split_once('/'))
+        else {
+            continue;
+        };
+        let Ok(turn) = turn.parse() else {
+            continue;
+        };
+        let entry = turns.entry(turn).or_default();
+        let value = Some((commit.to_string(), time.to_string()));
+        match name {
+            "start" => entry.start = value,
+            "end" => entry.end = value,
+            _ => {}
+        }
+    }
+    Ok(turns)
+}
+
+/// Number of the session's latest checkpointed turn (0 if none)
+pub fn last_turn(dir: &str, session_id: &str) -> u32 {
+    turn_refs(dir, session_id)
+        .ok()
+        .and_then(|turns| turns.keys().next_back().copied())
+        .unwrap_or(0)
+}
+
+/// Commits to diff for a turn: its start checkpoint and its end checkpoint, or the working
+/// tree as it is now while the turn is in progress
+pub fn turn_range(dir: &str, session_id: &str, turn: u32) -> Result<(String, String), String> {
+    let mut turns = turn_refs(dir, session_id)?;
+    let refs = turns
+        .remove(&turn)
+        .ok_or_else(|| format!("No checkpoint for turn {}", turn))?;
+    let (start, _) = refs
+        .start
+        .ok_or_else(|| format!("No checkpoint for the start of turn {}", turn))?;
+    let end = match refs.end {
+        Some((end, _)) => end,
+        None => worktree_tree(dir)?,
+    };
+    Ok((start, end))
+}
+
+/// Files changed and lines added/removed between two trees
+fn diff_stats(dir: &str, from: &str, to: &str) -> (u32, u32, u32) {
+    let numstat = output(git(dir, &["diff", "--numstat", from, to])).unwrap_or_default();
+    numstat.lines().fold((0, 0, 0), |(files, ins, del), line| {
+        let mut counts = line.split('\t').map(|c| c.parse::<u32>().unwrap_or(0));
+        (
+            files + 1,
+            ins + counts.next().unwrap_or(0),
+            del + counts.next().unwrap_or(0),
+        )
+    })
+}
+
+/// The session's checkpointed turns, oldest first
+pub fn list_turns(dir: &str, session_id: &str) -> Result<Vec<TurnSummary>, String> {
+    let mut summaries = Vec::new();
+    let mut current_tree = None;
+    for (turn, refs) in turn_refs(dir, session_id)? {
+        let Some((start, prompt_time)) = refs.start else {
+            continue;
+        };
+        let (end, end_time) = match refs.end {
+            Some(end) => end,
+            None => {
+                if current_tree.is_none() {
+                    current_tree = Some(worktree_tree(dir)?);
+                }
+                (current_tree.clone().unwrap_or_default(), String::new())
+            }
+        };
+        let (files_changed, insertions, deletions) = diff_stats(dir, &start, &end);
+        summaries.push(TurnSummary {
+            turn,
+            prompt_time,
+            end_time,
+            files_changed,
+            insertions,
+            deletions,
+        });
+    }
+    Ok(summaries)
+}
+
+/// Delete every snapshot ref of a session (in the background; their commits are left to
+/// `git gc`)
+pub fn discard_session(dir: String, session_id: String) {
+    let Ok(prefix) = session_ref(&session_id, "") else {
+        return;
+    };
+    std::thread::spawn(move || {
+        let Ok(listing) = output(git(&dir, &["for-each-ref", "--format=%(refname)", &prefix]))
+        else {
+            return;
+        };
+        let commands: String = listing
+            .lines()
+            .map(|refname| format!("delete {}\n", refname))
+            .collect();
+        if commands.is_empty() {
+            return;
+        }
+        let mut command = git(&dir, &["update-ref", "--stdin"]);
+        command.stdin(Stdio::piped());
+        let result = command.spawn().and_then(|mut child| {
+            if let Some(mut stdin) = child.stdin.take() {
+                stdin.write_all(commands.as_bytes())?;
+            }
+            child.wait()
+        });
+        if let Err(e) = result {
+            log::warn!(target: "eocc.snapshot", "Failed to delete refs of {}: {}", session_id, e);
+        }
+    });
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SnapshotKind {
+    /// The session started (see `session_start`)
+    Start,
+    /// A prompt was submitted, starting a new turn
+    TurnStart,
+    /// Claude stopped, ending the current turn
+    TurnEnd,
+}
+
+/// A snapshot to take for a session
+#[derive(Debug, Clone)]
+pub struct SnapshotJob {
+    /// Key of the session in `AppState::sessions`
+    pub key: String,
+    pub dir: String,
+    pub session_id: String,
+    pub kind: SnapshotKind,
+}
+
+/// Snapshots waiting for the worker
+#[derive(Default)]
+pub struct SnapshotQueue {
+    sender: Option<Sender<SnapshotJob>>,
+    /// Jobs queued before the worker started
+    backlog: Vec<SnapshotJob>,
+}
+
+impl SnapshotQueue {
+    pub fn push(&mut self, job: SnapshotJob) {
+        match &self.sender {
+            Some(sender) => {
+                if sender.send(job).is_err() {
+                    log::error!(target: "eocc.snapshot", "Snapshot worker stopped; snapshot dropped");
+                }
+            }
+            None => self.backlog.push(job),
+        }
+    }
+}
+
+/// Take a queued snapshot. Returns the start snapshot (for `Start`) and the session's latest
+/// turn, or None if there was nothing to take or it failed.
+fn take_snapshot(job: &SnapshotJob, last_turn: u32) -> Option<(Option<Snapshot>, u32)> {
+    let (turn, name) = match job.kind {
+        SnapshotKind::Start => {
+            return match session_start(&job.dir, &job.session_id) {
+                Ok(start) => Some((Some(start), last_turn)),
+                Err(e) => {
+                    log::warn!(target: "eocc.snapshot", "Failed to snapshot {}: {}", job.session_id, e);
+                    None
+                }
+            };
+        }
+        SnapshotKind::TurnStart => (last_turn + 1, "start"),
+        // Claude stopped before any prompt was checkpointed
+        SnapshotKind::TurnEnd if last_turn == 0 => return None,
+        SnapshotKind::TurnEnd => (last_turn, "end"),
+    };
+    match save_turn(&job.dir, &job.session_id, turn, name) {
+        Ok(_) => Some((None, turn)),
+        Err(e) => {
+            log::warn!(
+                target: "eocc.snapshot",
+                "Failed to checkpoint turn {} of {}: {}",
+                turn,
+                job.session_id,
+                e
+            );
+            None
+        }
+    }
+}
+
+/// Start the worker taking queued snapshots, including those queued before it started
+pub fn start_snapshot_worker(app_handle: tauri::AppHandle, state: Arc<Mutex<AppState>>) {
+    let (sender, jobs) = channel();
+    {
+        let Ok(mut state_guard) = state.lock() else {
+            log::error!(target: "eocc.snapshot", "Failed to acquire state lock for the snapshot worker");
+            return;
+        };
+        for job in state_guard.snapshots.backlog.drain(..) {
+            let _ = sender.send(job);
+        }
+        state_guard.snapshots.sender = Some(sender);
+    }
+
+    std::thread::spawn(move || {
+        // Latest turn by session key. Turns are numbered here, in the order of the events; a
+        // resumed session continues its numbering.
+        let mut turns: HashMap<String, u32> = HashMap::new();
+        while let Ok(job) = jobs.recv() {
+            let last = *turns
+                .entry(job.key.clone())
+                .or_insert_with(|| last_turn(&job.dir, &job.session_id));
+            let Some((start, turn)) = take_snapshot(&job, last) else {
+                continue;
+            };
+            turns.insert(job.key.clone(), turn);
+
+            let Ok(mut state_guard) = state.lock() else {
+                log::error!(target: "eocc.snapshot", "Failed to acquire state lock in snapshot worker");
+                return;
+            };
+            let Some(session) = state_guard
+                .sessions
+                .get_mut(&job.key)
+                .filter(|s| s.session_id == job.session_id)
+            else {
+                // Removed meanwhile; a resume starts counting from its refs again
+                turns.remove(&job.key);
+                continue;
+            };
+            if let Some(start) = start {
+                session.start_head = start.head;
+                session.start_snapshot = start.commit;
+            }
+            session.turns = turn;
+            emit_state_update(&app_handle, &state_guard);
+            save_runtime_state(&app_handle, &state_guard);
+        }
+    });
+}
@@ -1,8 +1,22 @@
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, VecDeque};
 use std::sync::{Arc, Mutex};
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+use crate::approval::{ApprovalQueue, ApprovalRequest};
+use crate::container::{map_session_path, PathMapping};
+use crate::cost::{default_price_table, CostLedger, ModelPrice};
+use crate::dnd::DndSettings;
+use crate::git::RepoLocation;
+use crate::history::parse_timestamp;
+use crate::notifications::NotificationSettings;
+use crate::reminders::ReminderSettings;
+use crate::remote::{is_remote_host, RemoteSettings};
+use crate::snapshot::{self, SnapshotQueue};
+use crate::transcript::{TokenUsage, TranscriptReader};
+use crate::webhooks::WebhookConfig;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum EventType {
     SessionStart,
@@ -11,6 +25,14 @@ pub enum EventType {
     Stop,
     PostToolUse,
     UserPromptSubmit,
+    /// Claude Code is about to show a permission dialog (blocking hook)
+    PermissionRequest,
+    /// A tool is about to run (blocking hook)
+    PreToolUse,
+    /// Outcome of an approval (policy rule, dashboard or timeout); recorded in history only
+    ApprovalDecision,
+    /// The session's process or tmux pane went away; recorded in history only
+    Disconnected,
     #[serde(other)]
     Unknown,
 }
@@ -45,10 +67,42 @@ pub struct EventInfo {
     pub npx_path: String,
     #[serde(default)]
     pub tmux_path: String,
+    /// PID of the Claude Code process (0 if unknown)
+    #[serde(default)]
+    pub claude_pid: u32,
+    #[serde(default)]
+    pub host: String,
+    /// Docker/Podman container the hook ran in (empty on the host)
+    #[serde(default)]
+    pub container_id: String,
+    /// Peer address of the TCP connection the event arrived on (empty for local hooks)
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub remote_peer: String,
+    #[serde(default)]
+    pub transcript_path: String,
+    /// Tool arguments, sent by the permission_request / pre_tool_use hooks
+    #[serde(default, skip_serializing_if = "serde_json::Value::is_null")]
+    pub tool_input: serde_json::Value,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+impl EventInfo {
+    /// Key used to identify the session this event belongs to.
+    /// Falls back to the project dir (then name) when the hook could not read a session_id.
+    pub fn session_key(&self) -> String {
+        if !self.session_id.is_empty() && self.session_id != "unknown" {
+            self.session_id.clone()
+        } else if !self.project_dir.is_empty() {
+            self.project_dir.clone()
+        } else {
+            self.project_name.clone()
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct SessionInfo {
+    #[serde(default)]
+    pub session_id: String,
     pub project_name: String,
     pub project_dir: String,
     pub status: SessionStatus,
@@ -57,14 +111,145 @@ pub struct SessionInfo {
     pub waiting_for: String,
     #[serde(default)]
     pub tmux_pane: String,
+    /// PID of the Claude Code process (0 if unknown)
+    #[serde(default)]
+    pub claude_pid: u32,
+    #[serde(default)]
+    pub host: String,
+    /// Docker/Podman container the session runs in (empty on the host)
+    #[serde(default)]
+    pub container_id: String,
+    /// Peer address of the remote hook reporting this session; such a session is never local
+    #[serde(default)]
+    pub remote_peer: String,
+    /// Host path of `project_dir` when a path mapping applies
+    #[serde(default)]
+    pub local_dir: String,
+    /// When the session was found dead (empty unless Disconnected)
+    #[serde(default)]
+    pub disconnected_at: String,
+    #[serde(default)]
+    pub transcript_path: String,
+    /// Text of the latest assistant message in the transcript
+    #[serde(default)]
+    pub last_assistant_message: String,
+    #[serde(default)]
+    pub model: String,
+    /// Cumulative token usage over the whole transcript
+    #[serde(default)]
+    pub token_usage: TokenUsage,
+    /// Context size of the latest assistant request
+    #[serde(default)]
+    pub context_tokens: u64,
+    /// Estimated spend of this session in USD
+    #[serde(default)]
+    pub cost_usd: f64,
+    #[serde(flatten)]
+    pub prefs: SessionPrefs,
+    /// Git repository and worktree of the project dir (None if not a repo)
+    #[serde(default)]
+    pub repo: Option<RepoLocation>,
+    /// HEAD when the session started
+    #[serde(default)]
+    pub start_head: String,
+    /// Working tree snapshot taken when the session started (see `snapshot`)
+    #[serde(default)]
+    pub start_snapshot: String,
+    /// Number of turns (prompts) checkpointed so far
+    #[serde(default)]
+    pub turns: u32,
+}
+
+impl SessionInfo {
+    /// Whether the session runs on another machine (containers on this one are local)
+    pub fn is_remote(&self) -> bool {
+        !self.remote_peer.is_empty()
+            || (self.container_id.is_empty()
+                && self.local_dir.is_empty()
+                && is_remote_host(&self.host))
+    }
+
+    fn has_snapshots(&self) -> bool {
+        !self.start_snapshot.is_empty() || self.turns > 0
+    }
+
+    /// Delete the git refs of the session's start and turn snapshots
+    fn discard_snapshots(&self) {
+        if self.has_snapshots() {
+            snapshot::discard_session(self.project_path().to_string(), self.session_id.clone());
+        }
+    }
+
+    /// Project dir on this machine
+    pub fn project_path(&self) -> &str {
+        if self.local_dir.is_empty() {
+            &self.project_dir
+        } else {
+            &self.local_dir
+        }
+    }
+
+    /// Alias if set, otherwise the project name
+    pub fn display_name(&self) -> &str {
+        if self.prefs.alias.is_empty() {
+            &self.project_name
+        } else {
+            &self.prefs.alias
+        }
+    }
+}
+
+/// Snapshot refs of a removed session, kept for a while in case it is resumed
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct EndedSnapshots {
+    /// Project dir on this machine
+    pub dir: String,
+    /// When the session was removed (RFC 3339)
+    pub ended_at: String,
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+/// Per-session choices of the user, kept across restarts and resumes
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SessionPrefs {
+    /// Shown instead of the project name
+    #[serde(default)]
+    pub alias: String,
+    /// Listed before other sessions
+    #[serde(default)]
+    pub pinned: bool,
+    /// No notifications, webhooks, reminders or sounds
+    #[serde(default)]
+    pub muted: bool,
+    /// Silenced like `muted` until this time (RFC 3339, empty = not snoozed)
+    #[serde(default)]
+    pub snoozed_until: String,
+}
+
+impl SessionPrefs {
+    pub fn is_snoozed(&self) -> bool {
+        chrono::DateTime::parse_from_rfc3339(&self.snoozed_until)
+            .is_ok_and(|until| until > chrono::Utc::now())
+    }
+
+    pub fn is_silenced(&self) -> bool {
+        self.muted || self.is_snoozed()
+    }
+
+    pub fn snooze_for(&mut self, minutes: u32) {
+        let until = chrono::Utc::now() + chrono::Duration::minutes(i64::from(minutes));
+        self.snoozed_until = until.to_rfc3339_opts(chrono::SecondsFormat::Secs, true);
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub enum SessionStatus {
+    #[default]
     Active,
     WaitingPermission,
     WaitingInput,
     Completed,
+    /// The Claude Code process or its tmux pane is gone without a SessionEnd
+    Disconnected,
 }
 
 impl SessionStatus {
@@ -74,14 +259,101 @@ impl SessionStatus {
             SessionStatus::WaitingPermission => "🔐",
             SessionStatus::WaitingInput => "⏳",
             SessionStatus::Completed => "✅",
+            SessionStatus::Disconnected => "🔌",
         }
     }
 }
 
+/// A span of time a session spent in one status. `end` is None while still ongoing.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatusInterval {
+    pub status: SessionStatus,
+    pub start: String,
+    pub end: Option<String>,
+}
+
+/// Bounded per-session history of events and status intervals
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SessionTimeline {
+    pub events: VecDeque<EventInfo>,
+    pub intervals: VecDeque<StatusInterval>,
+}
+
+impl SessionTimeline {
+    pub const MAX_EVENTS: usize = 200;
+    pub const MAX_INTERVALS: usize = 200;
+
+    /// Append an event and open a new interval if the session status changed
+    pub fn record(&mut self, event: &EventInfo, status: &SessionStatus) {
+        self.events.push_back(event.clone());
+        if self.events.len() > Self::MAX_EVENTS {
+            self.events.pop_front();
+        }
+        self.transition(status, &event.timestamp);
+    }
+
+    /// Open a new interval at `timestamp` if the status differs from the current one
+    pub fn transition(&mut self, status: &SessionStatus, timestamp: &str) {
+        if self.intervals.back().map(|i| &i.status) == Some(status) {
+            return;
+        }
+        if let Some(last) = self.intervals.back_mut() {
+            last.end = Some(timestamp.to_string());
+        }
+        self.intervals.push_back(StatusInterval {
+            status: status.clone(),
+            start: timestamp.to_string(),
+            end: None,
+        });
+        if self.intervals.len() > Self::MAX_INTERVALS {
+            self.intervals.pop_front();
+        }
+    }
+}
+
+/// Sessions sharing the same project directory
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectGroup {
+    pub project_name: String,
+    pub project_dir: String,
+    pub session_ids: Vec<String>,
+}
+
+/// Sessions in one worktree of a repository
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorktreeGroup {
+    pub path: String,
+    pub branch: String,
+    pub session_ids: Vec<String>,
+}
+
+/// Sessions sharing a git repository, by worktree. Directories outside git
+/// form a group of their own with an empty `common_dir`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RepoGroup {
+    /// Remote host the sessions run on (empty for this machine)
+    pub host: String,
+    pub name: String,
+    pub common_dir: String,
+    pub worktrees: Vec<WorktreeGroup>,
+}
+
+impl RepoGroup {
+    pub fn session_count(&self) -> usize {
+        self.worktrees.iter().map(|w| w.session_ids.len()).sum()
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DashboardData {
     pub sessions: Vec<SessionInfo>,
+    pub projects: Vec<ProjectGroup>,
+    pub repos: Vec<RepoGroup>,
     pub events: Vec<EventInfo>,
+    /// Tool calls waiting for Allow/Deny, oldest first
+    pub approvals: Vec<ApprovalRequest>,
+    /// Do Not Disturb is on: the dashboard stays silent
+    pub dnd_active: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -94,6 +366,47 @@ pub struct Settings {
     pub opacity_inactive: f64,
     #[serde(default = "Settings::default_sound_enabled")]
     pub sound_enabled: bool,
+    #[serde(default = "Settings::default_history_retention_days")]
+    pub history_retention_days: u32,
+    /// Remove Disconnected sessions after this many minutes (0 = keep until cleared)
+    #[serde(default = "Settings::default_auto_archive_disconnected_minutes")]
+    pub auto_archive_disconnected_minutes: u32,
+    /// Per-model prices used for cost estimates
+    #[serde(default = "Settings::default_model_prices")]
+    pub model_prices: Vec<ModelPrice>,
+    /// Daily spend alert threshold in USD (0 = off)
+    #[serde(default = "Settings::default_daily_budget_usd")]
+    pub daily_budget_usd: f64,
+    /// Per-session spend alert threshold in USD (0 = off)
+    #[serde(default = "Settings::default_session_budget_usd")]
+    pub session_budget_usd: f64,
+    /// Serve the local HTTP/WebSocket API on 127.0.0.1
+    #[serde(default = "Settings::default_api_enabled")]
+    pub api_enabled: bool,
+    #[serde(default = "Settings::default_api_port")]
+    pub api_port: u16,
+    /// How long a blocking permission hook waits for Allow/Deny before
+    /// falling back to the terminal prompt
+    #[serde(default = "Settings::default_approval_timeout_secs")]
+    pub approval_timeout_secs: u32,
+    /// Desktop notifications per status
+    #[serde(default = "Settings::default_notifications")]
+    pub notifications: NotificationSettings,
+    /// URLs notified on status transitions
+    #[serde(default = "Settings::default_webhooks")]
+    pub webhooks: Vec<WebhookConfig>,
+    /// Escalating reminders while a session keeps waiting
+    #[serde(default = "Settings::default_reminders")]
+    pub reminders: ReminderSettings,
+    /// Do Not Disturb schedule and manual pause
+    #[serde(default = "Settings::default_dnd")]
+    pub dnd: DndSettings,
+    /// Hooks on other machines and how to reach them
+    #[serde(default = "Settings::default_remote")]
+    pub remote: RemoteSettings,
+    /// Container path prefixes and the host paths they are mounted from
+    #[serde(default = "Settings::default_path_mappings")]
+    pub path_mappings: Vec<PathMapping>,
 }
 
 impl Settings {
@@ -101,6 +414,16 @@ impl Settings {
     pub const DEFAULT_OPACITY_ACTIVE: f64 = 1.0;
     pub const DEFAULT_OPACITY_INACTIVE: f64 = 0.3;
     pub const DEFAULT_SOUND_ENABLED: bool = true;
+    pub const DEFAULT_HISTORY_RETENTION_DAYS: u32 = 30;
+    pub const DEFAULT_AUTO_ARCHIVE_DISCONNECTED_MINUTES: u32 = 0;
+    pub const DEFAULT_DAILY_BUDGET_USD: f64 = 0.0;
+    pub const DEFAULT_SESSION_BUDGET_USD: f64 = 0.0;
+    pub const DEFAULT_API_ENABLED: bool = false;
+    pub const DEFAULT_API_PORT: u16 = 17380;
+    pub const DEFAULT_APPROVAL_TIMEOUT_SECS: u32 = 120;
+    pub const MIN_APPROVAL_TIMEOUT_SECS: u32 = 5;
+    /// Must stay below the hook timeout registered in Claude settings
+    pub const MAX_APPROVAL_TIMEOUT_SECS: u32 = 540;
 
     fn default_always_on_top() -> bool {
         Self::DEFAULT_ALWAYS_ON_TOP
@@ -117,6 +440,62 @@ impl Settings {
     fn default_sound_enabled() -> bool {
         Self::DEFAULT_SOUND_ENABLED
     }
+
+    fn default_history_retention_days() -> u32 {
+        Self::DEFAULT_HISTORY_RETENTION_DAYS
+    }
+
+    fn default_auto_archive_disconnected_minutes() -> u32 {
+        Self::DEFAULT_AUTO_ARCHIVE_DISCONNECTED_MINUTES
+    }
+
+    fn default_model_prices() -> Vec<ModelPrice> {
+        default_price_table()
+    }
+
+    fn default_daily_budget_usd() -> f64 {
+        Self::DEFAULT_DAILY_BUDGET_USD
+    }
+
+    fn default_session_budget_usd() -> f64 {
+        Self::DEFAULT_SESSION_BUDGET_USD
+    }
+
+    fn default_api_enabled() -> bool {
+        Self::DEFAULT_API_ENABLED
+    }
+
+    fn default_api_port() -> u16 {
+        Self::DEFAULT_API_PORT
+    }
+
+    fn default_approval_timeout_secs() -> u32 {
+        Self::DEFAULT_APPROVAL_TIMEOUT_SECS
+    }
+
+    fn default_notifications() -> NotificationSettings {
+        NotificationSettings::default()
+    }
+
+    fn default_webhooks() -> Vec<WebhookConfig> {
+        Vec::new()
+    }
+
+    fn default_reminders() -> ReminderSettings {
+        ReminderSettings::default()
+    }
+
+    fn default_dnd() -> DndSettings {
+        DndSettings::default()
+    }
+
+    fn default_remote() -> RemoteSettings {
+        RemoteSettings::default()
+    }
+
+    fn default_path_mappings() -> Vec<PathMapping> {
+        Vec::new()
+    }
 }
 
 impl Default for Settings {
@@ -126,6 +505,20 @@ impl Default for Settings {
             opacity_active: Self::DEFAULT_OPACITY_ACTIVE,
             opacity_inactive: Self::DEFAULT_OPACITY_INACTIVE,
             sound_enabled: Self::DEFAULT_SOUND_ENABLED,
+            history_retention_days: Self::DEFAULT_HISTORY_RETENTION_DAYS,
+            auto_archive_disconnected_minutes: Self::DEFAULT_AUTO_ARCHIVE_DISCONNECTED_MINUTES,
+            model_prices: default_price_table(),
+            daily_budget_usd: Self::DEFAULT_DAILY_BUDGET_USD,
+            session_budget_usd: Self::DEFAULT_SESSION_BUDGET_USD,
+            api_enabled: Self::DEFAULT_API_ENABLED,
+            api_port: Self::DEFAULT_API_PORT,
+            approval_timeout_secs: Self::DEFAULT_APPROVAL_TIMEOUT_SECS,
+            notifications: NotificationSettings::default(),
+            webhooks: Vec::new(),
+            reminders: ReminderSettings::default(),
+            dnd: DndSettings::default(),
+            remote: RemoteSettings::default(),
+            path_mappings: Vec::new(),
         }
     }
 }
@@ -152,12 +545,31 @@ impl CachedPaths {
 #[derive(Default)]
 pub struct AppState {
     pub sessions: HashMap<String, SessionInfo>,
+    pub timelines: HashMap<String, SessionTimeline>,
+    pub transcripts: HashMap<String, TranscriptReader>,
     pub recent_events: VecDeque<EventInfo>,
     pub settings: Settings,
     pub cached_paths: CachedPaths,
+    pub cost: CostLedger,
+    pub approvals: ApprovalQueue,
+    /// Prefs by session id, kept after the session ends so a resume picks them up again
+    pub session_prefs: HashMap<String, SessionPrefs>,
+    /// Resolved repository by project dir, refreshed on SessionStart and Stop
+    pub repo_cache: HashMap<String, Option<RepoLocation>>,
+    /// Working tree snapshots waiting to be taken
+    pub snapshots: SnapshotQueue,
+    /// Snapshots of removed sessions by session id, deleted once they expire
+    pub ended_snapshots: HashMap<String, EndedSnapshots>,
 }
 
 impl AppState {
+    pub fn disconnected_session_count(&self) -> usize {
+        self.sessions
+            .values()
+            .filter(|s| s.status == SessionStatus::Disconnected)
+            .count()
+    }
+
     pub fn waiting_session_count(&self) -> usize {
         self.sessions
             .values()
@@ -171,8 +583,12 @@ impl AppState {
     pub fn to_dashboard_data(&self) -> DashboardData {
         // Sort sessions by last_event timestamp in descending order (newest first)
         // Empty timestamps are sorted to the end
+        // Pinned sessions come first
         let mut sessions: Vec<SessionInfo> = self.sessions.values().cloned().collect();
         sessions.sort_by(|a, b| {
+            if a.prefs.pinned != b.prefs.pinned {
+                return b.prefs.pinned.cmp(&a.prefs.pinned);
+            }
             match (a.last_event.is_empty(), b.last_event.is_empty()) {
                 (true, true) => std::cmp::Ordering::Equal,
                 (true, false) => std::cmp::Ordering::Greater, // Empty goes to end
@@ -181,10 +597,148 @@ impl AppState {
             }
         });
 
+        let projects = group_by_project(&sessions);
+        let repos = group_by_repo(&sessions);
+
         DashboardData {
             sessions,
+            projects,
+            repos,
             events: self.recent_events.iter().cloned().collect(),
+            approvals: self.approvals.list(),
+            dnd_active: self.settings.dnd.is_active(),
+        }
+    }
+
+    fn take_session(&mut self, key: &str) -> Option<SessionInfo> {
+        let session = self.sessions.remove(key);
+        self.timelines.remove(key);
+        self.transcripts.remove(key);
+        self.approvals.cancel_session(key);
+        self.prune_session_prefs();
+        session
+    }
+
+    /// Remove a session together with its timeline. Its snapshots are kept until
+    /// `discard_ended_snapshots`, as the session may be resumed.
+    pub fn remove_session(&mut self, key: &str) {
+        let Some(session) = self.take_session(key) else {
+            return;
+        };
+        if session.has_snapshots() {
+            let ended = EndedSnapshots {
+                dir: session.project_path().to_string(),
+                ended_at: chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+            };
+            self.ended_snapshots.insert(session.session_id, ended);
+        }
+    }
+
+    /// Remove a session that has been disconnected for a while, deleting its snapshots too
+    pub fn archive_session(&mut self, key: &str) {
+        if let Some(session) = self.take_session(key) {
+            session.discard_snapshots();
+        }
+    }
+
+    /// Delete the snapshots of sessions removed before `cutoff` and not resumed since.
+    /// Returns true if any were deleted.
+    pub fn discard_ended_snapshots(&mut self, cutoff: DateTime<Utc>) -> bool {
+        let sessions = &self.sessions;
+        let before = self.ended_snapshots.len();
+        self.ended_snapshots.retain(|session_id, ended| {
+            // A resumed session uses its refs again; they are recorded anew when it ends
+            if sessions.values().any(|s| s.session_id == *session_id) {
+                return false;
+            }
+            if parse_timestamp(&ended.ended_at).is_some_and(|t| t >= cutoff) {
+                return true;
+            }
+            snapshot::discard_session(ended.dir.clone(), session_id.clone());
+            false
+        });
+        self.ended_snapshots.len() != before
+    }
+
+    /// Remove all sessions together with their timelines and snapshots
+    pub fn clear_sessions(&mut self) {
+        for (_, session) in self.sessions.drain() {
+            session.discard_snapshots();
+        }
+        for (session_id, ended) in self.ended_snapshots.drain() {
+            snapshot::discard_session(ended.dir, session_id);
+        }
+        self.timelines.clear();
+        self.transcripts.clear();
+        self.approvals.cancel_all();
+        self.prune_session_prefs();
+    }
+
+    /// Mark a session as Disconnected at `timestamp`. Returns false if it was not found.
+    pub fn mark_disconnected(&mut self, key: &str, timestamp: &str) -> bool {
+        let Some(session) = self.sessions.get_mut(key) else {
+            return false;
+        };
+        session.status = SessionStatus::Disconnected;
+        session.waiting_for.clear();
+        self.approvals.cancel_session(key);
+        session.disconnected_at = timestamp.to_string();
+        self.timelines
+            .entry(key.to_string())
+            .or_default()
+            .transition(&SessionStatus::Disconnected, timestamp);
+        true
+    }
+
+    /// Record the event in the session's timeline using the session's current status
+    pub fn record_timeline(&mut self, key: &str, event: &EventInfo) {
+        if let Some(session) = self.sessions.get(key) {
+            self.timelines
+                .entry(key.to_string())
+                .or_default()
+                .record(event, &session.status);
+        }
+    }
+
+    /// Stored prefs for a session id (defaults if none)
+    pub fn prefs_for(&self, session_id: &str) -> SessionPrefs {
+        self.session_prefs
+            .get(session_id)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Change a session's prefs and remember them for when it is resumed
+    pub fn update_session_prefs(
+        &mut self,
+        session_id: &str,
+        update: impl FnOnce(&mut SessionPrefs),
+    ) -> Result<(), String> {
+        let session = self
+            .sessions
+            .get_mut(session_id)
+            .ok_or_else(|| format!("Session not found: {}", session_id))?;
+        update(&mut session.prefs);
+        if session.prefs == SessionPrefs::default() {
+            self.session_prefs.remove(session_id);
+        } else {
+            self.session_prefs
+                .insert(session_id.to_string(), session.prefs.clone());
         }
+        Ok(())
+    }
+
+    /// Forget the stored prefs of sessions that are gone when all they hold is an expired snooze
+    pub fn prune_session_prefs(&mut self) {
+        let sessions = &self.sessions;
+        self.session_prefs.retain(|session_id, prefs| {
+            sessions.contains_key(session_id)
+                || prefs.is_snoozed()
+                || SessionPrefs {
+                    snoozed_until: String::new(),
+                    ..prefs.clone()
+                } != SessionPrefs::default()
+        });
     }
 
     /// Insert or update a session with the given status and waiting_for info
@@ -195,25 +749,187 @@ impl AppState {
         status: SessionStatus,
         waiting_for: String,
     ) {
+        let prefs = self.prefs_for(&key);
         self.sessions
-            .entry(key)
+            .entry(key.clone())
             .and_modify(|s| {
                 s.status = status.clone();
                 s.last_event = event.timestamp.clone();
                 s.waiting_for = waiting_for.clone();
+                s.disconnected_at.clear();
                 if !event.tmux_pane.is_empty() {
                     s.tmux_pane = event.tmux_pane.clone();
                 }
+                if event.claude_pid != 0 {
+                    s.claude_pid = event.claude_pid;
+                }
+                if !event.host.is_empty() {
+                    s.host = event.host.clone();
+                    s.container_id = event.container_id.clone();
+                }
+                if !event.remote_peer.is_empty() {
+                    s.remote_peer = event.remote_peer.clone();
+                }
+                if !event.transcript_path.is_empty() {
+                    s.transcript_path = event.transcript_path.clone();
+                }
             })
             .or_insert_with(|| SessionInfo {
+                session_id: key,
                 project_name: event.project_name.clone(),
                 project_dir: event.project_dir.clone(),
                 status,
                 last_event: event.timestamp.clone(),
                 waiting_for,
                 tmux_pane: event.tmux_pane.clone(),
+                claude_pid: event.claude_pid,
+                host: event.host.clone(),
+                container_id: event.container_id.clone(),
+                remote_peer: event.remote_peer.clone(),
+                disconnected_at: String::new(),
+                transcript_path: event.transcript_path.clone(),
+                prefs,
+                ..Default::default()
             });
     }
+
+    /// Map the session's project dir to this machine with the configured path mappings
+    pub fn update_local_dir(&mut self, key: &str) {
+        let Some(session) = self.sessions.get(key) else {
+            return;
+        };
+        // Paths reported over the network are never trusted to be on this machine
+        let local_dir = if session.remote_peer.is_empty() {
+            map_session_path(&self.settings.path_mappings, session, &session.project_dir)
+                .unwrap_or_default()
+        } else {
+            String::new()
+        };
+        if let Some(session) = self.sessions.get_mut(key) {
+            session.local_dir = local_dir;
+        }
+    }
+}
+
+/// Group sessions by project dir, keeping the order in which projects first appear
+pub fn group_by_project(sessions: &[SessionInfo]) -> Vec<ProjectGroup> {
+    let mut groups: Vec<ProjectGroup> = Vec::new();
+    for session in sessions {
+        let session_id = session.session_id.clone();
+        match groups.iter_mut().find(|g| {
+            g.project_dir == session.project_dir && g.project_name == session.project_name
+        }) {
+            Some(group) => group.session_ids.push(session_id),
+            None => groups.push(ProjectGroup {
+                project_name: session.project_name.clone(),
+                project_dir: session.project_dir.clone(),
+                session_ids: vec![session_id],
+            }),
+        }
+    }
+    groups
+}
+
+/// Group sessions by host and repository, then worktree, keeping the order in which they
+/// first appear
+pub fn group_by_repo<'a>(sessions: impl IntoIterator<Item = &'a SessionInfo>) -> Vec<RepoGroup> {
+    let mut groups: Vec<RepoGroup> = Vec::new();
+    for session in sessions {
+        let host = if session.is_remote() {
+            session.host.as_str()
+        } else {
+            ""
+        };
+        let (name, common_dir, path, branch) = match &session.repo {
+            Some(repo) => (
+                repo.name.as_str(),
+                repo.common_dir.as_str(),
+                repo.worktree.as_str(),
+                repo.branch.as_str(),
+            ),
+            None => (
+                session.project_name.as_str(),
+                "",
+                session.project_dir.as_str(),
+                "",
+            ),
+        };
+        let group_index = groups
+            .iter()
+            .position(|g| {
+                if g.host != host {
+                    false
+                } else if common_dir.is_empty() {
+                    g.common_dir.is_empty() && g.worktrees[0].path == path
+                } else {
+                    g.common_dir == common_dir
+                }
+            })
+            .unwrap_or_else(|| {
+                groups.push(RepoGroup {
+                    host: host.to_string(),
+                    name: name.to_string(),
+                    common_dir: common_dir.to_string(),
+                    worktrees: Vec::new(),
+                });
+                groups.len() - 1
+            });
+        let worktrees = &mut groups[group_index].worktrees;
+        match worktrees.iter_mut().find(|w| w.path == path) {
+            Some(worktree) => worktree.session_ids.push(session.session_id.clone()),
+            None => worktrees.push(WorktreeGroup {
+                path: path.to_string(),
+                branch: branch.to_string(),
+                session_ids: vec![session.session_id.clone()],
+            }),
+        }
+    }
+    groups
 }
 
 pub struct ManagedState(pub Arc<Mutex<AppState>>);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session(session_id: &str) -> SessionInfo {
+        SessionInfo {
+            session_id: session_id.to_string(),
+            project_dir: "/nonexistent/eocc-test".to_string(),
+            start_snapshot: "0123456".to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn removed_sessions_keep_snapshots_until_they_expire() {
+        let mut state = AppState::default();
+        state.sessions.insert("s1".to_string(), session("s1"));
+        state.sessions.insert("s2".to_string(), session("s2"));
+        state.remove_session("s1");
+        state.archive_session("s2");
+        assert_eq!(
+            state.ended_snapshots.keys().collect::<Vec<_>>(),
+            ["s1"],
+            "archived sessions are deleted right away"
+        );
+
+        let hour_ago = Utc::now() - chrono::Duration::hours(1);
+        assert!(!state.discard_ended_snapshots(hour_ago));
+        assert!(state.ended_snapshots.contains_key("s1"));
+        assert!(state.discard_ended_snapshots(Utc::now() + chrono::Duration::seconds(1)));
+        assert!(state.ended_snapshots.is_empty());
+    }
+
+    #[test]
+    fn resumed_sessions_take_their_snapshots_back() {
+        let mut state = AppState::default();
+        state.sessions.insert("s1".to_string(), session("s1"));
+        state.remove_session("s1");
+        state.sessions.insert("s1".to_string(), session("s1"));
+
+        assert!(state.discard_ended_snapshots(Utc::now() - chrono::Duration::hours(1)));
+        assert!(state.ended_snapshots.is_empty());
+    }
+}
@@ -0,0 +1,416 @@
+use chrono::{DateTime, Datelike, Local, NaiveDate, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use crate::events::status_for_event;
+use crate::history::parse_timestamp;
+use crate::state::{EventInfo, EventType, NotificationType, SessionStatus};
+
+/// Seconds spent in each session status
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StatusDurations {
+    pub active_secs: f64,
+    pub waiting_permission_secs: f64,
+    pub waiting_input_secs: f64,
+    pub completed_secs: f64,
+    pub disconnected_secs: f64,
+}
+
+impl StatusDurations {
+    fn add(&mut self, status: &SessionStatus, secs: f64) {
+        match status {
+            SessionStatus::Active => self.active_secs += secs,
+            SessionStatus::WaitingPermission => self.waiting_permission_secs += secs,
+            SessionStatus::WaitingInput => self.waiting_input_secs += secs,
+            SessionStatus::Completed => self.completed_secs += secs,
+            SessionStatus::Disconnected => self.disconnected_secs += secs,
+        }
+    }
+}
+
+/// Latency from a permission prompt to the next PostToolUse/UserPromptSubmit
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PermissionLatency {
+    pub count: u32,
+    pub total_secs: f64,
+    pub avg_secs: f64,
+    pub max_secs: f64,
+}
+
+impl PermissionLatency {
+    fn record(&mut self, secs: f64) {
+        self.count += 1;
+        self.total_secs += secs;
+        self.avg_secs = self.total_secs / f64::from(self.count);
+        self.max_secs = self.max_secs.max(secs);
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionStats {
+    pub session_id: String,
+    pub project_name: String,
+    pub project_dir: String,
+    pub first_event: String,
+    pub last_event: String,
+    pub time_in_status: StatusDurations,
+    pub permission_latency: PermissionLatency,
+}
+
+/// Aggregate for one project over one day (`YYYY-MM-DD`) or ISO week (`YYYY-Www`), local time
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PeriodStats {
+    pub period: String,
+    pub project_name: String,
+    pub project_dir: String,
+    pub time_in_status: StatusDurations,
+    pub permission_latency: PermissionLatency,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatsReport {
+    pub since: String,
+    pub until: String,
+    pub sessions: Vec<SessionStats>,
+    pub daily: Vec<PeriodStats>,
+    pub weekly: Vec<PeriodStats>,
+}
+
+fn day_label(day: NaiveDate) -> String {
+    day.format("%Y-%m-%d").to_string()
+}
+
+fn week_label(day: NaiveDate) -> String {
+    let week = day.iso_week();
+    format!("{}-W{:02}", week.year(), week.week())
+}
+
+fn local_day(ts: DateTime<Utc>) -> NaiveDate {
+    ts.with_timezone(&Local).date_naive()
+}
+
+/// Start of the next local day after `ts`, in UTC
+fn next_local_midnight(ts: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    let next_day = local_day(ts).succ_opt()?;
+    Local
+        .from_local_datetime(&next_day.and_hms_opt(0, 0, 0)?)
+        .earliest()
+        .map(|dt| dt.with_timezone(&Utc))
+}
+
+fn secs_between(start: DateTime<Utc>, end: DateTime<Utc>) -> f64 {
+    (end - start).num_milliseconds() as f64 / 1000.0
+}
+
+struct Aggregator {
+    since: DateTime<Utc>,
+    daily: BTreeMap<(String, String), PeriodStats>,
+    weekly: BTreeMap<(String, String), PeriodStats>,
+}
+
+impl Aggregator {
+    fn bucket<'a>(
+        map: &'a mut BTreeMap<(String, String), PeriodStats>,
+        period: String,
+        project: &SessionStats,
+    ) -> &'a mut PeriodStats {
+        map.entry((period.clone(), project.project_dir.clone()))
+            .or_insert_with(|| PeriodStats {
+                period,
+                project_name: project.project_name.clone(),
+                project_dir: project.project_dir.clone(),
+                time_in_status: StatusDurations::default(),
+                permission_latency: PermissionLatency::default(),
+            })
+    }
+
+    /// Add a status interval, split at local midnights so each day gets its share
+    fn add_interval(
+        &mut self,
+        session: &mut SessionStats,
+        status: &SessionStatus,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) {
+        let mut cursor = start.max(self.since);
+        while cursor < end {
+            let segment_end = next_local_midnight(cursor)
+                .filter(|midnight| *midnight > cursor)
+                .map_or(end, |midnight| midnight.min(end));
+            let secs = secs_between(cursor, segment_end);
+            let day = local_day(cursor);
+
+            session.time_in_status.add(status, secs);
+            Self::bucket(&mut self.daily, day_label(day), session)
+                .time_in_status
+                .add(status, secs);
+            Self::bucket(&mut self.weekly, week_label(day), session)
+                .time_in_status
+                .add(status, secs);
+
+            cursor = segment_end;
+        }
+    }
+
+    /// Record a permission latency against the day the prompt appeared
+    fn add_latency(&mut self, session: &mut SessionStats, prompted_at: DateTime<Utc>, secs: f64) {
+        let day = local_day(prompted_at);
+        session.permission_latency.record(secs);
+        Self::bucket(&mut self.daily, day_label(day), session)
+            .permission_latency
+            .record(secs);
+        Self::bucket(&mut self.weekly, week_label(day), session)
+            .permission_latency
+            .record(secs);
+    }
+}
+
+/// Compute per-session and per-project daily/weekly stats from history events.
+///
+/// Intervals still open at the end of the events are closed at `until` for sessions in
+/// `live_sessions`, and at their last event otherwise. A Disconnected event (from the liveness
+/// check) ends the running interval, so time in Disconnected counts only until the session
+/// sends events again.
+pub fn compute_stats(
+    events: &[EventInfo],
+    live_sessions: &HashSet<String>,
+    since: DateTime<Utc>,
+    until: DateTime<Utc>,
+) -> StatsReport {
+    // Group events by session, keeping only those with a parseable timestamp
+    let mut by_session: HashMap<String, Vec<(DateTime<Utc>, &EventInfo)>> = HashMap::new();
+    for event in events {
+        if let Some(ts) = parse_timestamp(&event.timestamp) {
+            by_session
+                .entry(event.session_key())
+                .or_default()
+                .push((ts, event));
+        }
+    }
+
+    let mut aggregator = Aggregator {
+        since,
+        daily: BTreeMap::new(),
+        weekly: BTreeMap::new(),
+    };
+    let mut sessions = Vec::new();
+
+    for (session_id, mut session_events) in by_session {
+        session_events.sort_by_key(|(ts, _)| *ts);
+        let (Some((first_ts, first)), Some((last_ts, _))) = (
+            session_events.first().copied(),
+            session_events.last().copied(),
+        ) else {
+            continue;
+        };
+
+        let mut session = SessionStats {
+            project_name: first.project_name.clone(),
+            project_dir: first.project_dir.clone(),
+            first_event: first_ts.to_rfc3339(),
+            last_event: last_ts.to_rfc3339(),
+            session_id: session_id.clone(),
+            time_in_status: StatusDurations::default(),
+            permission_latency: PermissionLatency::default(),
+        };
+
+        let mut current: Option<(SessionStatus, DateTime<Utc>)> = None;
+        let mut prompted_at: Option<DateTime<Utc>> = None;
+
+        for (ts, event) in &session_events {
+            let next_status = status_for_event(event);
+            let ended = event.event_type == EventType::SessionEnd;
+
+            // Close the running interval when the status changes or the session ends
+            let changed = match (&current, &next_status) {
+                (Some((status, _)), Some(next)) => status != next,
+                _ => ended,
+            };
+            if changed {
+                if let Some((status, start)) = current.take() {
+                    aggregator.add_interval(&mut session, &status, start, *ts);
+                }
+            }
+            if current.is_none() && !ended {
+                current = next_status.map(|status| (status, *ts));
+            }
+
+            match event.event_type {
+                EventType::Notification
+                    if event.notification_type == NotificationType::PermissionPrompt =>
+                {
+                    prompted_at.get_or_insert(*ts);
+                }
+                EventType::PostToolUse | EventType::UserPromptSubmit => {
+                    if let Some(start) = prompted_at.take() {
+                        aggregator.add_latency(&mut session, start, secs_between(start, *ts));
+                    }
+                }
+                EventType::SessionEnd | EventType::Disconnected => prompted_at = None,
+                _ => {}
+            }
+        }
+
+        if let Some((status, start)) = current {
+            let end = if live_sessions.contains(&session_id) {
+                until
+            } else {
+                last_ts
+            };
+            aggregator.add_interval(&mut session, &status, start, end);
+        }
+
+        sessions.push(session);
+    }
+
+    // Newest first
+    sessions.sort_by(|a, b| b.last_event.cmp(&a.last_event));
+    let daily = aggregator.daily.into_values().rev().collect();
+    let weekly = aggregator.weekly.into_values().rev().collect();
+
+    StatsReport {
+        since: since.to_rfc3339(),
+        until: until.to_rfc3339(),
+        sessions,
+        daily,
+        weekly,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn event(ts: DateTime<Utc>, event: &str, notification_type: &str) -> EventInfo {
+        serde_json::from_value(serde_json::json!({
+            "timestamp": ts.to_rfc3339(),
+            "event": event,
+            "matcher": "",
+            "project_name": "app",
+            "project_dir": "/home/me/app",
+            "session_id": "s1",
+            "message": "",
+            "notification_type": notification_type,
+        }))
+        .unwrap()
+    }
+
+    fn local(day: NaiveDate, hour: u32) -> DateTime<Utc> {
+        Local
+            .from_local_datetime(&day.and_hms_opt(hour, 0, 0).unwrap())
+            .earliest()
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    fn stats(events: &[EventInfo], live: &[&str], until: DateTime<Utc>) -> StatsReport {
+        let live = live.iter().map(|s| s.to_string()).collect();
+        let since = until - Duration::days(30);
+        compute_stats(events, &live, since, until)
+    }
+
+    #[test]
+    fn splits_intervals_at_local_midnight() {
+        let day = NaiveDate::from_ymd_opt(2025, 1, 15).unwrap();
+        let next = day.succ_opt().unwrap();
+        let events = [
+            event(local(day, 23), "user_prompt_submit", "other"),
+            event(local(next, 1), "stop", "other"),
+        ];
+        let report = stats(&events, &[], local(next, 12));
+
+        assert_eq!(report.sessions[0].time_in_status.active_secs, 7200.0);
+        let daily: Vec<(&str, f64)> = report
+            .daily
+            .iter()
+            .map(|p| (p.period.as_str(), p.time_in_status.active_secs))
+            .collect();
+        assert_eq!(daily, [("2025-01-16", 3600.0), ("2025-01-15", 3600.0)]);
+        assert_eq!(report.weekly[0].period, "2025-W03");
+        assert_eq!(report.weekly[0].time_in_status.active_secs, 7200.0);
+    }
+
+    #[test]
+    fn permission_latency_until_the_tool_runs() {
+        let t0 = Utc::now() - Duration::hours(1);
+        let events = [
+            event(t0, "user_prompt_submit", "other"),
+            event(
+                t0 + Duration::seconds(10),
+                "notification",
+                "permission_prompt",
+            ),
+            event(t0 + Duration::seconds(40), "post_tool_use", "other"),
+            event(t0 + Duration::seconds(50), "stop", "other"),
+        ];
+        let session = &stats(&events, &[], Utc::now()).sessions[0];
+
+        assert_eq!(session.permission_latency.count, 1);
+        assert_eq!(session.permission_latency.avg_secs, 30.0);
+        assert_eq!(session.time_in_status.waiting_permission_secs, 30.0);
+        assert_eq!(session.time_in_status.active_secs, 20.0);
+    }
+
+    #[test]
+    fn session_end_closes_the_interval() {
+        let t0 = Utc::now() - Duration::hours(1);
+        let events = [
+            event(t0, "user_prompt_submit", "other"),
+            event(t0 + Duration::seconds(60), "session_end", "other"),
+        ];
+        // Even if the session were still listed as live
+        let session = &stats(&events, &["s1"], Utc::now()).sessions[0];
+
+        assert_eq!(session.time_in_status.active_secs, 60.0);
+        assert_eq!(session.time_in_status.completed_secs, 0.0);
+    }
+
+    #[test]
+    fn live_sessions_run_until_now() {
+        let until = Utc::now();
+        let events = [event(
+            until - Duration::seconds(90),
+            "user_prompt_submit",
+            "other",
+        )];
+
+        assert_eq!(
+            stats(&events, &["s1"], until).sessions[0]
+                .time_in_status
+                .active_secs,
+            90.0
+        );
+        assert_eq!(
+            stats(&events, &[], until).sessions[0]
+                .time_in_status
+                .active_secs,
+            0.0
+        );
+    }
+
+    #[test]
+    fn disconnect_ends_waiting_time() {
+        let t0 = Utc::now() - Duration::hours(3);
+        let mut events = vec![
+            event(t0, "notification", "permission_prompt"),
+            event(t0 + Duration::seconds(60), "disconnected", "other"),
+        ];
+        // Disconnected sessions are not live, so nothing runs on until now
+        let session = &stats(&events, &[], Utc::now()).sessions[0];
+        assert_eq!(session.time_in_status.waiting_permission_secs, 60.0);
+        assert_eq!(session.time_in_status.disconnected_secs, 0.0);
+        assert_eq!(session.permission_latency.count, 0);
+
+        // Resumed later: the gap counts as disconnected, not as a permission wait
+        events.push(event(
+            t0 + Duration::seconds(660),
+            "user_prompt_submit",
+            "other",
+        ));
+        let session = &stats(&events, &[], Utc::now()).sessions[0];
+        assert_eq!(session.time_in_status.waiting_permission_secs, 60.0);
+        assert_eq!(session.time_in_status.disconnected_secs, 600.0);
+        assert_eq!(session.permission_latency.count, 0);
+    }
+}
@@ -123,6 +123,16 @@ pub fn capture_pane(pane_id: &str) -> Result<String, String> {
     ])
 }
 
+/// Bring a pane to the front of the attached tmux client
+pub fn select_pane(pane_id: &str) -> Result<(), String> {
+    validate_pane_id(pane_id)?;
+    // Fails when no client is attached; the pane is still selected for the next attach
+    let _ = run_tmux_command(&["switch-client", "-t", pane_id]);
+    run_tmux_command(&["select-window", "-t", pane_id])?;
+    run_tmux_command(&["select-pane", "-t", pane_id])?;
+    Ok(())
+}
+
 pub fn send_keys(pane_id: &str, keys: &str) -> Result<(), String> {
     validate_pane_id(pane_id)?;
     log::info!(target: "eocc.tmux", "send_keys: pane_id={}, keys={}", pane_id, keys);
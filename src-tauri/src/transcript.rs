@@ -0,0 +1,248 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+
+use crate::container::map_session_path;
+use crate::state::AppState;
+
+/// Maximum characters of the last assistant message kept on the session
+const MAX_MESSAGE_CHARS: usize = 2000;
+
+/// Token usage reported by the Claude API in transcript entries
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TokenUsage {
+    #[serde(default)]
+    pub input_tokens: u64,
+    #[serde(default)]
+    pub output_tokens: u64,
+    #[serde(default)]
+    pub cache_creation_input_tokens: u64,
+    #[serde(default)]
+    pub cache_read_input_tokens: u64,
+}
+
+impl TokenUsage {
+    pub fn add(&mut self, other: &TokenUsage) {
+        self.input_tokens += other.input_tokens;
+        self.output_tokens += other.output_tokens;
+        self.cache_creation_input_tokens += other.cache_creation_input_tokens;
+        self.cache_read_input_tokens += other.cache_read_input_tokens;
+    }
+
+    /// Tokens in `self` beyond `previous`
+    pub fn since(&self, previous: &TokenUsage) -> TokenUsage {
+        TokenUsage {
+            input_tokens: self.input_tokens.saturating_sub(previous.input_tokens),
+            output_tokens: self.output_tokens.saturating_sub(previous.output_tokens),
+            cache_creation_input_tokens: self
+                .cache_creation_input_tokens
+                .saturating_sub(previous.cache_creation_input_tokens),
+            cache_read_input_tokens: self
+                .cache_read_input_tokens
+                .saturating_sub(previous.cache_read_input_tokens),
+        }
+    }
+
+    /// Size of the context window used by the request this usage belongs to
+    pub fn context_tokens(&self) -> u64 {
+        self.input_tokens
+            + self.cache_creation_input_tokens
+            + self.cache_read_input_tokens
+            + self.output_tokens
+    }
+}
+
+/// Usage of one assistant API message, for cost accounting
+#[derive(Debug, Clone)]
+pub struct UsageRecord {
+    pub timestamp: String,
+    pub model: String,
+    pub usage: TokenUsage,
+}
+
+#[derive(Deserialize)]
+struct TranscriptLine {
+    #[serde(rename = "type", default)]
+    line_type: String,
+    #[serde(default)]
+    timestamp: String,
+    #[serde(default)]
+    message: Option<TranscriptMessage>,
+}
+
+#[derive(Deserialize)]
+struct TranscriptMessage {
+    #[serde(default)]
+    id: String,
+    #[serde(default)]
+    model: String,
+    #[serde(default)]
+    content: serde_json::Value,
+    #[serde(default)]
+    usage: Option<TokenUsage>,
+}
+
+/// Incremental reader for a Claude Code JSONL transcript.
+/// Only bytes appended since the previous read are parsed.
+#[derive(Debug, Default)]
+pub struct TranscriptReader {
+    path: String,
+    offset: u64,
+    /// Latest usage counted per assistant message id. One API message is written as several
+    /// lines sharing the same id, and later lines may report more output tokens.
+    counted: HashMap<String, TokenUsage>,
+    pub usage: TokenUsage,
+    pub last_usage: Option<TokenUsage>,
+    pub model: String,
+    pub last_assistant_message: String,
+    /// Usage counted since the last `take_new_usage` call
+    new_usage: Vec<UsageRecord>,
+}
+
+impl TranscriptReader {
+    pub fn take_new_usage(&mut self) -> Vec<UsageRecord> {
+        std::mem::take(&mut self.new_usage)
+    }
+
+    /// Read lines appended to `path` since the last call
+    pub fn read_new(&mut self, path: &str) -> std::io::Result<()> {
+        if path != self.path {
+            *self = TranscriptReader {
+                path: path.to_string(),
+                ..Default::default()
+            };
+        }
+
+        let mut file = File::open(path)?;
+        let len = file.metadata()?.len();
+        if len < self.offset {
+            // Transcript was truncated or replaced - start over
+            *self = TranscriptReader {
+                path: path.to_string(),
+                ..Default::default()
+            };
+        }
+        if len == self.offset {
+            return Ok(());
+        }
+
+        file.seek(SeekFrom::Start(self.offset))?;
+        let mut buf = Vec::new();
+        file.read_to_end(&mut buf)?;
+
+        // Only consume complete lines; a partially written last line is read next time
+        let Some(last_newline) = buf.iter().rposition(|b| *b == b'\n') else {
+            return Ok(());
+        };
+        self.offset += last_newline as u64 + 1;
+
+        for line in buf[..last_newline].split(|b| *b == b'\n') {
+            if line.is_empty() {
+                continue;
+            }
+            if let Ok(entry) = serde_json::from_slice::<TranscriptLine>(line) {
+                self.apply(entry);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn apply(&mut self, entry: TranscriptLine) {
+        if entry.line_type != "assistant" {
+            return;
+        }
+        let Some(message) = entry.message else {
+            return;
+        };
+
+        if !message.model.is_empty() {
+            self.model = message.model.clone();
+        }
+
+        if let Some(usage) = &message.usage {
+            let added = if message.id.is_empty() {
+                usage.clone()
+            } else {
+                let counted = self.counted.entry(message.id.clone()).or_default();
+                let added = usage.since(counted);
+                counted.add(&added);
+                added
+            };
+            if added != TokenUsage::default() {
+                self.usage.add(&added);
+                self.new_usage.push(UsageRecord {
+                    timestamp: entry.timestamp.clone(),
+                    model: message.model.clone(),
+                    usage: added,
+                });
+            }
+            self.last_usage = Some(usage.clone());
+        }
+
+        let text = extract_text(&message.content);
+        if !text.is_empty() {
+            self.last_assistant_message = text.chars().take(MAX_MESSAGE_CHARS).collect();
+        }
+    }
+}
+
+/// Join the text blocks of a message content (string or array of blocks)
+fn extract_text(content: &serde_json::Value) -> String {
+    match content {
+        serde_json::Value::String(s) => s.trim().to_string(),
+        serde_json::Value::Array(blocks) => blocks
+            .iter()
+            .filter(|b| b.get("type").and_then(|t| t.as_str()) == Some("text"))
+            .filter_map(|b| b.get("text").and_then(|t| t.as_str()))
+            .map(str::trim)
+            .filter(|t| !t.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n"),
+        _ => String::new(),
+    }
+}
+
+/// Tail the session's transcript and copy the latest message, model and usage onto it
+pub fn update_session_transcript(state: &mut AppState, key: &str) {
+    let Some(session) = state.sessions.get(key) else {
+        return;
+    };
+    // The transcript of a remote session is not readable from here
+    if session.transcript_path.is_empty() || session.is_remote() {
+        return;
+    }
+    let path = map_session_path(
+        &state.settings.path_mappings,
+        session,
+        &session.transcript_path,
+    )
+    .unwrap_or_else(|| session.transcript_path.clone());
+
+    let reader = state.transcripts.entry(key.to_string()).or_default();
+    if let Err(e) = reader.read_new(&path) {
+        log::warn!(target: "eocc.transcript", "Failed to read transcript {}: {:?}", path, e);
+        return;
+    }
+    let new_usage = reader.take_new_usage();
+    let reader = &state.transcripts[key];
+
+    let Some(session) = state.sessions.get_mut(key) else {
+        return;
+    };
+    state.cost.record(session, &new_usage, &state.settings);
+    session.cost_usd = state.cost.session_cost_usd(key);
+    session.token_usage = reader.usage.clone();
+    session.context_tokens = reader
+        .last_usage
+        .as_ref()
+        .map(TokenUsage::context_tokens)
+        .unwrap_or(0);
+    if !reader.model.is_empty() {
+        session.model = reader.model.clone();
+    }
+    if !reader.last_assistant_message.is_empty() {
+        session.last_assistant_message = reader.last_assistant_message.clone();
+    }
+}
@@ -1,9 +1,15 @@
 use tauri::{Emitter, Manager};
 
+use std::sync::Arc;
+
+use crate::api::ApiServer;
+use crate::cost::is_over_daily_budget;
 use crate::menu::build_tray_menu;
 use crate::state::AppState;
 
 pub fn update_tray_and_badge(app: &tauri::AppHandle, state: &AppState) {
+    let dnd_active = state.settings.dnd.is_active();
+
     // Update tray menu
     if let Some(tray) = app.tray_by_id("main") {
         if let Ok(new_menu) = build_tray_menu(app, state) {
@@ -17,16 +23,31 @@ pub fn update_tray_and_badge(app: &tauri::AppHandle, state: &AppState) {
             format!("Eyes on Claude Code - {} waiting", waiting_count)
         } else if state.sessions.is_empty() {
             "Eyes on Claude Code - No active sessions".to_string()
+        } else if state.disconnected_session_count() > 0 {
+            format!(
+                "Eyes on Claude Code - {} disconnected",
+                state.disconnected_session_count()
+            )
         } else {
             "Eyes on Claude Code".to_string()
         };
+        let tooltip = if is_over_daily_budget(state) {
+            format!("{} - over daily budget", tooltip)
+        } else {
+            tooltip
+        };
+        let tooltip = if dnd_active {
+            format!("🌙 {} - Do Not Disturb", tooltip)
+        } else {
+            tooltip
+        };
         let _ = tray.set_tooltip(Some(tooltip));
     }
 
     // Update badge count using the dashboard window
     if let Some(window) = app.get_webview_window("dashboard") {
         let waiting_count = state.waiting_session_count();
-        let badge_count = if waiting_count > 0 {
+        let badge_count = if waiting_count > 0 && !dnd_active {
             Some(waiting_count as i64)
         } else {
             None
@@ -35,7 +56,12 @@ pub fn update_tray_and_badge(app: &tauri::AppHandle, state: &AppState) {
     }
 }
 
+/// Push the state to the dashboard and API clients (notifications are sent by the callers
+/// that apply events, see `notify_status_changes`)
 pub fn emit_state_update(app: &tauri::AppHandle, state: &AppState) {
     let data = state.to_dashboard_data();
     let _ = app.emit("state-updated", &data);
+    if let Some(api) = app.try_state::<Arc<ApiServer>>() {
+        api.broadcast("state-updated", &data);
+    }
 }
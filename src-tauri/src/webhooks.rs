@@ -0,0 +1,415 @@
+//! Outgoing webhooks: POST JSON to user-configured URLs when a session changes status.
+//!
+//! Delivery shells out to `curl` so any HTTP or HTTPS endpoint works. Bodies come from
+//! per-status templates where `{{field}}` is replaced by a `SessionInfo` field and
+//! `{{event.field}}` by a field of the session's latest `EventInfo`.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::Duration;
+
+use crate::notifications::status_text;
+use crate::state::{AppState, EventInfo, SessionInfo, SessionStatus};
+
+/// Delivery attempts per webhook call; the delay doubles after each failure
+const MAX_ATTEMPTS: u32 = 4;
+const INITIAL_BACKOFF: Duration = Duration::from_secs(2);
+const REQUEST_TIMEOUT_SECS: &str = "10";
+/// Template key used when there is none for the new status
+const DEFAULT_TEMPLATE_KEY: &str = "default";
+
+#[cfg(windows)]
+const NULL_DEVICE: &str = "NUL";
+#[cfg(not(windows))]
+const NULL_DEVICE: &str = "/dev/null";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookConfig {
+    pub url: String,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    /// Statuses that trigger the webhook (empty = WaitingPermission, WaitingInput and Completed)
+    #[serde(default)]
+    pub statuses: Vec<SessionStatus>,
+    /// Extra request headers, e.g. Authorization
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+    /// JSON body templates keyed by status name (e.g. "WaitingPermission") or "default"
+    #[serde(default)]
+    pub templates: BTreeMap<String, String>,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+impl WebhookConfig {
+    fn triggers_on(&self, status: &SessionStatus) -> bool {
+        if self.statuses.is_empty() {
+            matches!(
+                status,
+                SessionStatus::WaitingPermission
+                    | SessionStatus::WaitingInput
+                    | SessionStatus::Completed
+            )
+        } else {
+            self.statuses.contains(status)
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if !(self.url.starts_with("http://") || self.url.starts_with("https://")) {
+            return Err(format!(
+                "Webhook URL must start with http:// or https://: {}",
+                self.url
+            ));
+        }
+        let has_line_break = |s: &str| s.contains('\r') || s.contains('\n');
+        if has_line_break(&self.url)
+            || self.headers.iter().any(|(k, v)| {
+                k.is_empty() || k.contains(':') || has_line_break(k) || has_line_break(v)
+            })
+        {
+            return Err(
+                "Webhook URL and headers must be single-line 'Name: value' pairs".to_string(),
+            );
+        }
+        // Every template must render to valid JSON
+        let session = sample_session();
+        for (key, template) in &self.templates {
+            let body = render_template(template, &session, None);
+            serde_json::from_str::<serde_json::Value>(&body)
+                .map_err(|e| format!("Template {:?} does not render to valid JSON: {}", key, e))?;
+        }
+        Ok(())
+    }
+}
+
+/// One-line summary, e.g. "🔐 my-app - Waiting for permission: Bash: npm test"
+fn summary(session: &SessionInfo) -> String {
+    let mut text = format!(
+        "{} {} - {}",
+        session.status.emoji(),
+        session.display_name(),
+        status_text(&session.status)
+    );
+    if !session.waiting_for.is_empty() {
+        text.push_str(": ");
+        text.push_str(&session.waiting_for);
+    }
+    text
+}
+
+/// Template fields: session fields at the top level, event fields under `event.`
+fn template_fields(session: &SessionInfo, event: Option<&EventInfo>) -> BTreeMap<String, String> {
+    let mut fields = BTreeMap::new();
+    let mut add = |prefix: &str, value: serde_json::Value| {
+        if let serde_json::Value::Object(map) = value {
+            for (key, value) in map {
+                let text = match value {
+                    serde_json::Value::String(s) => s,
+                    serde_json::Value::Null => String::new(),
+                    other => other.to_string(),
+                };
+                fields.insert(format!("{}{}", prefix, key), text);
+            }
+        }
+    };
+    add("", serde_json::to_value(session).unwrap_or_default());
+    if let Some(event) = event {
+        add("event.", serde_json::to_value(event).unwrap_or_default());
+    }
+    fields.insert("summary".to_string(), summary(session));
+    fields
+}
+
+/// Replace `{{field}}` placeholders with JSON-escaped values (unknown fields become empty)
+fn render_template(template: &str, session: &SessionInfo, event: Option<&EventInfo>) -> String {
+    let fields = template_fields(session, event);
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let Some(end) = after.find("}}") else {
+            out.push_str(&rest[start..]);
+            return out;
+        };
+        let value = fields
+            .get(after[..end].trim())
+            .map(String::as_str)
+            .unwrap_or("");
+        let escaped = serde_json::to_string(value).unwrap_or_default();
+        out.push_str(&escaped[1..escaped.len() - 1]);
+        rest = &after[end + 2..];
+    }
+    out.push_str(rest);
+    out
+}
+
+fn build_body(webhook: &WebhookConfig, session: &SessionInfo, event: Option<&EventInfo>) -> String {
+    let status_key = format!("{:?}", session.status);
+    match webhook
+        .templates
+        .get(&status_key)
+        .or_else(|| webhook.templates.get(DEFAULT_TEMPLATE_KEY))
+    {
+        Some(template) => render_template(template, session, event),
+        None => serde_json::json!({
+            "text": summary(session),
+            "status": session.status,
+            "session": session,
+            "event": event,
+        })
+        .to_string(),
+    }
+}
+
+/// Custom headers for curl's `-H @file`, readable only by the current user, so secrets such as
+/// an Authorization value do not show up in the process list
+struct HeaderFile(PathBuf);
+
+impl HeaderFile {
+    fn new(headers: &BTreeMap<String, String>) -> Result<Self, String> {
+        static COUNTER: AtomicU32 = AtomicU32::new(0);
+        let path = std::env::temp_dir().join(format!(
+            "eocc-webhook-headers-{}-{}",
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        let mut options = OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        let file = Self(path);
+        let mut writer = options
+            .open(&file.0)
+            .map_err(|e| format!("Failed to write webhook headers: {}", e))?;
+        for (name, value) in headers {
+            writeln!(writer, "{}: {}", name, value)
+                .map_err(|e| format!("Failed to write webhook headers: {}", e))?;
+        }
+        Ok(file)
+    }
+}
+
+impl Drop for HeaderFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.0);
+    }
+}
+
+/// POST a JSON body once and return the HTTP status code; Err means no response was received
+fn post_json(webhook: &WebhookConfig, body: &str) -> Result<u16, String> {
+    let mut command = Command::new("curl");
+    command.args([
+        "-sS",
+        "-o",
+        NULL_DEVICE,
+        "-w",
+        "%{http_code}",
+        "--max-time",
+        REQUEST_TIMEOUT_SECS,
+        "-X",
+        "POST",
+        "-H",
+        "Content-Type: application/json",
+    ]);
+    let header_file = if webhook.headers.is_empty() {
+        None
+    } else {
+        Some(HeaderFile::new(&webhook.headers)?)
+    };
+    if let Some(header_file) = &header_file {
+        command
+            .arg("-H")
+            .arg(format!("@{}", header_file.0.display()));
+    }
+    command
+        .args(["--data-binary", "@-", "--"])
+        .arg(&webhook.url)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+
+    let mut child = command
+        .spawn()
+        .map_err(|e| format!("Failed to run curl: {}", e))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(body.as_bytes())
+            .map_err(|e| format!("Failed to send request body: {}", e))?;
+    }
+    let output = child
+        .wait_with_output()
+        .map_err(|e| format!("Failed to run curl: {}", e))?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+    }
+    String::from_utf8_lossy(&output.stdout)
+        .trim()
+        .parse::<u16>()
+        .map_err(|_| "Invalid response from curl".to_string())
+}
+
+/// Whether a failed delivery may succeed later: timeouts, rate limits and server errors.
+/// Other client errors (bad URL, bad token) would fail the same way again.
+fn is_retryable(code: u16) -> bool {
+    code == 408 || code == 429 || code >= 500
+}
+
+/// Deliver on a background thread, retrying transport errors and retryable HTTP statuses
+/// with exponential backoff
+fn deliver(webhook: WebhookConfig, body: String) {
+    std::thread::spawn(move || {
+        let mut delay = INITIAL_BACKOFF;
+        for attempt in 1..=MAX_ATTEMPTS {
+            let (error, retryable) = match post_json(&webhook, &body) {
+                Ok(code) if (200..300).contains(&code) => return,
+                Ok(code) => (format!("HTTP {}", code), is_retryable(code)),
+                Err(e) => (e, true),
+            };
+            log::warn!(
+                target: "eocc.webhooks",
+                "Delivery to {} failed (attempt {}/{}): {}",
+                webhook.url,
+                attempt,
+                MAX_ATTEMPTS,
+                error
+            );
+            if !retryable {
+                return;
+            }
+            if attempt < MAX_ATTEMPTS {
+                std::thread::sleep(delay);
+                delay *= 2;
+            }
+        }
+    });
+}
+
+/// Send webhooks for sessions that just changed status
+pub fn dispatch(state: &AppState, transitions: &[SessionInfo]) {
+    let webhooks: Vec<&WebhookConfig> = state
+        .settings
+        .webhooks
+        .iter()
+        .filter(|w| w.enabled)
+        .collect();
+    if webhooks.is_empty() {
+        return;
+    }
+    for session in transitions {
+        let event = state
+            .timelines
+            .get(&session.session_id)
+            .and_then(|t| t.events.back());
+        for webhook in webhooks.iter().filter(|w| w.triggers_on(&session.status)) {
+            deliver((*webhook).clone(), build_body(webhook, session, event));
+        }
+    }
+}
+
+fn sample_session() -> SessionInfo {
+    SessionInfo {
+        session_id: "test-session".to_string(),
+        project_name: "eocc-webhook-test".to_string(),
+        project_dir: "/tmp/eocc-webhook-test".to_string(),
+        status: SessionStatus::WaitingPermission,
+        waiting_for: "Bash: npm test".to_string(),
+        ..Default::default()
+    }
+}
+
+/// Send one sample notification without retries and return the HTTP status code
+pub fn send_test(webhook: &WebhookConfig) -> Result<u16, String> {
+    webhook.validate()?;
+    match post_json(webhook, &build_body(webhook, &sample_session(), None))? {
+        code if (200..300).contains(&code) => Ok(code),
+        code => Err(format!("HTTP {}", code)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Read};
+    use std::net::TcpListener;
+
+    /// Accept one request and return its lowercased header lines and body
+    fn receive_one(listener: TcpListener) -> (Vec<String>, String) {
+        let (stream, _) = listener.accept().unwrap();
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+        let mut headers = Vec::new();
+        loop {
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            let line = line.trim_end();
+            if line.is_empty() {
+                break;
+            }
+            headers.push(line.to_ascii_lowercase());
+        }
+        let length: usize = headers
+            .iter()
+            .find_map(|h| h.strip_prefix("content-length:"))
+            .map(|v| v.trim().parse().unwrap())
+            .unwrap_or(0);
+        let mut body = vec![0u8; length];
+        reader.read_exact(&mut body).unwrap();
+        let mut stream = stream;
+        stream
+            .write_all(b"HTTP/1.1 204 No Content\r\nConnection: close\r\n\r\n")
+            .unwrap();
+        (headers, String::from_utf8(body).unwrap())
+    }
+
+    #[test]
+    fn send_test_posts_rendered_template_and_headers() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        let server = std::thread::spawn(move || receive_one(listener));
+
+        let webhook = WebhookConfig {
+            url,
+            enabled: true,
+            statuses: Vec::new(),
+            headers: BTreeMap::from([("X-Token".to_string(), "s3cret \"quoted\"".to_string())]),
+            templates: BTreeMap::from([(
+                "WaitingPermission".to_string(),
+                r#"{"text": "{{summary}}", "project": "{{project_name}}", "tool": "{{waiting_for}}"}"#
+                    .to_string(),
+            )]),
+        };
+        assert_eq!(send_test(&webhook), Ok(204));
+
+        let (headers, body) = server.join().unwrap();
+        assert!(headers.iter().any(|h| h == "post /hook http/1.1"));
+        assert!(headers
+            .iter()
+            .any(|h| h == "content-type: application/json"));
+        assert!(headers.iter().any(|h| h == "x-token: s3cret \"quoted\""));
+        let body: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(body["project"], "eocc-webhook-test");
+        assert_eq!(body["tool"], "Bash: npm test");
+        assert_eq!(
+            body["text"],
+            "🔐 eocc-webhook-test - Waiting for permission: Bash: npm test"
+        );
+    }
+
+    #[test]
+    fn only_transient_statuses_are_retried() {
+        for code in [408, 429, 500, 502, 503] {
+            assert!(is_retryable(code), "{}", code);
+        }
+        for code in [400, 401, 403, 404, 410, 422] {
+            assert!(!is_retryable(code), "{}", code);
+        }
+    }
+}
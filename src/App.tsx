@@ -54,8 +54,16 @@ const Dashboard = () => {
 
   return (
     <div className="container bg-bg-primary h-screen rounded-xl max-w-[900px] mx-auto flex flex-col p-2.5">
-      <Header sessions={dashboardData.sessions} onRefresh={refreshData} />
-      <SessionList sessions={dashboardData.sessions} />
+      <Header
+        sessions={dashboardData.sessions}
+        dndActive={dashboardData.dnd_active}
+        onRefresh={refreshData}
+      />
+      <SessionList
+        sessions={dashboardData.sessions}
+        repos={dashboardData.repos}
+        approvals={dashboardData.approvals}
+      />
     </div>
   );
 };
@@ -0,0 +1,52 @@
+import { useState } from 'react';
+import type { ApprovalRequest } from '@/types';
+import { resolveApproval } from '@/lib/tauri';
+
+interface ApprovalPanelProps {
+  approvals: ApprovalRequest[];
+}
+
+export const ApprovalPanel = ({ approvals }: ApprovalPanelProps) => {
+  const [error, setError] = useState<string | null>(null);
+
+  const handleResolve = async (id: string, allow: boolean) => {
+    try {
+      setError(null);
+      await resolveApproval(id, allow);
+    } catch (err) {
+      const message = err instanceof Error ? err.message : String(err);
+      setError(message);
+      console.error('Failed to resolve approval:', err);
+    }
+  };
+
+  return (
+    <div className="border-t border-bg-card px-2 py-1.5 space-y-1.5">
+      {error && <div className="text-red-400 text-[0.625rem] truncate">{error}</div>}
+      {approvals.map((approval) => (
+        <div key={approval.id} className="space-y-1">
+          <div className="text-warning text-[0.625rem] font-semibold">🔐 {approval.tool_name}</div>
+          {approval.tool_input && (
+            <pre className="font-mono text-text-primary bg-bg-card rounded p-1 text-[0.5rem] max-h-32 overflow-auto whitespace-pre-wrap break-all">
+              {JSON.stringify(approval.tool_input, null, 2)}
+            </pre>
+          )}
+          <div className="flex gap-1">
+            <button
+              onClick={() => handleResolve(approval.id, true)}
+              className="flex-1 py-0.5 text-[0.625rem] text-white bg-success/60 hover:bg-success rounded transition-colors"
+            >
+              Allow
+            </button>
+            <button
+              onClick={() => handleResolve(approval.id, false)}
+              className="flex-1 py-0.5 text-[0.625rem] text-white bg-red-500/60 hover:bg-red-500 rounded transition-colors"
+            >
+              Deny
+            </button>
+          </div>
+        </div>
+      ))}
+    </div>
+  );
+};
@@ -2,10 +2,11 @@ import type { SessionInfo } from '@/types';
 
 interface HeaderProps {
   sessions: SessionInfo[];
+  dndActive: boolean;
   onRefresh: () => void;
 }
 
-export const Header = ({ sessions, onRefresh }: HeaderProps) => {
+export const Header = ({ sessions, dndActive, onRefresh }: HeaderProps) => {
   const waitingCount = sessions.filter(
     (s) => s.status === 'WaitingPermission' || s.status === 'WaitingInput'
   ).length;
@@ -23,6 +24,7 @@ export const Header = ({ sessions, onRefresh }: HeaderProps) => {
             className={`w-2 h-2 rounded-full bg-success ${isWaiting ? 'bg-warning animate-pulse-slow' : ''}`}
           />
           <span>{isWaiting ? `${waitingCount} waiting` : 'Monitoring'}</span>
+          {dndActive && <span title="Do Not Disturb">🌙</span>}
         </div>
       </div>
       <div className="flex justify-between items-center">
@@ -1,32 +1,58 @@
 import { useState, useEffect, useCallback, useRef } from 'react';
-import type { SessionInfo, GitInfo } from '@/types';
-import { getStatusEmoji, getStatusClass, formatRelativeTime } from '@/lib/utils';
+import type { ApprovalRequest, SessionInfo, GitInfo, TurnSummary } from '@/types';
 import {
+  getStatusEmoji,
+  getStatusClass,
+  formatRelativeTime,
+  formatTokens,
+  isSessionSilenced,
+  isSessionSnoozed,
+  parseDiffSpec,
+  sessionDisplayName,
+} from '@/lib/utils';
+import {
+  acknowledgeSession,
   removeSession,
+  setSessionAlias,
+  setSessionMuted,
+  setSessionPinned,
+  snoozeSession,
   getRepoGitInfo,
+  listSessionTurns,
+  onGitInfoUpdated,
   openDiff,
   openTmuxViewer,
   type DiffType,
 } from '@/lib/tauri';
 import { ChevronDownIcon } from './icons';
 import { DiffButton } from './DiffButton';
+import { ApprovalPanel } from './ApprovalPanel';
 
 const FOCUS_REFRESH_MIN_INTERVAL = 5000;
+const SNOOZE_MINUTES = 60;
 
 interface SessionCardProps {
   session: SessionInfo;
+  approvals: ApprovalRequest[];
+  // Set when the session runs on another machine
+  remoteHost?: string;
 }
 
-export const SessionCard = ({ session }: SessionCardProps) => {
+export const SessionCard = ({ session, approvals, remoteHost }: SessionCardProps) => {
   const [isExpanded, setIsExpanded] = useState(false);
   const [gitInfo, setGitInfo] = useState<GitInfo | null>(null);
   const [isLoadingGit, setIsLoadingGit] = useState(false);
   const [error, setError] = useState<string | null>(null);
   const [relativeTime, setRelativeTime] = useState(() => formatRelativeTime(session.last_event));
+  const [aliasDraft, setAliasDraft] = useState<string | null>(null);
+  const [turns, setTurns] = useState<TurnSummary[]>([]);
+  const [customDiff, setCustomDiff] = useState('');
   const isLoadingGitRef = useRef(false);
   const lastFocusFetchTimeRef = useRef(0);
 
   const statusClass = getStatusClass(session.status);
+  // Git and diffs run on the host path of a containerized session
+  const projectPath = session.local_dir || session.project_dir;
 
   // Update relative time display periodically (every 60 seconds)
   useEffect(() => {
@@ -49,13 +75,48 @@ export const SessionCard = ({ session }: SessionCardProps) => {
 
   const handleRemove = async () => {
     try {
-      await removeSession(session.project_dir);
+      await removeSession(session.session_id);
     } catch (error) {
       console.error('Failed to remove session:', error);
     }
   };
 
+  const runAction = async (action: () => Promise<unknown>, label: string) => {
+    try {
+      setError(null);
+      await action();
+    } catch (err) {
+      const message = err instanceof Error ? err.message : String(err);
+      setError(`Failed to ${label}: ${message}`);
+      console.error(`Failed to ${label}:`, err);
+    }
+  };
+
+  const isSnoozed = isSessionSnoozed(session);
+
+  const handleTogglePin = () =>
+    runAction(() => setSessionPinned(session.session_id, !session.pinned), 'pin session');
+
+  const handleToggleMute = () =>
+    runAction(() => setSessionMuted(session.session_id, !session.muted), 'mute session');
+
+  const handleToggleSnooze = () => {
+    const until = isSnoozed ? null : new Date(Date.now() + SNOOZE_MINUTES * 60_000).toISOString();
+    runAction(() => snoozeSession(session.session_id, until), 'snooze session');
+  };
+
+  const handleSaveAlias = () => {
+    if (aliasDraft === null) return;
+    const alias = aliasDraft;
+    setAliasDraft(null);
+    runAction(() => setSessionAlias(session.session_id, alias), 'rename session');
+  };
+
   const handleToggleExpand = () => {
+    // Looking at the session counts as noticing it
+    acknowledgeSession(session.session_id).catch((err) =>
+      console.error('Failed to acknowledge session:', err)
+    );
     setIsExpanded(!isExpanded);
   };
 
@@ -65,7 +126,7 @@ export const SessionCard = ({ session }: SessionCardProps) => {
     setIsLoadingGit(true);
     setError(null);
     try {
-      const info = await getRepoGitInfo(session.project_dir);
+      const info = await getRepoGitInfo(projectPath, remoteHost);
       setGitInfo(info);
     } catch (err) {
       const message = err instanceof Error ? err.message : String(err);
@@ -75,12 +136,25 @@ export const SessionCard = ({ session }: SessionCardProps) => {
       isLoadingGitRef.current = false;
       setIsLoadingGit(false);
     }
-  }, [session.project_dir]);
+  }, [projectPath, remoteHost]);
 
-  // Reset git info when session event changes (e.g., after commit)
+  // Local repositories are watched and pushed on change
   useEffect(() => {
-    setGitInfo(null);
-  }, [session.last_event]);
+    let unlisten: (() => void) | undefined;
+    onGitInfoUpdated((update) => {
+      if (update.project_dir === projectPath && update.host === (remoteHost ?? '')) {
+        setGitInfo(update.info);
+      }
+    }).then((u) => {
+      unlisten = u;
+    });
+    return () => unlisten?.();
+  }, [projectPath, remoteHost]);
+
+  // Remote repositories cannot be watched: reload after session events (e.g., after commit)
+  useEffect(() => {
+    if (remoteHost) setGitInfo(null);
+  }, [session.last_event, remoteHost]);
 
   // Load git info when expanded
   useEffect(() => {
@@ -105,12 +179,33 @@ export const SessionCard = ({ session }: SessionCardProps) => {
     return () => window.removeEventListener('focus', handleFocus);
   }, [isExpanded, fetchGitInfo]);
 
+  // Load turn checkpoints when expanded; a new prompt or stop adds or completes one
+  useEffect(() => {
+    if (!isExpanded || session.turns === 0) return;
+    listSessionTurns(session.session_id)
+      .then(setTurns)
+      .catch((err) => console.error('Failed to load turns:', err));
+  }, [isExpanded, session.session_id, session.turns, session.status]);
+
   const handleDiffClick = async (type: DiffType) => {
     try {
       setError(null);
       // For branch diff, use the detected default branch
       const baseBranch = type === 'branch' ? gitInfo?.default_branch : undefined;
-      await openDiff(session.project_dir, type, baseBranch);
+      await openDiff(projectPath, type, baseBranch, remoteHost, session.session_id);
+    } catch (err) {
+      const message = err instanceof Error ? err.message : String(err);
+      setError(message);
+      console.error('Failed to open diff:', err);
+    }
+  };
+
+  const handleCustomDiff = async () => {
+    const spec = parseDiffSpec(customDiff);
+    if (!spec) return;
+    try {
+      setError(null);
+      await openDiff(projectPath, 'custom', undefined, remoteHost, session.session_id, spec);
     } catch (err) {
       const message = err instanceof Error ? err.message : String(err);
       setError(message);
@@ -134,6 +229,7 @@ export const SessionCard = ({ session }: SessionCardProps) => {
     waiting: 'border-l-4 border-warning',
     completed: 'border-l-4 border-info',
     active: 'border-l-4 border-success',
+    disconnected: 'border-l-4 border-text-secondary opacity-60',
   }[statusClass];
 
   return (
@@ -144,11 +240,29 @@ export const SessionCard = ({ session }: SessionCardProps) => {
       <div className="flex items-center p-2 gap-2 cursor-pointer" onClick={handleToggleExpand}>
         <div className="text-base w-6 shrink-0 text-center">{getStatusEmoji(session.status)}</div>
         <div className="flex-1 min-w-0 overflow-hidden">
-          <div className="font-semibold truncate text-xs">{session.project_name}</div>
+          <div className="font-semibold truncate text-xs">
+            {session.pinned && '📌 '}
+            {sessionDisplayName(session)}
+            {isSessionSilenced(session) && ' 🔕'}
+          </div>
           <div className="font-mono text-text-secondary truncate text-[0.5rem]">
+            {remoteHost && <span className="text-info">🖥 {remoteHost}:</span>}
+            {session.container_id && <span className="text-info">📦 </span>}
             {session.project_dir}
+            {session.repo && <span className="text-success"> ⎇ {session.repo.branch}</span>}
           </div>
           {relativeTime && <div className="text-text-secondary text-[0.5rem]">{relativeTime}</div>}
+          {session.context_tokens > 0 && (
+            <div className="text-text-secondary text-[0.5rem] truncate">
+              {session.model} · {formatTokens(session.context_tokens)} ctx
+              {session.cost_usd > 0 && ` · $${session.cost_usd.toFixed(2)}`}
+            </div>
+          )}
+          {session.last_assistant_message && (
+            <div className="text-text-primary text-[0.5rem] line-clamp-2 mt-0.5">
+              {session.last_assistant_message}
+            </div>
+          )}
           {session.waiting_for && (
             <div className="text-warning bg-warning/10 rounded inline-block mt-1 truncate max-w-full text-[0.5rem] py-0.5 px-1">
               ⏸ {session.waiting_for}
@@ -164,6 +278,9 @@ export const SessionCard = ({ session }: SessionCardProps) => {
         </div>
       </div>
 
+      {/* Pending permission prompts */}
+      {approvals.length > 0 && <ApprovalPanel approvals={approvals} />}
+
       {/* Expanded content - Git info and actions */}
       {isExpanded && (
         <div className="border-t border-bg-card px-2 py-2 space-y-1.5">
@@ -216,6 +333,41 @@ export const SessionCard = ({ session }: SessionCardProps) => {
                 )}
               </div>
 
+              {/* Everything this session changed */}
+              {session.start_snapshot && (
+                <div className="flex items-center justify-between py-0.5">
+                  <div className="flex items-center gap-1 min-w-0">
+                    <span className="text-text-secondary text-[0.625rem] shrink-0">
+                      since start:
+                    </span>
+                    <span className="text-info text-[0.625rem] font-mono">
+                      #{session.start_head.slice(0, 7) || 'initial'}
+                    </span>
+                  </div>
+                  <DiffButton onClick={() => handleDiffClick('since_session_start')} small />
+                </div>
+              )}
+
+              {/* Per-turn changes, newest first */}
+              {turns.length > 0 && (
+                <div className="max-h-24 overflow-y-auto space-y-px">
+                  {[...turns].reverse().map((turn) => (
+                    <div key={turn.turn} className="flex items-center justify-between gap-1">
+                      <span className="text-text-secondary text-[0.625rem] truncate">
+                        turn {turn.turn} · {formatRelativeTime(turn.prompt_time)}
+                        {!turn.end_time && ' (running)'}
+                      </span>
+                      <span className="text-[0.5rem] font-mono shrink-0">
+                        {turn.files_changed} files{' '}
+                        <span className="text-green-400">+{turn.insertions}</span>{' '}
+                        <span className="text-red-400">-{turn.deletions}</span>
+                      </span>
+                      <DiffButton onClick={() => handleDiffClick(`turn:${turn.turn}`)} small />
+                    </div>
+                  ))}
+                </div>
+              )}
+
               {/* Latest commit */}
               <div className="flex items-center justify-between py-0.5">
                 <div className="flex items-center gap-1 min-w-0">
@@ -232,16 +384,93 @@ export const SessionCard = ({ session }: SessionCardProps) => {
                 <div className="flex items-center gap-1 min-w-0">
                   <span className="text-text-secondary text-[0.625rem] shrink-0">branch:</span>
                   <span className="text-success text-[0.625rem] truncate">{gitInfo.branch}</span>
+                  {(gitInfo.ahead_default > 0 || gitInfo.behind_default > 0) && (
+                    <span
+                      className="text-text-secondary text-[0.625rem] font-mono shrink-0"
+                      title={`vs ${gitInfo.default_branch}`}
+                    >
+                      ↑{gitInfo.ahead_default} ↓{gitInfo.behind_default}
+                    </span>
+                  )}
                 </div>
                 <DiffButton onClick={() => handleDiffClick('branch')} small className="shrink-0" />
               </div>
+
+              {/* Custom range, stash or upstream diff */}
+              <div className="flex items-center justify-between gap-1 py-0.5">
+                <input
+                  value={customDiff}
+                  placeholder="main...HEAD, stash@{0}, upstream -- paths"
+                  onChange={(e) => setCustomDiff(e.target.value)}
+                  onKeyDown={(e) => {
+                    if (e.key === 'Enter') handleCustomDiff();
+                  }}
+                  className="flex-1 min-w-0 bg-bg-card rounded px-1.5 py-0.5 text-[0.625rem] font-mono text-text-primary outline-none"
+                />
+                {customDiff.trim() && (
+                  <DiffButton onClick={handleCustomDiff} small className="shrink-0" />
+                )}
+              </div>
+
+              {/* Upstream and stash */}
+              {(gitInfo.upstream || gitInfo.stash_count > 0) && (
+                <div className="flex items-center gap-1 py-0.5 min-w-0 text-[0.625rem]">
+                  {gitInfo.upstream && (
+                    <>
+                      <span className="text-text-secondary shrink-0">upstream:</span>
+                      <span className="text-info truncate">{gitInfo.upstream}</span>
+                      <span className="text-text-secondary font-mono shrink-0">
+                        ↑{gitInfo.ahead} ↓{gitInfo.behind}
+                      </span>
+                    </>
+                  )}
+                  {gitInfo.stash_count > 0 && (
+                    <span className="text-text-secondary shrink-0 ml-auto">
+                      stash: {gitInfo.stash_count}
+                    </span>
+                  )}
+                </div>
+              )}
+
+              {/* In-progress merge/rebase and conflicts */}
+              {(gitInfo.operation || gitInfo.unmerged_paths.length > 0) && (
+                <div className="text-warning bg-warning/10 rounded px-1.5 py-0.5 text-[0.625rem]">
+                  {gitInfo.operation && `${gitInfo.operation.replace('_', '-')} in progress`}
+                  {gitInfo.operation && gitInfo.unmerged_paths.length > 0 && ' · '}
+                  {gitInfo.unmerged_paths.length > 0 &&
+                    `${gitInfo.unmerged_paths.length} conflicted file(s)`}
+                </div>
+              )}
+
+              {/* Changed files */}
+              {gitInfo.files.length > 0 && (
+                <div className="max-h-24 overflow-y-auto font-mono text-[0.5rem] space-y-px">
+                  {gitInfo.files.map((file) => (
+                    <div key={file.path} className="flex items-center gap-1 min-w-0">
+                      <span className="text-text-secondary shrink-0">
+                        {file.index}
+                        {file.worktree}
+                      </span>
+                      <span className="truncate flex-1" title={file.orig_path}>
+                        {file.path}
+                      </span>
+                      {file.insertions !== null && (
+                        <span className="text-green-400 shrink-0">+{file.insertions}</span>
+                      )}
+                      {file.deletions !== null && (
+                        <span className="text-red-400 shrink-0">-{file.deletions}</span>
+                      )}
+                    </div>
+                  ))}
+                </div>
+              )}
             </>
           ) : (
             <div className="text-text-secondary text-[0.625rem]">Not a git repository</div>
           )}
 
-          {/* tmux pane info */}
-          {session.tmux_pane && (
+          {/* tmux pane info (the pane of a remote session is not reachable from here) */}
+          {session.tmux_pane && !remoteHost && (
             <div className="pt-1.5 border-t border-bg-card">
               <div className="flex items-center justify-between py-0.5">
                 <div className="flex items-center gap-1">
@@ -260,6 +489,43 @@ export const SessionCard = ({ session }: SessionCardProps) => {
             </div>
           )}
 
+          {/* Session prefs */}
+          <div className="pt-1.5 border-t border-bg-card space-y-1">
+            {aliasDraft !== null && (
+              <input
+                autoFocus
+                value={aliasDraft}
+                placeholder={session.project_name}
+                onChange={(e) => setAliasDraft(e.target.value)}
+                onBlur={handleSaveAlias}
+                onKeyDown={(e) => {
+                  if (e.key === 'Enter') handleSaveAlias();
+                  if (e.key === 'Escape') setAliasDraft(null);
+                }}
+                className="w-full bg-bg-card rounded px-1.5 py-0.5 text-[0.625rem] text-text-primary outline-none"
+              />
+            )}
+            <div className="flex gap-1">
+              {[
+                { label: 'Rename', onClick: () => setAliasDraft(session.alias) },
+                { label: session.pinned ? 'Unpin' : 'Pin', onClick: handleTogglePin },
+                { label: session.muted ? 'Unmute' : 'Mute', onClick: handleToggleMute },
+                {
+                  label: isSnoozed ? 'Unsnooze' : `Snooze ${SNOOZE_MINUTES}m`,
+                  onClick: handleToggleSnooze,
+                },
+              ].map(({ label, onClick }) => (
+                <button
+                  key={label}
+                  onClick={onClick}
+                  className="flex-1 text-[0.625rem] text-text-secondary hover:text-white px-1.5 py-0.5 bg-bg-card rounded hover:bg-white/10 transition-colors"
+                >
+                  {label}
+                </button>
+              ))}
+            </div>
+          </div>
+
           {/* Remove session button */}
           <div className="pt-1.5 border-t border-bg-card">
             <button
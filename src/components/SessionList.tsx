@@ -1,20 +1,81 @@
-import type { SessionInfo } from '@/types';
+import { Fragment } from 'react';
+import type { ApprovalRequest, RepoGroup, SessionInfo } from '@/types';
 import { SessionCard } from './SessionCard';
 import { EmptyState } from './EmptyState';
 
 interface SessionListProps {
   sessions: SessionInfo[];
+  repos: RepoGroup[];
+  approvals: ApprovalRequest[];
 }
 
-export const SessionList = ({ sessions }: SessionListProps) => {
+export const SessionList = ({ sessions, repos, approvals }: SessionListProps) => {
+  const sessionsById = new Map(sessions.map((s) => [s.session_id, s]));
+  const approvalsFor = (sessionId: string) => approvals.filter((a) => a.session_id === sessionId);
+  const sessionsIn = (ids: string[]) =>
+    ids.map((id) => sessionsById.get(id)).filter((s): s is SessionInfo => s !== undefined);
+  // This machine first, then remote hosts in order of appearance
+  const hosts = [...new Set(['', ...repos.map((r) => r.host)])];
+
+  const renderRepo = (repo: RepoGroup) => {
+    const repoSessions = sessionsIn(repo.worktrees.flatMap((w) => w.session_ids));
+    const remoteHost = repo.host || undefined;
+
+    // Only show a repository header when several sessions share the repository
+    if (repoSessions.length === 1) {
+      const session = repoSessions[0];
+      return (
+        <SessionCard
+          key={session.session_id}
+          session={session}
+          approvals={approvalsFor(session.session_id)}
+          remoteHost={remoteHost}
+        />
+      );
+    }
+
+    return (
+      <div
+        key={repo.common_dir || repo.worktrees[0]?.path || repo.name}
+        className="flex flex-col gap-1"
+      >
+        <div className="text-text-secondary text-[0.625rem] px-1 truncate">
+          📁 {repo.name} ({repoSessions.length})
+        </div>
+        {repo.worktrees.map((worktree) => (
+          <div key={worktree.path} className="flex flex-col gap-1 pl-2">
+            {repo.common_dir && repo.worktrees.length > 1 && (
+              <div className="text-success text-[0.5rem] px-1 truncate font-mono">
+                ⎇ {worktree.branch}
+              </div>
+            )}
+            {sessionsIn(worktree.session_ids).map((session) => (
+              <SessionCard
+                key={session.session_id}
+                session={session}
+                approvals={approvalsFor(session.session_id)}
+                remoteHost={remoteHost}
+              />
+            ))}
+          </div>
+        ))}
+      </div>
+    );
+  };
+
   return (
     <div className="flex-1 overflow-y-scroll min-h-0">
       <div className="flex flex-col gap-2">
         {sessions.length === 0 ? (
           <EmptyState icon="📭" message="No active sessions" />
         ) : (
-          sessions.map((session) => (
-            <SessionCard key={session.project_dir || session.project_name} session={session} />
+          hosts.map((host) => (
+            <Fragment key={host || 'local'}>
+              {host && (
+                <div className="text-info text-[0.625rem] px-1 pt-1 truncate">🖥 {host}</div>
+              )}
+              {repos.filter((repo) => repo.host === host).map(renderRepo)}
+            </Fragment>
           ))
         )}
       </div>
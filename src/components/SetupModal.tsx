@@ -19,6 +19,8 @@ const HOOK_LABELS: Record<keyof HookStatus, string> = {
   stop: 'Stop',
   post_tool_use: 'PostToolUse',
   user_prompt_submit: 'UserPromptSubmit',
+  pre_tool_use: 'PreToolUse',
+  permission_request: 'PermissionRequest',
   notification_permission: 'Notification (permission_prompt)',
   notification_idle: 'Notification (idle_prompt)',
 };
@@ -1,7 +1,16 @@
 import { useState, useEffect, useCallback, useRef, type ReactNode } from 'react';
 import type { DashboardData, Settings, SessionInfo } from '@/types';
-import { getDashboardData, getSettings, onStateUpdated, onSettingsUpdated } from '@/lib/tauri';
+import {
+  getDashboardData,
+  getSettings,
+  onBudgetAlert,
+  onDndDigest,
+  onSessionReminder,
+  onStateUpdated,
+  onSettingsUpdated,
+} from '@/lib/tauri';
 import { playCompletionSound, playWaitingSound } from '@/lib/audio';
+import { isSessionSilenced } from '@/lib/utils';
 import { AppContext, defaultDashboardData, defaultSettings } from './appContextStore';
 
 export const AppProvider = ({ children }: { children: ReactNode }) => {
@@ -10,6 +19,7 @@ export const AppProvider = ({ children }: { children: ReactNode }) => {
   const [isLoading, setIsLoading] = useState(true);
   const previousStatesRef = useRef<Map<string, string>>(new Map());
   const soundEnabledRef = useRef(true);
+  const dndActiveRef = useRef(false);
 
   // Keep soundEnabledRef in sync with settings
   useEffect(() => {
@@ -17,14 +27,18 @@ export const AppProvider = ({ children }: { children: ReactNode }) => {
   }, [settings.sound_enabled]);
 
   const checkAndPlaySounds = useCallback((sessions: SessionInfo[]) => {
-    if (!soundEnabledRef.current) return;
-
     for (const session of sessions) {
-      const key = session.project_dir || session.project_name;
+      const key = session.session_id;
       const prevStatus = previousStatesRef.current.get(key);
       const currentStatus = session.status;
 
-      if (prevStatus !== currentStatus) {
+      // Statuses are still tracked during Do Not Disturb so nothing replays when it ends
+      if (
+        prevStatus !== currentStatus &&
+        soundEnabledRef.current &&
+        !dndActiveRef.current &&
+        !isSessionSilenced(session)
+      ) {
         if (currentStatus === 'Completed') {
           playCompletionSound();
         } else if (currentStatus === 'WaitingPermission' || currentStatus === 'WaitingInput') {
@@ -35,7 +49,7 @@ export const AppProvider = ({ children }: { children: ReactNode }) => {
     }
 
     // Cleanup old sessions
-    const currentKeys = new Set(sessions.map((s) => s.project_dir || s.project_name));
+    const currentKeys = new Set(sessions.map((s) => s.session_id));
     for (const key of previousStatesRef.current.keys()) {
       if (!currentKeys.has(key)) {
         previousStatesRef.current.delete(key);
@@ -73,6 +87,7 @@ export const AppProvider = ({ children }: { children: ReactNode }) => {
     const unlisteners: Array<() => void> = [];
 
     onStateUpdated((data) => {
+      dndActiveRef.current = data.dnd_active;
       checkAndPlaySounds(data.sessions);
       setDashboardData(data);
     }).then((unlisten) => unlisteners.push(unlisten));
@@ -81,6 +96,22 @@ export const AppProvider = ({ children }: { children: ReactNode }) => {
       setSettings(newSettings);
     }).then((unlisten) => unlisteners.push(unlisten));
 
+    onBudgetAlert((alert) => {
+      console.warn(
+        `Budget exceeded (${alert.scope} ${alert.label}): $${alert.spent_usd.toFixed(2)} / $${alert.budget_usd.toFixed(2)}`
+      );
+      if (soundEnabledRef.current && !dndActiveRef.current) playWaitingSound();
+    }).then((unlisten) => unlisteners.push(unlisten));
+
+    onSessionReminder(() => {
+      if (soundEnabledRef.current) playWaitingSound();
+    }).then((unlisten) => unlisteners.push(unlisten));
+
+    // One chime for everything that happened during Do Not Disturb
+    onDndDigest(() => {
+      if (soundEnabledRef.current) playWaitingSound();
+    }).then((unlisten) => unlisteners.push(unlisten));
+
     return () => {
       unlisteners.forEach((unlisten) => unlisten());
     };
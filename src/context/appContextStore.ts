@@ -8,13 +8,39 @@ export interface AppContextValue {
   refreshData: () => Promise<void>;
 }
 
-export const defaultDashboardData: DashboardData = { sessions: [], events: [] };
+export const defaultDashboardData: DashboardData = {
+  sessions: [],
+  projects: [],
+  repos: [],
+  events: [],
+  approvals: [],
+  dnd_active: false,
+};
 
 export const defaultSettings: Settings = {
   always_on_top: true,
   opacity_active: 1.0,
   opacity_inactive: 0.3,
   sound_enabled: true,
+  history_retention_days: 30,
+  auto_archive_disconnected_minutes: 0,
+  model_prices: [],
+  daily_budget_usd: 0,
+  session_budget_usd: 0,
+  api_enabled: false,
+  api_port: 17380,
+  approval_timeout_secs: 120,
+  notifications: {
+    waiting_permission: { enabled: true, actions: ['open_dashboard', 'jump_to_pane', 'snooze'] },
+    waiting_input: { enabled: true, actions: ['open_dashboard', 'jump_to_pane', 'snooze'] },
+    completed: { enabled: true, actions: ['open_dashboard', 'jump_to_pane'] },
+    snooze_minutes: 15,
+  },
+  webhooks: [],
+  reminders: { enabled: true, intervals_minutes: [2, 10, 30], project_intervals: {} },
+  dnd: { schedule: [], paused_until: null },
+  remote: { enabled: false, bind_address: '127.0.0.1', port: 17381, ssh_hosts: {} },
+  path_mappings: [],
 };
 
 export const AppContext = createContext<AppContextValue | null>(null);
@@ -2,27 +2,118 @@ import { invoke } from '@tauri-apps/api/core';
 import { listen, type UnlistenFn } from '@tauri-apps/api/event';
 import { getCurrentWindow, getAllWindows } from '@tauri-apps/api/window';
 import type {
+  ApiInfo,
+  BudgetAlert,
+  CostSummary,
   DashboardData,
+  DiffSpec,
   DiffType,
+  EventFilter,
+  EventPage,
   GitInfo,
+  GitInfoUpdate,
+  ModelPrice,
+  PathMapping,
+  RemoteInfo,
+  NotificationSettings,
+  QuietHours,
+  ReminderSettings,
+  Policy,
+  SessionInfo,
+  SessionReminder,
+  SessionTimeline,
   Settings,
   SetupStatus,
+  StatsReport,
+  TurnSummary,
   TmuxPane,
   TmuxPaneSize,
+  WebhookConfig,
 } from '@/types';
 
 // Commands
 export const getDashboardData = () => invoke<DashboardData>('get_dashboard_data');
-export const removeSession = (projectDir: string) => invoke('remove_session', { projectDir });
+export const removeSession = (sessionId: string) => invoke('remove_session', { sessionId });
+export const setSessionAlias = (sessionId: string, alias: string) =>
+  invoke('set_session_alias', { sessionId, alias });
+export const setSessionPinned = (sessionId: string, pinned: boolean) =>
+  invoke('set_session_pinned', { sessionId, pinned });
+export const setSessionMuted = (sessionId: string, muted: boolean) =>
+  invoke('set_session_muted', { sessionId, muted });
+// until is an RFC 3339 time; null unsnoozes
+export const snoozeSession = (sessionId: string, until: string | null) =>
+  invoke('snooze_session', { sessionId, until });
+export const getSessionTimeline = (sessionId: string) =>
+  invoke<SessionTimeline>('get_session_timeline', { sessionId });
 export const clearAllSessions = () => invoke('clear_all_sessions');
 export const getSettings = () => invoke<Settings>('get_settings');
-export const getRepoGitInfo = (projectDir: string) =>
-  invoke<GitInfo>('get_repo_git_info', { projectDir });
+// host: the session's host, so git runs over SSH for remote sessions
+export const getRepoGitInfo = (projectDir: string, host?: string) =>
+  invoke<GitInfo>('get_repo_git_info', { projectDir, host });
+
+export const queryEvents = (filter: EventFilter, offset = 0, limit = 100) =>
+  invoke<EventPage>('query_events', { filter, offset, limit });
+export const getStats = (days?: number) => invoke<StatsReport>('get_stats', { days });
+export const setHistoryRetentionDays = (days: number) =>
+  invoke('set_history_retention_days', { days });
+
+export const setAutoArchiveMinutes = (minutes: number) =>
+  invoke('set_auto_archive_minutes', { minutes });
+
+export const getCosts = () => invoke<CostSummary>('get_costs');
+export const setPriceTable = (prices: ModelPrice[]) => invoke('set_price_table', { prices });
+export const setBudgets = (dailyUsd: number, sessionUsd: number) =>
+  invoke('set_budgets', { dailyUsd, sessionUsd });
+
+export const setNotificationSettings = (notifications: NotificationSettings) =>
+  invoke('set_notification_settings', { notifications });
+
+export const setReminderSettings = (reminders: ReminderSettings) =>
+  invoke('set_reminder_settings', { reminders });
+export const acknowledgeSession = (sessionId: string) =>
+  invoke('acknowledge_session', { sessionId });
+
+export const setDndSchedule = (schedule: QuietHours[]) => invoke('set_dnd_schedule', { schedule });
+// 0 resumes notifications
+export const pauseNotifications = (minutes: number) => invoke('pause_notifications', { minutes });
+
+export const setWebhooks = (webhooks: WebhookConfig[]) => invoke('set_webhooks', { webhooks });
+export const testWebhook = (webhook: WebhookConfig) => invoke<number>('test_webhook', { webhook });
+
+export const resolveApproval = (id: string, allow: boolean, message?: string) =>
+  invoke('resolve_approval', { id, allow, message });
+export const setApprovalTimeout = (secs: number) => invoke('set_approval_timeout', { secs });
+export const getPolicy = () => invoke<Policy>('get_policy');
+export const setPolicy = (policy: Policy) => invoke('set_policy', { policy });
+
+export const getApiInfo = () => invoke<ApiInfo>('get_api_info');
+export const setApiEnabled = (enabled: boolean, port?: number) =>
+  invoke<ApiInfo>('set_api_enabled', { enabled, port });
+export const regenerateApiToken = () => invoke<ApiInfo>('regenerate_api_token');
+
+// Remote hosts
+export const getRemoteInfo = () => invoke<RemoteInfo>('get_remote_info');
+export const setRemoteListener = (enabled: boolean, bindAddress: string, port: number) =>
+  invoke<RemoteInfo>('set_remote_listener', { enabled, bindAddress, port });
+export const regenerateRemoteToken = () => invoke<RemoteInfo>('regenerate_remote_token');
+export const setRemoteSshHosts = (sshHosts: Record<string, string>) =>
+  invoke('set_remote_ssh_hosts', { sshHosts });
+export const setPathMappings = (mappings: PathMapping[]) =>
+  invoke('set_path_mappings', { mappings });
 
 export type { DiffType };
 
-export const openDiff = (projectDir: string, diffType: DiffType, baseBranch?: string) =>
-  invoke('open_diff', { projectDir, diffType, baseBranch });
+export const openDiff = (
+  projectDir: string,
+  diffType: DiffType,
+  baseBranch?: string,
+  host?: string,
+  sessionId?: string,
+  spec?: DiffSpec
+) => invoke('open_diff', { projectDir, diffType, baseBranch, host, sessionId, spec });
+
+export const listSessionTurns = (sessionId: string) =>
+  invoke<TurnSummary[]>('list_session_turns', { sessionId });
 
 // Setup commands
 export const getSetupStatus = () => invoke<SetupStatus>('get_setup_status');
@@ -40,6 +131,26 @@ export const onSettingsUpdated = (callback: (settings: Settings) => void): Promi
   return listen<Settings>('settings-updated', (event) => callback(event.payload));
 };
 
+export const onBudgetAlert = (callback: (alert: BudgetAlert) => void): Promise<UnlistenFn> => {
+  return listen<BudgetAlert>('budget-alert', (event) => callback(event.payload));
+};
+
+export const onSessionReminder = (
+  callback: (reminder: SessionReminder) => void
+): Promise<UnlistenFn> => {
+  return listen<SessionReminder>('session-reminder', (event) => callback(event.payload));
+};
+
+export const onDndDigest = (callback: (sessions: SessionInfo[]) => void): Promise<UnlistenFn> => {
+  return listen<SessionInfo[]>('dnd-digest', (event) => callback(event.payload));
+};
+
+export const onGitInfoUpdated = (
+  callback: (update: GitInfoUpdate) => void
+): Promise<UnlistenFn> => {
+  return listen<GitInfoUpdate>('git-info-updated', (event) => callback(event.payload));
+};
+
 export const onWindowFocus = (callback: () => void): Promise<UnlistenFn> => {
   return listen('tauri://focus', callback);
 };
@@ -1,4 +1,4 @@
-import type { SessionStatus, HookStatus } from '@/types';
+import type { DiffSpec, SessionInfo, SessionStatus, HookStatus } from '@/types';
 
 export const getStatusEmoji = (status: SessionStatus): string => {
   switch (status) {
@@ -10,18 +10,24 @@ export const getStatusEmoji = (status: SessionStatus): string => {
       return '✅';
     case 'Active':
       return '🟢';
+    case 'Disconnected':
+      return '🔌';
     default:
       return '📌';
   }
 };
 
-export const getStatusClass = (status: SessionStatus): 'waiting' | 'completed' | 'active' => {
+export const getStatusClass = (
+  status: SessionStatus
+): 'waiting' | 'completed' | 'active' | 'disconnected' => {
   switch (status) {
     case 'WaitingPermission':
     case 'WaitingInput':
       return 'waiting';
     case 'Completed':
       return 'completed';
+    case 'Disconnected':
+      return 'disconnected';
     case 'Active':
     default:
       return 'active';
@@ -35,6 +41,8 @@ export const allHooksConfigured = (hooks: HookStatus): boolean => {
     hooks.stop &&
     hooks.post_tool_use &&
     hooks.user_prompt_submit &&
+    hooks.pre_tool_use &&
+    hooks.permission_request &&
     hooks.notification_permission &&
     hooks.notification_idle
   );
@@ -68,3 +76,40 @@ export const formatRelativeTime = (isoTimestamp: string): string => {
   // For older dates, show date
   return date.toLocaleDateString('en-US', { month: 'short', day: 'numeric' });
 };
+
+// Format a token count compactly (e.g. 950, 45k, 1.2M)
+export const formatTokens = (tokens: number): string => {
+  if (tokens >= 1_000_000) return `${(tokens / 1_000_000).toFixed(1)}M`;
+  if (tokens >= 1_000) return `${Math.floor(tokens / 1_000)}k`;
+  return String(tokens);
+};
+
+// Alias if set, otherwise the project name
+export const sessionDisplayName = (session: SessionInfo): string =>
+  session.alias || session.project_name;
+
+export const isSessionSnoozed = (session: SessionInfo): boolean =>
+  !!session.snoozed_until && new Date(session.snoozed_until).getTime() > Date.now();
+
+// Muted or snoozed sessions raise no sounds or notifications
+export const isSessionSilenced = (session: SessionInfo): boolean =>
+  session.muted || isSessionSnoozed(session);
+
+// Parse a custom diff: "A B", "A...B", "A" (vs working tree), "stash@{n}" or "upstream",
+// optionally followed by "-- paths". Returns null if empty.
+export const parseDiffSpec = (input: string): DiffSpec | null => {
+  const [revs, pathPart = ''] = input.split(/\s+--(?:\s+|$)/, 2);
+  const words = revs.trim().split(/\s+/).filter(Boolean);
+  const paths = pathPart.trim().split(/\s+/).filter(Boolean);
+  if (words.length === 0) return null;
+
+  const [from, to] = words;
+  const stash = /^stash@\{(\d+)\}$/.exec(from);
+  if (stash) return { kind: 'stash', index: Number(stash[1]), paths };
+  if (from === 'upstream' || from === '@{upstream}' || from === '@{u}') {
+    return { kind: 'upstream', paths };
+  }
+  const mergeBase = /^(.+)\.\.\.(.+)$/.exec(from);
+  if (mergeBase) return { kind: 'merge_base', from: mergeBase[1], to: mergeBase[2], paths };
+  return { kind: 'revisions', from, to, paths };
+};
@@ -1,5 +1,10 @@
 // Session status matching Rust enum
-export type SessionStatus = 'Active' | 'WaitingPermission' | 'WaitingInput' | 'Completed';
+export type SessionStatus =
+  | 'Active'
+  | 'WaitingPermission'
+  | 'WaitingInput'
+  | 'Completed'
+  | 'Disconnected';
 
 // Notification type matching Rust enum (snake_case from serde)
 export type NotificationType = 'permission_prompt' | 'idle_prompt' | 'other';
@@ -12,15 +17,62 @@ export type EventType =
   | 'stop'
   | 'post_tool_use'
   | 'user_prompt_submit'
+  | 'permission_request'
+  | 'pre_tool_use'
+  | 'approval_decision'
+  | 'disconnected'
   | 'unknown';
 
+// Token usage reported in the Claude Code transcript
+export interface TokenUsage {
+  input_tokens: number;
+  output_tokens: number;
+  cache_creation_input_tokens: number;
+  cache_read_input_tokens: number;
+}
+
 export interface SessionInfo {
+  session_id: string;
   project_name: string;
   project_dir: string;
   status: SessionStatus;
   last_event: string;
   waiting_for: string;
   tmux_pane: string;
+  claude_pid: number;
+  host: string;
+  // Docker/Podman container the session runs in ('' on the host)
+  container_id: string;
+  // Address of the remote hook reporting this session over TCP ('' for local hooks)
+  remote_peer: string;
+  // Host path of project_dir when a path mapping applies
+  local_dir: string;
+  disconnected_at: string;
+  transcript_path: string;
+  last_assistant_message: string;
+  model: string;
+  token_usage: TokenUsage;
+  context_tokens: number;
+  cost_usd: number;
+  // Per-session prefs, kept across restarts and resumes
+  alias: string;
+  pinned: boolean;
+  muted: boolean;
+  snoozed_until: string;
+  repo: RepoLocation | null;
+  // HEAD and working tree snapshot taken when the session started
+  start_head: string;
+  start_snapshot: string;
+  turns: number;
+}
+
+// Git repository and worktree of a session's project dir
+export interface RepoLocation {
+  common_dir: string;
+  name: string;
+  worktree: string;
+  branch: string;
+  is_main_worktree: boolean;
 }
 
 export interface EventInfo {
@@ -34,11 +86,142 @@ export interface EventInfo {
   notification_type: NotificationType;
   tool_name: string;
   tmux_pane: string;
+  claude_pid: number;
+  host: string;
+  container_id: string;
+  remote_peer?: string;
+  transcript_path: string;
+  tool_input?: Record<string, unknown>;
+}
+
+// A tool call waiting for Allow/Deny from the dashboard
+export interface ApprovalRequest {
+  id: string;
+  session_id: string;
+  project_name: string;
+  project_dir: string;
+  hook_event: EventType;
+  tool_name: string;
+  tool_input: Record<string, unknown> | null;
+  requested_at: string;
+}
+
+// Auto-approval rule; unset globs match anything, the first matching rule wins
+export interface PolicyRule {
+  action: 'allow' | 'deny' | 'ask';
+  tool?: string;
+  command?: string;
+  path?: string;
+  project_dir?: string;
+  message?: string;
+}
+
+export interface Policy {
+  rules: PolicyRule[];
+}
+
+// A span of time a session spent in one status (end is null while ongoing)
+export interface StatusInterval {
+  status: SessionStatus;
+  start: string;
+  end: string | null;
+}
+
+// Bounded per-session history of events and status intervals
+export interface SessionTimeline {
+  events: EventInfo[];
+  intervals: StatusInterval[];
+}
+
+// Sessions sharing the same project directory
+export interface ProjectGroup {
+  project_name: string;
+  project_dir: string;
+  session_ids: string[];
+}
+
+// Sessions in one worktree of a repository
+export interface WorktreeGroup {
+  path: string;
+  branch: string;
+  session_ids: string[];
+}
+
+// Sessions sharing a git repository; common_dir is empty for directories outside git
+export interface RepoGroup {
+  // Remote host of the sessions ('' for this machine)
+  host: string;
+  name: string;
+  common_dir: string;
+  worktrees: WorktreeGroup[];
 }
 
 export interface DashboardData {
   sessions: SessionInfo[];
+  projects: ProjectGroup[];
+  repos: RepoGroup[];
   events: EventInfo[];
+  approvals: ApprovalRequest[];
+  dnd_active: boolean;
+}
+
+// Filter for event history queries (all set fields must match)
+export interface EventFilter {
+  project_dir?: string;
+  session_id?: string;
+  event_types?: EventType[];
+  since?: string;
+  until?: string;
+}
+
+// One page of event history results, newest first
+export interface EventPage {
+  total: number;
+  events: EventInfo[];
+}
+
+// Seconds spent in each session status
+export interface StatusDurations {
+  active_secs: number;
+  waiting_permission_secs: number;
+  waiting_input_secs: number;
+  completed_secs: number;
+  disconnected_secs: number;
+}
+
+// Latency from a permission prompt to the next tool use / prompt submit
+export interface PermissionLatency {
+  count: number;
+  total_secs: number;
+  avg_secs: number;
+  max_secs: number;
+}
+
+export interface SessionStats {
+  session_id: string;
+  project_name: string;
+  project_dir: string;
+  first_event: string;
+  last_event: string;
+  time_in_status: StatusDurations;
+  permission_latency: PermissionLatency;
+}
+
+// Per-project aggregate for a day (YYYY-MM-DD) or ISO week (YYYY-Www)
+export interface PeriodStats {
+  period: string;
+  project_name: string;
+  project_dir: string;
+  time_in_status: StatusDurations;
+  permission_latency: PermissionLatency;
+}
+
+export interface StatsReport {
+  since: string;
+  until: string;
+  sessions: SessionStats[];
+  daily: PeriodStats[];
+  weekly: PeriodStats[];
 }
 
 export interface Settings {
@@ -46,6 +229,146 @@ export interface Settings {
   opacity_active: number;
   opacity_inactive: number;
   sound_enabled: boolean;
+  history_retention_days: number;
+  auto_archive_disconnected_minutes: number;
+  model_prices: ModelPrice[];
+  daily_budget_usd: number;
+  session_budget_usd: number;
+  api_enabled: boolean;
+  api_port: number;
+  approval_timeout_secs: number;
+  notifications: NotificationSettings;
+  webhooks: WebhookConfig[];
+  reminders: ReminderSettings;
+  dnd: DndSettings;
+  remote: RemoteSettings;
+  path_mappings: PathMapping[];
+}
+
+// Container path prefix -> host path; container matches a container id prefix or hostname
+export interface PathMapping {
+  container: string;
+  container_prefix: string;
+  host_prefix: string;
+}
+
+// Hooks on other machines; ssh_hosts maps a reported hostname to an SSH destination
+export interface RemoteSettings {
+  enabled: boolean;
+  bind_address: string;
+  port: number;
+  ssh_hosts: Record<string, string>;
+}
+
+export type Weekday = 'Mon' | 'Tue' | 'Wed' | 'Thu' | 'Fri' | 'Sat' | 'Sun';
+
+// Recurring quiet period in local time ("HH:MM"); an end before the start runs overnight
+export interface QuietHours {
+  days: Weekday[];
+  start: string;
+  end: string;
+}
+
+// Do Not Disturb schedule; paused_until is the end of a manual pause (RFC 3339)
+export interface DndSettings {
+  schedule: QuietHours[];
+  paused_until: string | null;
+}
+
+// Minutes spent waiting after which reminders fire; project_intervals is keyed by project dir
+export interface ReminderSettings {
+  enabled: boolean;
+  intervals_minutes: number[];
+  project_intervals: Record<string, number[]>;
+}
+
+// Payload of the 'session-reminder' event
+export interface SessionReminder {
+  session_id: string;
+  project_name: string;
+  waiting_for: string;
+  level: number;
+  waiting_minutes: number;
+}
+
+// Outgoing webhook; templates are keyed by status name or 'default'
+export interface WebhookConfig {
+  url: string;
+  enabled: boolean;
+  statuses: SessionStatus[];
+  headers: Record<string, string>;
+  templates: Record<string, string>;
+}
+
+export type NotificationAction = 'open_dashboard' | 'jump_to_pane' | 'snooze';
+
+export interface StatusNotification {
+  enabled: boolean;
+  actions: NotificationAction[];
+}
+
+// Desktop notifications raised when a session enters each status
+export interface NotificationSettings {
+  waiting_permission: StatusNotification;
+  waiting_input: StatusNotification;
+  completed: StatusNotification;
+  snooze_minutes: number;
+}
+
+export interface ApiInfo {
+  enabled: boolean;
+  running: boolean;
+  port: number;
+  token: string;
+  base_url: string;
+}
+
+export interface RemoteInfo {
+  enabled: boolean;
+  running: boolean;
+  bind_address: string;
+  port: number;
+  token: string;
+  hostname: string;
+}
+
+// Prices in USD per million tokens, matched by longest model id prefix
+export interface ModelPrice {
+  model_prefix: string;
+  input_per_mtok: number;
+  output_per_mtok: number;
+  cache_write_per_mtok: number;
+  cache_read_per_mtok: number;
+}
+
+export interface DayCost {
+  total_usd: number;
+  by_project: Record<string, number>;
+  budget_alerted: boolean;
+}
+
+export interface SessionCost {
+  project_name: string;
+  project_dir: string;
+  total_usd: number;
+  usage: TokenUsage;
+  counted_through: string;
+  budget_alerted: boolean;
+}
+
+export interface CostSummary {
+  today_usd: number;
+  daily_budget_usd: number;
+  session_budget_usd: number;
+  days: Record<string, DayCost>;
+  sessions: Record<string, SessionCost>;
+}
+
+export interface BudgetAlert {
+  scope: 'daily' | 'session';
+  label: string;
+  spent_usd: number;
+  budget_usd: number;
 }
 
 export interface GitInfo {
@@ -56,10 +379,65 @@ export interface GitInfo {
   has_unstaged_changes: boolean;
   has_staged_changes: boolean;
   is_git_repo: boolean;
+  upstream: string;
+  ahead: number;
+  behind: number;
+  ahead_default: number;
+  behind_default: number;
+  stash_count: number;
+  operation: GitOperation | null;
+  unmerged_paths: string[];
+  files: FileStatus[];
+}
+
+// Payload of the git-info-updated event (host is empty for local repositories)
+export interface GitInfoUpdate {
+  project_dir: string;
+  host: string;
+  info: GitInfo;
+}
+
+export type GitOperation = 'merge' | 'rebase' | 'cherry_pick' | 'revert';
+
+// Status letters from `git status --porcelain=v2` ('.' = unchanged, '?' = untracked)
+export interface FileStatus {
+  path: string;
+  orig_path?: string;
+  index: string;
+  worktree: string;
+  insertions: number | null;
+  deletions: number | null;
 }
 
 // Diff type for difit integration
-export type DiffType = 'unstaged' | 'staged' | 'commit' | 'branch';
+export type DiffType =
+  | 'unstaged'
+  | 'staged'
+  | 'commit'
+  | 'branch'
+  | 'since_session_start'
+  | `turn:${number}`
+  | 'custom';
+
+// What a custom diff compares (to omitted = working tree)
+export type DiffTarget =
+  | { kind: 'revisions'; from: string; to?: string }
+  | { kind: 'merge_base'; from: string; to: string }
+  | { kind: 'stash'; index: number }
+  | { kind: 'upstream' };
+
+export type DiffSpec = DiffTarget & { paths: string[] };
+
+// A checkpointed turn (prompt to stop) of a session
+export interface TurnSummary {
+  turn: number;
+  prompt_time: string;
+  // Empty while the turn is in progress
+  end_time: string;
+  files_changed: number;
+  insertions: number;
+  deletions: number;
+}
 
 // Tmux pane information
 export interface TmuxPane {
@@ -84,6 +462,8 @@ export interface HookStatus {
   stop: boolean;
   post_tool_use: boolean;
   user_prompt_submit: boolean;
+  pre_tool_use: boolean;
+  permission_request: boolean;
   notification_permission: boolean;
   notification_idle: boolean;
 }